@@ -0,0 +1,58 @@
+/* C API of treasure-search, built with
+ * cargo rustc --lib --release --features ffi --crate-type cdylib
+ */
+#ifndef TREASURE_SEARCH_H
+#define TREASURE_SEARCH_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct TsEngine TsEngine;
+
+/* Statistics of the last stepped generation */
+typedef struct TsStats {
+    uint32_t generation;
+    uint64_t evaluations;
+    double best_fitness;
+    double mean_fitness;
+    double worst_fitness;
+    double diversity;
+    /* Of the best chromosome so far */
+    double best_so_far_fitness;
+    uint32_t best_so_far_treasures;
+    uint32_t best_so_far_steps;
+    bool solved;
+} TsStats;
+
+/* JSON object, every key is optional:
+ * map (text form), fitness ("steps" or "treasures"), subjects, mutation_probability,
+ * selection ("roulette" or "tournament"), elitism, instruction_set ("classic" or "extended"),
+ * iteration_limit, memory_size, seed.
+ * NULL when the configuration is invalid, ts_last_error() tells why. */
+TsEngine *ts_engine_new(const char *config_json);
+
+/* Steps at most the given number of generations, fewer once every treasure is collected.
+ * Returns the number of stepped generations, -1 for a NULL engine. */
+int64_t ts_engine_step(TsEngine *engine, uint32_t generations);
+
+/* Copies at most capacity bytes of the best genome so far and returns its length, 0 before the first generation */
+size_t ts_engine_best_genome(const TsEngine *engine, uint8_t *buffer, size_t capacity);
+
+/* False for a NULL engine or before the first generation */
+bool ts_engine_stats(const TsEngine *engine, TsStats *stats);
+
+void ts_engine_free(TsEngine *engine);
+
+/* Valid until the next failing call on the same thread, NULL when nothing failed */
+const char *ts_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
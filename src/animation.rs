@@ -0,0 +1,91 @@
+#![cfg_attr(not(feature = "export"), allow(dead_code))]
+
+use std::path::Path;
+
+use crate::core::{AREA_TILE_PLAYER, AREA_TILE_TREASURE};
+use crate::map::GameArea;
+
+const CELL_SIZE: usize = 16;
+// In hundredths of a second
+const FRAME_DELAY: u16 = 15;
+const LAST_FRAME_DELAY: u16 = 150;
+
+const COLOR_BACKGROUND: u8 = 0;
+const COLOR_GRID: u8 = 1;
+const COLOR_TREASURE: u8 = 2;
+const COLOR_PATH: u8 = 3;
+const COLOR_PLAYER: u8 = 4;
+const COLOR_START: u8 = 5;
+const PALETTE: [u8; 18] = [
+    0xf4, 0xf4, 0xf4,
+    0xd0, 0xd0, 0xd0,
+    0xf2, 0xc1, 0x2e,
+    0x9d, 0xbd, 0xf5,
+    0x2f, 0xa8, 0x4f,
+    0x1d, 0x5e, 0x2f,
+];
+
+// Animation of the player walking the trajectory, one frame per move, collected treasures disappear
+#[cfg(feature = "export")]
+pub fn write_path(path: &Path, game_area: &GameArea, trajectory: &[(usize, usize)]) -> Result<(), String> {
+    use std::borrow::Cow;
+    use std::fs::File;
+
+    let width = game_area.width() * CELL_SIZE;
+    let height = game_area.height() * CELL_SIZE;
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &PALETTE).map_err(|e| e.to_string())?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+
+    for moves in 1..=trajectory.len().max(1) {
+        let walked = &trajectory[..moves.min(trajectory.len())];
+        let mut frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: if moves >= trajectory.len() { LAST_FRAME_DELAY } else { FRAME_DELAY },
+            buffer: Cow::Owned(draw(game_area, walked)),
+            ..gif::Frame::default()
+        };
+        frame.make_lzw_pre_encoded();
+        encoder.write_lzw_pre_encoded_frame(&frame).map_err(|e| e.to_string())?;
+    }
+    return Ok(());
+}
+
+// Without the feature there is no GIF encoder
+#[cfg(not(feature = "export"))]
+pub fn write_path(_path: &Path, _game_area: &GameArea, _trajectory: &[(usize, usize)]) -> Result<(), String> {
+    return Err(String::from("the animation requires the \"export\" feature"));
+}
+
+fn draw(game_area: &GameArea, walked: &[(usize, usize)]) -> Vec<u8> {
+    let width = game_area.width() * CELL_SIZE;
+    let mut pixels: Vec<u8> = vec![COLOR_BACKGROUND; width * game_area.height() * CELL_SIZE];
+    let position = walked.last().copied();
+    for (y, row) in game_area.rows().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let visited = walked.contains(&(x, y));
+            let color = if position == Some((x, y)) {
+                COLOR_PLAYER
+            } else if tile == AREA_TILE_PLAYER {
+                COLOR_START
+            } else if visited {
+                COLOR_PATH
+            } else if tile == AREA_TILE_TREASURE {
+                COLOR_TREASURE
+            } else {
+                COLOR_BACKGROUND
+            };
+            for py in 0..CELL_SIZE {
+                for px in 0..CELL_SIZE {
+                    let border = px == 0 || py == 0;
+                    // Tiles are drawn inset so the grid stays visible
+                    let inset = (2..CELL_SIZE - 1).contains(&px) && (2..CELL_SIZE - 1).contains(&py);
+                    let pixel = if border { COLOR_GRID } else if inset { color } else { COLOR_BACKGROUND };
+                    pixels[(y * CELL_SIZE + py) * width + x * CELL_SIZE + px] = pixel;
+                }
+            }
+        }
+    }
+    return pixels;
+}
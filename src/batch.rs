@@ -0,0 +1,256 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+use serde::Serialize;
+
+use crate::cli;
+use crate::error::Error;
+use crate::stats;
+
+// Options of one run, passed to a child process after the run subcommand
+pub struct Job {
+    pub name: String,
+    // Name of the job in the job file, shared by all of its runs
+    pub config: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct JobResult {
+    pub job: String,
+    pub exit_code: Option<i32>,
+    // JSON summary of the run, see report::RunSummary
+    pub summary: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl JobResult {
+    pub fn solved(&self) -> bool {
+        return self.exit_code == Some(0);
+    }
+
+    // Finished without a full solution, anything else than that is a failure
+    pub fn unsolved(&self) -> bool {
+        return self.exit_code == Some(1);
+    }
+}
+
+pub fn load_jobs(path: &Path) -> Result<Vec<Job>, Error> {
+    let text = std::fs::read_to_string(path)?;
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| Error::Parse(e.to_string()))?;
+    let mut defaults: Vec<String> = Vec::new();
+    if let Some(shared) = table.get("defaults") {
+        let shared = shared.as_table().ok_or_else(|| Error::InvalidConfig(String::from("defaults has to be a table")))?;
+        for (key, value) in shared {
+            cli::push_option(&mut defaults, key, value)?;
+        }
+    }
+    let entries = match table.get("job") {
+        Some(toml::Value::Array(entries)) => entries,
+        _ => return Err(Error::InvalidConfig(String::from("the file has no [[job]] tables"))),
+    };
+    let mut jobs: Vec<Job> = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let entry = entry.as_table().ok_or_else(|| Error::InvalidConfig(String::from("every job has to be a table")))?;
+        let mut args = defaults.clone();
+        let mut name = format!("job {}", index + 1);
+        for (key, value) in entry {
+            match (key.as_str(), value) {
+                ("name", toml::Value::String(value)) => name = value.clone(),
+                _ => cli::push_option(&mut args, key, value)?,
+            }
+        }
+        jobs.push(Job { config: name.clone(), name, args });
+    }
+    return Ok(jobs);
+}
+
+// Every job repeated with consecutive seeds starting at its own seed (0 unless given)
+pub fn repeat_jobs(jobs: Vec<Job>, runs: u32) -> Vec<Job> {
+    if runs <= 1 {
+        return jobs;
+    }
+    let mut repeated: Vec<Job> = Vec::with_capacity(jobs.len() * runs as usize);
+    for job in jobs {
+        let seed: u64 = job.args.iter().rposition(|arg| arg == "--seed")
+            .and_then(|i| job.args.get(i + 1))
+            .and_then(|seed| seed.parse::<u64>().ok())
+            .unwrap_or(0);
+        for run in 0..runs {
+            let mut args = job.args.clone();
+            args.extend([String::from("--seed"), (seed + run as u64).to_string()]);
+            repeated.push(Job { name: format!("{} run {}", job.name, run + 1), config: job.config.clone(), args });
+        }
+    }
+    return repeated;
+}
+
+// One row per job of the job file aggregating all of its runs
+pub fn print_comparison(jobs: &[Job], results: &[JobResult]) {
+    let mut configs: Vec<&str> = Vec::new();
+    for job in jobs {
+        if !configs.contains(&job.config.as_str()) {
+            configs.push(&job.config);
+        }
+    }
+    let width = configs.iter().map(|config| config.chars().count()).max().unwrap_or(0).max(3);
+    eprintln!("{:<width$}  {:>4}  {:>7}  {:>17}  {:>19}  {:>9}", "Job", "Runs", "Solved", "Mean best fitness",
+              "Mean gen. to solve", "Mean time", width = width);
+    for config in configs {
+        let runs: Vec<&JobResult> = jobs.iter().zip(results).filter(|(job, _)| job.config == config).map(|(_, result)| result).collect();
+        let field = |result: &JobResult, pointer: &str| result.summary.as_ref().and_then(|summary| summary.pointer(pointer)?.as_f64());
+        let mean = |values: Vec<f64>| if values.is_empty() {
+            String::from("-")
+        } else {
+            format!("{:.4}", values.iter().sum::<f64>() / values.len() as f64)
+        };
+        let solved = runs.iter().filter(|result| result.solved()).count();
+        let fitness = mean(runs.iter().filter_map(|result| field(result, "/best/fitness")).collect());
+        let generations = mean(runs.iter().filter(|result| result.solved()).filter_map(|result| field(result, "/generations")).collect());
+        let time = mean(runs.iter().filter_map(|result| field(result, "/wall_time_seconds")).collect());
+        eprintln!("{:<width$}  {:>4}  {:>6.1}%  {:>17}  {:>19}  {:>8}s", config, runs.len(),
+                  100.0 * solved as f64 / runs.len() as f64, fitness, generations, time, width = width);
+    }
+}
+
+// The value at the JSON pointer of every run of the job, infinite generations for the runs without a solution
+fn samples(jobs: &[Job], results: &[JobResult], config: &str, pointer: &str) -> Vec<f64> {
+    return jobs.iter().zip(results).filter(|(job, _)| job.config == config).filter_map(|(_, result)| {
+        let value = result.summary.as_ref()?.pointer(pointer)?.as_f64()?;
+        if pointer == "/generations" && !result.solved() {
+            return Some(f64::INFINITY);
+        }
+        Some(value)
+    }).collect();
+}
+
+// Mann-Whitney U test of every pair of jobs of the job file on the best fitness and the generations to a solution,
+// runs without one count as slower than every solved run. Runs which failed are left out.
+pub fn print_significance(jobs: &[Job], results: &[JobResult]) {
+    let mut configs: Vec<&str> = Vec::new();
+    for job in jobs {
+        if !configs.contains(&job.config.as_str()) {
+            configs.push(&job.config);
+        }
+    }
+    if configs.len() < 2 {
+        return;
+    }
+    let test = |first: &[f64], second: &[f64], lower_is_better: bool| -> String {
+        return match stats::mann_whitney(first, second) {
+            Some(test) => {
+                let effect_size = if lower_is_better { -test.effect_size } else { test.effect_size };
+                format!("U {:.1}, p {:.4}, r {:+.2}", test.u, test.p_value, effect_size)
+            }
+            None => String::from("-"),
+        };
+    };
+    eprintln!("Mann-Whitney U, two-sided, effect size r from -1 to 1 favouring the first job when positive:");
+    for (i, first) in configs.iter().enumerate() {
+        for second in &configs[i + 1..] {
+            let fitness = test(&samples(jobs, results, first, "/best/fitness"), &samples(jobs, results, second, "/best/fitness"), false);
+            let generations = test(&samples(jobs, results, first, "/generations"), &samples(jobs, results, second, "/generations"), true);
+            eprintln!("  {} vs {}: best fitness {}; generations to solve {}", first, second, fitness, generations);
+        }
+    }
+}
+
+// Jobs are taken in order by a fixed number of workers, records are written as the jobs finish.
+// The results are returned in the order of the jobs.
+pub fn run_jobs(jobs: &[Job], parallelism: usize, output: &mut dyn Write) -> Result<Vec<JobResult>, String> {
+    let executable = std::env::current_exe().map_err(|e| e.to_string())?;
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel::<(usize, JobResult)>();
+    let mut finished: Vec<Option<JobResult>> = (0..jobs.len()).map(|_| None).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..parallelism.min(jobs.len()) {
+            let sender = sender.clone();
+            let (next, executable) = (&next, &executable);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(job) = jobs.get(index) else {
+                    return;
+                };
+                if sender.send((index, run_job(executable, job, index))).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(sender);
+        for (count, (index, result)) in receiver.iter().enumerate() {
+            let status = if result.solved() { "solved" } else if result.unsolved() { "unsolved" } else { "failed" };
+            eprintln!("[{}/{}] {}: {}", count + 1, jobs.len(), result.job, status);
+            if let Ok(record) = serde_json::to_string(&result) {
+                writeln!(output, "{}", record).ok();
+            }
+            finished[index] = Some(result);
+        }
+    });
+    return Ok(finished.into_iter().flatten().collect());
+}
+
+// Where the result records go, the standard output without a file
+pub fn results_output(results: Option<&Path>) -> Result<Box<dyn Write>, String> {
+    return match results {
+        Some(path) => Ok(Box::new(std::fs::File::create(path).map_err(|e| e.to_string())?)),
+        None => Ok(Box::new(std::io::stdout())),
+    };
+}
+
+pub fn run_job(executable: &PathBuf, job: &Job, index: usize) -> JobResult {
+    let summary_path = std::env::temp_dir().join(format!("treasure-search-{}-{}.json", std::process::id(), index));
+    let child = Command::new(executable)
+        .arg("run")
+        .args(&job.args)
+        .args(["--quiet", "--non-interactive", "--output-file"])
+        .arg(&summary_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output();
+    let output = match child {
+        Ok(output) => output,
+        Err(e) => return JobResult { job: job.name.clone(), exit_code: None, summary: None, error: Some(e.to_string()) },
+    };
+    let summary = std::fs::read_to_string(&summary_path).ok().and_then(|json| serde_json::from_str(&json).ok());
+    std::fs::remove_file(&summary_path).ok();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    return JobResult {
+        job: job.name.clone(),
+        exit_code: output.status.code(),
+        summary,
+        error: Some(stderr).filter(|stderr| !stderr.is_empty()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(config: &str) -> Job {
+        return Job { name: String::from(config), config: String::from(config), args: Vec::new() };
+    }
+
+    fn result(exit_code: Option<i32>, summary: Option<serde_json::Value>) -> JobResult {
+        return JobResult { job: String::new(), exit_code, summary, error: None };
+    }
+
+    #[test]
+    fn unsolved_runs_take_infinite_generations() {
+        let jobs = [job("a"), job("a"), job("b"), job("a")];
+        let results = [
+            result(Some(0), Some(serde_json::json!({ "generations": 12, "best": { "fitness": 3.5 } }))),
+            result(Some(1), Some(serde_json::json!({ "generations": 40, "best": { "fitness": 1.5 } }))),
+            result(Some(0), Some(serde_json::json!({ "generations": 7, "best": { "fitness": 9.0 } }))),
+            // Failed without a summary
+            result(None, None),
+        ];
+        assert_eq!(samples(&jobs, &results, "a", "/generations"), [12.0, f64::INFINITY]);
+        assert_eq!(samples(&jobs, &results, "a", "/best/fitness"), [3.5, 1.5]);
+        assert_eq!(samples(&jobs, &results, "b", "/generations"), [7.0]);
+        assert!(samples(&jobs, &results, "c", "/generations").is_empty());
+    }
+}
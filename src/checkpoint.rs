@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Chromosome, Genes};
+use crate::engine::Snapshot;
+use crate::genome_file;
+use crate::stats::GenerationStats;
+use crate::vm::InstructionSet;
+
+// Everything the search needs to continue exactly where it stopped, taken before a generation is evaluated
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Checkpoint {
+    pub seed: u64,
+    pub map_hash: String,
+    pub snapshot: Snapshot,
+}
+
+// Written next to the target first so a crash while saving keeps the previous checkpoint
+pub fn save(path: &Path, checkpoint: &Checkpoint) -> Result<(), String> {
+    return write(path, checkpoint);
+}
+
+pub fn load(path: &Path) -> Result<Checkpoint, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    return serde_json::from_str(&json).map_err(|e| e.to_string());
+}
+
+// Evaluated population of a run from the best, another run can start from its genomes
+#[derive(Serialize, Debug)]
+pub struct SavedPopulation<'a> {
+    pub seed: u64,
+    pub map_hash: String,
+    pub generation: u32,
+    pub stats: GenerationStats,
+    pub chromosomes: &'a [Chromosome],
+}
+
+// Only the genomes are read back, the file may be curated by hand down to them
+#[derive(Deserialize, Debug)]
+pub struct LoadedPopulation {
+    #[serde(default)]
+    pub map_hash: Option<String>,
+    // Only known for genome files
+    #[serde(skip)]
+    pub instruction_set: Option<InstructionSet>,
+    chromosomes: Vec<LoadedGenome>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LoadedGenome {
+    genes: Genes,
+}
+
+impl LoadedPopulation {
+    pub fn into_genomes(self) -> Vec<Genes> {
+        return self.chromosomes.into_iter().map(|chromosome| chromosome.genes).collect();
+    }
+}
+
+pub fn save_population(path: &Path, population: &SavedPopulation) -> Result<(), String> {
+    return write(path, population);
+}
+
+// A genome file is a population of one
+pub fn load_population(path: &Path) -> Result<LoadedPopulation, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if genome_file::is_genome_file(&bytes) {
+        let (header, program) = genome_file::decode(&bytes).map_err(|e| e.to_string())?;
+        let genes = Genes::try_from_slice(&program).ok_or("the genome is too long")?;
+        return Ok(LoadedPopulation {
+            map_hash: header.map_fingerprint.map(|fingerprint| format!("{:016x}", fingerprint)),
+            instruction_set: Some(header.instruction_set),
+            chromosomes: vec![LoadedGenome { genes }],
+        });
+    }
+    return serde_json::from_slice(&bytes).map_err(|e| e.to_string());
+}
+
+fn write<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, json).map_err(|e| e.to_string())?;
+    return std::fs::rename(&temporary, path).map_err(|e| e.to_string());
+}
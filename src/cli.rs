@@ -0,0 +1,817 @@
+use std::path::{Path, PathBuf};
+
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+
+use crate::core::{Comparison, Selection, StepAlphabet};
+use crate::ensemble::{self, Aggregation};
+use crate::error::Error;
+use crate::fitness;
+use crate::encoding::GenomeFormat;
+use crate::render::ColorMode;
+use crate::scaling::FitnessScaling;
+use crate::vm::{self, CollisionPolicy, InstructionSet};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Evolves virtual machine programs which collect every treasure on a 2D map")]
+// Values from the configuration file are passed first, later occurrences win
+#[command(args_override_self = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// TOML file with default values of the options, top-level keys for the shared options
+    /// and a table named after the subcommand for its own, e.g. `[run] subjects = 200`
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Map file, one line per row: '.' empty tile, 'P' player, 'T' treasure (built-in 7x7 map unless given).
+    /// Repeat it to evaluate on several maps with as many treasures, combined as --aggregate says
+    #[arg(long, global = true, value_name = "FILE")]
+    pub map: Vec<PathBuf>,
+
+    /// Instruction set: classic (0) or extended (1)
+    #[arg(short = 'i', long, global = true, default_value = "classic", value_parser = parse_instruction_set)]
+    pub instruction_set: InstructionSet,
+
+    /// Seed, random unless given (replay, profile and bench default to 0)
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// Validate the options and the map, print the resolved configuration and exit
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Print only the final result
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print every improvement of the best solution, twice for statistics of every generation
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Format of the log enabled with RUST_LOG: text or json
+    #[arg(long, global = true, default_value = "text", value_parser = ["text", "json"])]
+    pub log_format: String,
+
+    /// Colored map output: auto, always or never
+    #[arg(long, global = true, default_value = "auto", value_parser = parse_color)]
+    pub color: ColorMode,
+
+    /// Direction labels of steps, disassembly and exports: HPDL, UDLR, NSEW, arrows or four glyphs for up, right, down, left
+    #[arg(long, visible_alias = "direction-labels", global = true, default_value = "hpdl", value_parser = parse_glyphs)]
+    pub glyphs: StepAlphabet,
+
+    /// Formats the genome of a solution is printed in, comma separated: bytes, hex, base64, asm or moves
+    #[arg(long, global = true, default_value = "bytes", value_delimiter = ',', value_parser = parse_genome_format)]
+    pub genome_format: Vec<GenomeFormat>,
+
+    /// Chromosome ordering: fitness or lexicographic (implies rank scaling)
+    #[arg(long, global = true, default_value = "fitness", value_parser = parse_comparison)]
+    pub compare: Comparison,
+
+    /// Fitness function
+    #[arg(long, global = true, default_value = "steps", value_parser = fitness::FITNESS_FUNCTIONS)]
+    pub fitness: String,
+
+    /// Penalty per step of the steps fitness, scaled by map area unless given
+    #[arg(long, global = true)]
+    pub step_penalty: Option<f64>,
+
+    /// Allow negative fitness
+    #[arg(long, global = true)]
+    pub no_clamp: bool,
+
+    /// Rhai expression or @file computing the fitness (variables: treasures, all_treasures, steps,
+    /// iterations, iteration_limit, energy (iterations left), complexity, memory_size)
+    #[arg(long, global = true, value_name = "EXPRESSION|@FILE")]
+    pub fitness_script: Option<String>,
+
+    /// Weight of the bonus for ending close to an uncollected treasure
+    #[arg(long, global = true, value_name = "WEIGHT")]
+    pub distance_shaping: Option<f64>,
+
+    /// Weight of the credit for passing close to uncollected treasures
+    #[arg(long, global = true, value_name = "WEIGHT")]
+    pub proximity_credit: Option<f64>,
+
+    /// Weight of the bonus for full solutions using fewer iterations
+    #[arg(long, global = true, value_name = "WEIGHT")]
+    pub iteration_bonus: Option<f64>,
+
+    /// Weight of the penalty for executing more distinct instructions
+    #[arg(long, global = true, value_name = "WEIGHT")]
+    pub parsimony: Option<f64>,
+
+    /// Additional start positions every chromosome is evaluated from on every map, "x,y;x,y;..."
+    #[arg(long, global = true, value_parser = parse_starts)]
+    pub starts: Option<Starts>,
+
+    /// Last 4 memory cells hold x, y, remaining treasures and the tile under the player
+    #[arg(long, global = true)]
+    pub inputs: bool,
+
+    /// Maximum number of moves, unlimited unless given
+    #[arg(long, global = true)]
+    pub max_moves: Option<u32>,
+
+    /// Iteration limit of the virtual machine
+    #[arg(long, global = true, default_value_t = vm::DEFAULT_ITERATION_LIMIT)]
+    pub iterations: u32,
+
+    /// Memory size of the virtual machine in bytes
+    #[arg(long, global = true, default_value_t = vm::MAX_MEMORY_SIZE, value_parser = parse_memory_size)]
+    pub memory_size: usize,
+
+    /// Moves leaving the map: terminate, block or wrap
+    #[arg(long, global = true, default_value = "terminate", value_parser = parse_collision)]
+    pub collision: CollisionPolicy,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Evolve a program collecting every treasure
+    Run(Box<RunArgs>),
+    /// Run a genome once and print its path
+    Replay(ReplayArgs),
+    /// Walk the map yourself with the arrow keys, scored with the same fitness function
+    Play(PlayArgs),
+    /// Generate a random map
+    Mapgen(MapgenArgs),
+    /// Convert the trail of an artificial ant benchmark (Santa Fe, Los Altos Hills) to a map
+    ImportTrail(ImportTrailArgs),
+    /// Shortest tour collecting every treasure and the fitness landscape of random genomes
+    Analyze(AnalyzeArgs),
+    /// Measure evaluations per second of random genomes
+    Bench(BenchArgs),
+    /// Time every instruction of a genome
+    Profile(ProfileArgs),
+    /// Fill an archive of the best programs for every path length and map coverage
+    MapElites(MapElitesArgs),
+    /// Evolve a small population explaining every phase of every generation
+    Teach(TeachArgs),
+    /// Run every job of a TOML job file, each in its own process
+    Batch(BatchArgs),
+    /// Run named configurations on the same maps and seeds for the same evaluation budget and rank them
+    Tournament(TournamentArgs),
+    /// Evaluate genomes for runs on other machines started with --workers
+    Worker(WorkerArgs),
+    /// Run the search of a manifest written with `run --manifest` again and check that it ends the same way
+    Reproduce(ReproduceArgs),
+    /// Print the shell completion script: bash, zsh, fish, elvish or powershell
+    #[command(hide = true)]
+    Completions(CompletionsArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Number of chromosomes in every generation
+    #[arg(short = 'n', long, default_value_t = 100, value_parser = parse_subjects)]
+    pub subjects: usize,
+
+    /// Generation after which the best solution so far is reported
+    #[arg(short = 'g', long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+    pub generations: u32,
+
+    /// Wall-clock budget in seconds, the search ends at whichever of the budgets is spent first
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    pub max_seconds: Option<f64>,
+
+    /// Evaluation budget, e.g. to compare configurations on equal terms; the last generation evaluates only the
+    /// chromosomes it leaves room for
+    #[arg(long, value_name = "EVALUATIONS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_evaluations: Option<u64>,
+
+    /// Threads evaluating the population, 0 for one per core; the result does not depend on it (needs the "parallel" feature)
+    #[arg(long, default_value_t = 1)]
+    pub threads: usize,
+
+    /// Programs run together in lockstep, faster on large populations; the result does not depend on it
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub batch_size: u32,
+
+    /// Once a full solution is found, give up evaluations as soon as they cannot beat it any more (e.g. too many steps);
+    /// their fitness is that of the run so far, ignored with --samples
+    #[arg(long)]
+    pub early_exit: bool,
+
+    /// Addresses of `worker` processes sharing the evaluation of every generation, e.g. host1:7171,host2:7171;
+    /// genomes of a worker which fails are evaluated here and the result does not depend on it
+    #[arg(long, value_name = "ADDRESS", value_delimiter = ',')]
+    pub workers: Vec<String>,
+
+    /// Probability of flipping every bit of a child
+    #[arg(short = 'm', long, default_value_t = 0.01, value_parser = parse_probability)]
+    pub mutation: f64,
+
+    /// Parent selection method: roulette, tournament or alias (the roulette drawn from a table built once per generation)
+    #[arg(short = 's', long, default_value = "roulette", value_parser = parse_selection)]
+    pub selection: Selection,
+
+    /// Best chromosomes copied unchanged into the next generation
+    #[arg(long, default_value_t = 0)]
+    pub elitism: usize,
+
+    /// Fitness scaling before roulette selection: none, linear[:multiple], sigma[:c] or rank
+    #[arg(long, default_value = "none", value_parser = parse_scaling)]
+    pub scaling: FitnessScaling,
+
+    /// Aggregation of fitness over maps and start positions: mean, min or weighted:w1,w2,... (a weight per map and start)
+    #[arg(long, default_value = "mean", value_parser = parse_aggregation)]
+    pub aggregate: Aggregation,
+
+    /// Evaluations of every chromosome with different random streams
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: u32,
+
+    /// Sample limit for chromosomes which cannot be told apart (default 4 times --samples)
+    #[arg(long)]
+    pub max_samples: Option<u32>,
+
+    /// Never ask whether to keep searching, follow --until (solution unless given) instead
+    #[arg(long)]
+    pub non_interactive: bool,
+
+    /// What follows a full solution instead of a prompt: stop (solution), search N more generations
+    /// for a better one (solution+N) or search until the generation, time or evaluation budget is spent (budget)
+    #[arg(long, value_parser = parse_stop_policy)]
+    pub until: Option<StopPolicy>,
+
+    /// Summary printed at the end: text or json
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    pub output: String,
+
+    /// File the JSON summary is written to instead of the standard output, implies --output json
+    #[arg(long, value_name = "FILE")]
+    pub output_file: Option<PathBuf>,
+
+    /// CSV file one row of fitness statistics is appended to every generation
+    #[arg(long, value_name = "FILE")]
+    pub log_csv: Option<PathBuf>,
+
+    /// File one JSON object is appended to for every generation, new best solution and restart
+    #[arg(long, value_name = "FILE")]
+    pub log_jsonl: Option<PathBuf>,
+
+    /// JSON file the best solution is written to whenever it improves
+    #[arg(long, value_name = "FILE")]
+    pub autosave: Option<PathBuf>,
+
+    /// Genome file the best solution is written to on completion, with its instruction set, map and fitness;
+    /// replay and --initial-population read it
+    #[arg(long, value_name = "FILE")]
+    pub save_genome: Option<PathBuf>,
+
+    /// JSON file written on completion with everything needed to run the same search again with `reproduce`:
+    /// the resolved options, the seed, the map and its hash, the version and the best fitness reached
+    #[arg(long, value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+
+    /// Directory an event file with the scalars of every generation (fitness, diversity, operator success rates)
+    /// is written to for TensorBoard, e.g. runs/first with `tensorboard --logdir runs`
+    #[arg(long, value_name = "DIR")]
+    pub tensorboard: Option<PathBuf>,
+
+    /// JSON file with the best known solution of every map, the run is compared against the record of its map and
+    /// replaces it when it does better
+    #[arg(long, value_name = "FILE")]
+    pub leaderboard: Option<PathBuf>,
+
+    /// Interval of the population statistics: generations, e.g. 500, or seconds, e.g. 2s
+    #[arg(long, default_value = "500", value_parser = parse_report_interval)]
+    pub report_every: ReportInterval,
+
+    /// Adds evaluations and machine iterations per second and the time spent in evaluation, selection and
+    /// reproduction to the population statistics
+    #[arg(long)]
+    pub throughput: bool,
+
+    /// PNG chart of the best and mean fitness of every generation, written on completion
+    #[arg(long, value_name = "FILE")]
+    pub plot: Option<PathBuf>,
+
+    /// SVG file the map with the path of the best solution is written to on completion
+    #[arg(long, value_name = "FILE")]
+    pub svg: Option<PathBuf>,
+
+    /// Animated GIF of the best solution walking the map, written on completion
+    #[arg(long, value_name = "FILE")]
+    pub gif: Option<PathBuf>,
+
+    /// Live dashboard with the animated best path instead of the regular output, implies --non-interactive
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Restart the search whenever the map file given with --map changes
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Local TCP port accepting commands changing the running search: mutation <probability>,
+    /// elitism <count> and restart, one per line
+    #[arg(long, value_name = "PORT")]
+    pub control_port: Option<u16>,
+
+    /// Address of an HTTP server answering with the state of the search as JSON: GET /status, /generation, /best
+    /// and /stats, POST /stop ends the search after the current generation
+    #[arg(long, value_name = "ADDRESS")]
+    pub serve: Option<String>,
+
+    /// Address of a WebSocket server pushing every generation and best path as JSON, e.g. to a browser page
+    /// animating the search, see the README for the messages
+    #[arg(long, value_name = "ADDRESS")]
+    pub live: Option<String>,
+
+    /// File the state of the search is periodically saved to
+    #[arg(long, value_name = "FILE")]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Generations between two checkpoints
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    pub checkpoint_every: u32,
+
+    /// Continue the search saved in a checkpoint, the other options have to match the checkpointed run
+    #[arg(long, value_name = "FILE")]
+    pub resume: Option<PathBuf>,
+
+    /// JSON file the evaluated population is written to on completion, best first with the statistics of every
+    /// chromosome and of the generation
+    #[arg(long, value_name = "FILE")]
+    pub save_population: Option<PathBuf>,
+
+    /// Population saved with --save-population, possibly edited, or a genome file, the search starts from its genomes. Missing ones
+    /// are random, those beyond --subjects are left out.
+    #[arg(long, value_name = "FILE", conflicts_with = "resume")]
+    pub initial_population: Option<PathBuf>,
+}
+
+// What a non-interactive run does instead of asking
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopPolicy {
+    FirstSolution,
+    // Generations searched for a better solution after the first one
+    Extra(u32),
+    Budget,
+}
+
+// How often the population statistics are printed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReportInterval {
+    Generations(u32),
+    Seconds(f64),
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Genome in its printed form, e.g. "[200, 193, 147, ...]", or a file with it, a JSON run summary or a checkpoint
+    pub genome: String,
+
+    /// Iterations to dump, e.g. "10,50,100"
+    #[arg(long, value_name = "ITERATIONS")]
+    pub dump_at: Option<String>,
+
+    /// Directory of the dumped iterations
+    #[arg(long, default_value = ".")]
+    pub dump_dir: PathBuf,
+
+    /// Animate the path step by step on the map
+    #[arg(long)]
+    pub play: bool,
+
+    /// Delay between the frames of the animation in milliseconds
+    #[arg(long, default_value_t = 200)]
+    pub frame_delay: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct PlayArgs {
+    /// Genome to compare the route with, in any form replay accepts
+    #[arg(long, value_name = "GENOME")]
+    pub against: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    pub shell: clap_complete::Shell,
+}
+
+#[derive(Args, Debug)]
+pub struct MapgenArgs {
+    #[arg(long, default_value_t = 7)]
+    pub width: usize,
+
+    #[arg(long, default_value_t = 7)]
+    pub height: usize,
+
+    #[arg(long, default_value_t = 5)]
+    pub treasures: usize,
+
+    /// File the map is written to, printed unless given
+    #[arg(short = 'o', long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportTrailArgs {
+    /// Trail file as published with GP systems: '#' food, any other glyph empty, 'S' the start (top left corner
+    /// without one), optionally a first line with the width and the height as in ECJ
+    pub file: PathBuf,
+
+    /// File the map is written to, printed unless given
+    #[arg(short = 'o', long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct AnalyzeArgs {
+    /// Random genomes sampled with all their single-bit neighbours
+    #[arg(long, default_value_t = 100)]
+    pub samples: usize,
+
+    /// Length of the random bit-flip walk
+    #[arg(long, default_value_t = 10000)]
+    pub walk_length: usize,
+
+    /// Only the shortest tour collecting every treasure, no random genomes are sampled
+    #[arg(long)]
+    pub tour_only: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Number of evaluated random genomes
+    #[arg(long, default_value_t = 100000)]
+    pub evaluations: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct ProfileArgs {
+    /// Genome in its printed form, e.g. "[200, 193, 147, ...]", or a file with it, a JSON run summary or a checkpoint
+    pub genome: String,
+
+    /// Number of profiled runs
+    #[arg(long, default_value_t = 1000)]
+    pub runs: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct MapElitesArgs {
+    /// Number of evaluated programs
+    #[arg(long, default_value_t = 100000)]
+    pub evaluations: u32,
+
+    /// Probability of flipping every bit of a child
+    #[arg(short = 'm', long, default_value_t = 0.02, value_parser = parse_probability)]
+    pub mutation: f64,
+
+    /// Bins per descriptor
+    #[arg(long, default_value_t = 8)]
+    pub bins: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct TeachArgs {
+    /// Number of chromosomes in every generation
+    #[arg(short = 'n', long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(2..=32))]
+    pub subjects: u32,
+
+    /// Number of generations
+    #[arg(short = 'g', long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub generations: u32,
+
+    /// Probability of flipping every bit of a child
+    #[arg(short = 'm', long, default_value_t = 0.01, value_parser = parse_probability)]
+    pub mutation: f64,
+
+    /// Parent selection method: roulette, tournament or alias (the roulette drawn from a table built once per generation)
+    #[arg(short = 's', long, default_value = "roulette", value_parser = parse_selection)]
+    pub selection: Selection,
+
+    /// Leading bytes of every genome shown bit by bit
+    #[arg(long, default_value_t = 2)]
+    pub bytes: usize,
+
+    /// Do not wait for Enter after every phase
+    #[arg(long)]
+    pub no_pause: bool,
+}
+
+pub fn print_completions(shell: clap_complete::Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// TOML file with a [[job]] table of run options per job, e.g. `map`, `seed` or `generations`,
+    /// and an optional [defaults] table shared by all jobs
+    pub file: PathBuf,
+
+    /// Jobs running at the same time
+    #[arg(short = 'j', long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+
+    /// Runs of every job with consecutive seeds starting at the seed of the job
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub runs: u32,
+
+    /// File one JSON result record per job is written to instead of the standard output
+    #[arg(long, value_name = "FILE")]
+    pub results: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct TournamentArgs {
+    /// TOML file with the budget `evaluations`, optional `maps` (files) and `seeds` (or `runs`, seeds from 0),
+    /// a [[config]] table of run options per configuration with its `name` and an optional [defaults] table
+    pub file: PathBuf,
+
+    /// Runs at the same time
+    #[arg(short = 'j', long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+
+    /// File one JSON result record per run is written to, none are written without it
+    #[arg(long, value_name = "FILE")]
+    pub results: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ReproduceArgs {
+    /// Manifest file of the run
+    pub manifest: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct WorkerArgs {
+    /// Address the coordinators connect to
+    #[arg(long, default_value = "127.0.0.1:7171")]
+    pub listen: String,
+}
+
+// Prefix of the environment variables of the options, e.g. TREASURE_SUBJECTS for --subjects
+const ENV_PREFIX: &str = "TREASURE_";
+
+// Command line arguments with the values of the configuration file and then of the environment inserted before them,
+// the command line wins over the environment and the environment over the file
+pub fn parse() -> Result<Cli, Error> {
+    return Ok(Cli::parse_from(resolved_args()?));
+}
+
+// The command line parse() reads, e.g. for workers which parse it again
+pub fn resolved_args() -> Result<Vec<String>, Error> {
+    let mut args: Vec<String> = std::env::args().collect();
+    let command = Cli::command();
+    let subcommand = args.iter().position(|arg| command.find_subcommand(arg).is_some());
+    let subcommand_command = subcommand.and_then(|index| command.find_subcommand(&args[index]));
+    let path = args.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix("--config") {
+        Some("") => args.get(i + 1).cloned(),
+        Some(value) => value.strip_prefix('=').map(String::from),
+        None => None,
+    }).or_else(|| std::env::var(format!("{}CONFIG", ENV_PREFIX)).ok());
+    let (mut shared_args, mut subcommand_args) = match path {
+        Some(path) => config_args(Path::new(&path), subcommand_command.map(|subcommand| subcommand.get_name()))
+            .map_err(|e| Error::InvalidConfig(format!("Invalid configuration file {}: {}", path, e)))?,
+        None => (Vec::new(), Vec::new()),
+    };
+    shared_args.extend(env_args(&command)?);
+    if let Some(subcommand_command) = subcommand_command {
+        subcommand_args.extend(env_args(subcommand_command)?);
+    }
+    if let Some(index) = subcommand {
+        args.splice(index + 1..index + 1, subcommand_args);
+    }
+    args.splice(1..1, shared_args);
+    return Ok(args);
+}
+
+// Options of the command line parse() reads, globals included, without the program, the subcommand and the options
+// with the long names given, e.g. to start the same search again in another process
+pub fn options_without(names: &[&str]) -> Result<Vec<String>, Error> {
+    let args = resolved_args()?;
+    let command = Cli::command();
+    let subcommand = args.iter().position(|arg| command.find_subcommand(arg).is_some());
+    let mut commands: Vec<&clap::Command> = vec![&command];
+    commands.extend(subcommand.and_then(|index| command.find_subcommand(&args[index])));
+    let find = |matches: &dyn Fn(&clap::Arg) -> bool| commands.iter().flat_map(|command| command.get_arguments()).find(|&arg| matches(arg));
+    let mut options: Vec<String> = Vec::new();
+    let mut index = 1;
+    while index < args.len() {
+        let token = &args[index];
+        index += 1;
+        if Some(index - 1) == subcommand {
+            continue;
+        }
+        // With the value in the same token, e.g. --seed=3
+        let (arg, inline) = if let Some(long) = token.strip_prefix("--") {
+            let name = long.split('=').next().unwrap_or(long);
+            (find(&|arg| arg.get_long() == Some(name) || arg.get_all_aliases().is_some_and(|aliases| aliases.contains(&name))),
+             long.contains('='))
+        } else if let Some(short) = token.strip_prefix('-').and_then(|short| short.chars().next()) {
+            (find(&|arg| arg.get_short() == Some(short)), token.len() > 2)
+        } else {
+            (None, false)
+        };
+        match arg {
+            Some(arg) if arg.get_long().is_some_and(|long| names.contains(&long)) => {
+                if arg.get_action().takes_values() && !inline {
+                    index += 1;
+                }
+            }
+            _ => options.push(token.clone()),
+        }
+    }
+    return Ok(options);
+}
+
+// Options of the command set in the environment, flags by true or false.
+// Not clap's own env support, which would let the configuration file win over the environment.
+fn env_args(command: &clap::Command) -> Result<Vec<String>, Error> {
+    let mut args: Vec<String> = Vec::new();
+    for arg in command.get_arguments() {
+        let Some(long) = arg.get_long().filter(|&long| long != "config") else {
+            continue;
+        };
+        let name = format!("{}{}", ENV_PREFIX, long.to_ascii_uppercase().replace('-', "_"));
+        let Ok(value) = std::env::var(&name) else {
+            continue;
+        };
+        if arg.get_action().takes_values() {
+            args.extend([format!("--{}", long), value]);
+            continue;
+        }
+        match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => args.push(format!("--{}", long)),
+            "0" | "false" | "no" | "off" | "" => {}
+            _ => return Err(Error::InvalidConfig(format!("{} is a flag, expected true or false", name))),
+        }
+    }
+    return Ok(args);
+}
+
+// Every key becomes the long option of the same name, underscores are accepted instead of dashes
+fn config_args(path: &Path, subcommand: Option<&str>) -> Result<(Vec<String>, Vec<String>), Error> {
+    let text = std::fs::read_to_string(path)?;
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| Error::Parse(e.to_string()))?;
+    let mut shared_args: Vec<String> = Vec::new();
+    let mut subcommand_args: Vec<String> = Vec::new();
+    for (key, value) in &table {
+        match value {
+            toml::Value::Table(table) if subcommand == Some(key.as_str()) => {
+                for (key, value) in table {
+                    push_option(&mut subcommand_args, key, value)?;
+                }
+            }
+            // Tables of other subcommands
+            toml::Value::Table(_) => {}
+            _ => push_option(&mut shared_args, key, value)?,
+        }
+    }
+    return Ok((shared_args, subcommand_args));
+}
+
+pub fn push_option(args: &mut Vec<String>, key: &str, value: &toml::Value) -> Result<(), Error> {
+    let option = format!("--{}", key.replace('_', "-"));
+    match value {
+        toml::Value::Boolean(true) => args.push(option),
+        toml::Value::Boolean(false) => {}
+        toml::Value::String(value) => args.extend([option, value.clone()]),
+        toml::Value::Integer(value) => args.extend([option, value.to_string()]),
+        toml::Value::Float(value) => args.extend([option, value.to_string()]),
+        // Repeated options, e.g. map = ["a.txt", "b.txt"]
+        toml::Value::Array(values) => {
+            for value in values {
+                push_option(args, key, value)?;
+            }
+        }
+        _ => return Err(Error::InvalidConfig(format!("unsupported value of {}", key))),
+    }
+    return Ok(());
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    VeryVerbose,
+}
+
+impl Cli {
+    pub fn verbosity(&self) -> Verbosity {
+        if self.quiet {
+            return Verbosity::Quiet;
+        }
+        return match self.verbose {
+            0 => Verbosity::Normal,
+            1 => Verbosity::Verbose,
+            _ => Verbosity::VeryVerbose,
+        };
+    }
+}
+
+// Newtype so clap does not parse every position separately
+#[derive(Clone, Debug)]
+pub struct Starts(pub Vec<(usize, usize)>);
+
+fn parse_subjects(text: &str) -> Result<usize, String> {
+    let subjects = text.parse::<usize>().map_err(|e| e.to_string())?;
+    if subjects < 20 {
+        return Err(String::from("minimum number of subjects is 20"));
+    }
+    return Ok(subjects);
+}
+
+fn parse_seconds(text: &str) -> Result<f64, String> {
+    let seconds = text.parse::<f64>().map_err(|e| e.to_string())?;
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(String::from("time budget must be positive"));
+    }
+    return Ok(seconds);
+}
+
+fn parse_probability(text: &str) -> Result<f64, String> {
+    let probability = text.parse::<f64>().map_err(|e| e.to_string())?;
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(String::from("probability must be between 0 and 1"));
+    }
+    return Ok(probability);
+}
+
+fn parse_selection(text: &str) -> Result<Selection, String> {
+    return Selection::parse(text).ok_or_else(|| String::from("expected roulette, tournament or alias"));
+}
+
+fn parse_stop_policy(text: &str) -> Result<StopPolicy, String> {
+    let text = text.to_ascii_lowercase();
+    if let Some(extra) = text.strip_prefix("solution+") {
+        return extra.parse::<u32>().map(StopPolicy::Extra).map_err(|e| e.to_string());
+    }
+    return match text.as_str() {
+        "solution" => Ok(StopPolicy::FirstSolution),
+        "budget" => Ok(StopPolicy::Budget),
+        _ => Err(String::from("expected solution, solution+<generations> or budget")),
+    };
+}
+
+fn parse_report_interval(text: &str) -> Result<ReportInterval, String> {
+    if let Some(seconds) = text.strip_suffix('s') {
+        return parse_seconds(seconds).map(ReportInterval::Seconds);
+    }
+    let generations = text.parse::<u32>().map_err(|e| e.to_string())?;
+    if generations == 0 {
+        return Err(String::from("interval must be at least one generation"));
+    }
+    return Ok(ReportInterval::Generations(generations));
+}
+
+fn parse_genome_format(text: &str) -> Result<GenomeFormat, String> {
+    return GenomeFormat::parse(text).ok_or_else(|| String::from("expected bytes, hex, base64, asm or moves"));
+}
+
+fn parse_instruction_set(text: &str) -> Result<InstructionSet, String> {
+    return InstructionSet::parse(text).ok_or_else(|| String::from("expected classic or extended"));
+}
+
+fn parse_glyphs(text: &str) -> Result<StepAlphabet, String> {
+    return StepAlphabet::parse(text).ok_or_else(|| String::from("expected a preset or four glyphs"));
+}
+
+fn parse_color(text: &str) -> Result<ColorMode, String> {
+    return ColorMode::parse(text).ok_or_else(|| String::from("expected auto, always or never"));
+}
+
+fn parse_comparison(text: &str) -> Result<Comparison, String> {
+    return match text.to_ascii_lowercase().as_str() {
+        "fitness" => Ok(Comparison::Fitness),
+        "lexicographic" => Ok(Comparison::Lexicographic),
+        _ => Err(String::from("expected fitness or lexicographic")),
+    };
+}
+
+fn parse_scaling(text: &str) -> Result<FitnessScaling, String> {
+    return FitnessScaling::parse(text).ok_or_else(|| String::from("expected none, linear[:multiple], sigma[:c] or rank"));
+}
+
+fn parse_starts(text: &str) -> Result<Starts, String> {
+    return ensemble::parse_starts(text).map(Starts).ok_or_else(|| String::from("expected x,y;x,y;..."));
+}
+
+fn parse_aggregation(text: &str) -> Result<Aggregation, String> {
+    return Aggregation::parse(text).ok_or_else(|| String::from("expected mean, min or weighted:w1,w2,..."));
+}
+
+fn parse_memory_size(text: &str) -> Result<usize, String> {
+    let memory_size = text.parse::<usize>().map_err(|e| e.to_string())?;
+    if !(1..=vm::MAX_MEMORY_SIZE).contains(&memory_size) {
+        return Err(format!("memory size must be between 1 and {}", vm::MAX_MEMORY_SIZE));
+    }
+    return Ok(memory_size);
+}
+
+fn parse_collision(text: &str) -> Result<CollisionPolicy, String> {
+    return match text.to_ascii_lowercase().as_str() {
+        "terminate" => Ok(CollisionPolicy::Terminate),
+        "block" => Ok(CollisionPolicy::Block),
+        "wrap" => Ok(CollisionPolicy::Wrap),
+        _ => Err(String::from("expected terminate, block or wrap")),
+    };
+}
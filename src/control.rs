@@ -0,0 +1,112 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+// Change of a running search requested over the control socket
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ControlCommand {
+    Mutation(f64),
+    Elitism(usize),
+    // Replaces the population with random chromosomes except for the best one
+    Restart,
+}
+
+pub const HELP: &str = "commands: mutation <probability>, elitism <count>, restart";
+
+impl ControlCommand {
+    // The elitism is checked against the number of subjects of the search
+    pub fn parse(line: &str, subjects: usize) -> Result<ControlCommand, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("").to_ascii_lowercase();
+        let argument = words.next();
+        if let Some(word) = words.next() {
+            return Err(format!("unexpected {:?}, {}", word, HELP));
+        }
+        return match (command.as_str(), argument) {
+            ("mutation", Some(value)) => {
+                let probability = value.parse::<f64>().map_err(|e| e.to_string())?;
+                if !(0.0..=1.0).contains(&probability) {
+                    return Err(String::from("probability must be between 0 and 1"));
+                }
+                Ok(ControlCommand::Mutation(probability))
+            }
+            ("elitism", Some(value)) => {
+                let count = value.parse::<usize>().map_err(|e| e.to_string())?;
+                if count >= subjects {
+                    return Err(format!("elitism must be below the {} subjects", subjects));
+                }
+                Ok(ControlCommand::Elitism(count))
+            }
+            ("restart", None) => Ok(ControlCommand::Restart),
+            _ => Err(String::from(HELP)),
+        };
+    }
+}
+
+// Accepts line-based commands on a local TCP port, e.g. with `nc 127.0.0.1 <port>`
+pub struct Console {
+    receiver: Receiver<ControlCommand>,
+}
+
+impl Console {
+    pub fn listen(port: u16, subjects: usize) -> std::io::Result<Console> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                std::thread::spawn(move || serve(stream, sender, subjects));
+            }
+        });
+        return Ok(Console { receiver });
+    }
+
+    // Commands received since the last call
+    pub fn pending(&self) -> Vec<ControlCommand> {
+        return self.receiver.try_iter().collect();
+    }
+}
+
+fn serve(stream: TcpStream, sender: Sender<ControlCommand>, subjects: usize) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match ControlCommand::parse(&line, subjects) {
+            Ok(command) if sender.send(command).is_ok() => String::from("ok"),
+            // The search has finished
+            Ok(_) => return,
+            Err(e) => format!("error: {}", e),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(ControlCommand::parse("mutation 0.25", 10), Ok(ControlCommand::Mutation(0.25)));
+        assert_eq!(ControlCommand::parse("  Elitism 9 ", 10), Ok(ControlCommand::Elitism(9)));
+        assert_eq!(ControlCommand::parse("restart", 10), Ok(ControlCommand::Restart));
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        for line in ["", "mutation", "mutation 1.5", "mutation x", "elitism -1", "restart now", "stop"] {
+            assert!(ControlCommand::parse(line, 10).is_err(), "{:?}", line);
+        }
+        assert_eq!(ControlCommand::parse("mutation 0.5 junk", 10), Err(format!("unexpected \"junk\", {}", HELP)));
+        assert_eq!(ControlCommand::parse("elitism 10", 10), Err(String::from("elitism must be below the 10 subjects")));
+    }
+}
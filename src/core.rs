@@ -1,195 +1,423 @@
+use ::core::cmp::Ordering;
+use ::core::fmt;
+use alloc::{format, string::String, sync::Arc, vec, vec::Vec};
+
 use rand::Rng;
 use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use crate::crossover::CrossoverOperator;
+use crate::map::GameArea;
+use crate::mutation::MutationOperator;
+use crate::noise::SampleStats;
+use crate::selection::{AliasSelection, RouletteSelection, SelectionStrategy, TournamentSelection};
+use crate::vm::MAX_MEMORY_SIZE;
 
 pub const AREA_TILE_PLAYER: u8 = 1;
 pub const AREA_TILE_TREASURE: u8 = 2;
 pub const AREA_TILE_NOTHING: u8 = 0;
 
-pub const DIR_UP: usize = 0;
-pub const DIR_RIGHT: usize = 1;
-pub const DIR_DOWN: usize = 2;
-pub const DIR_LEFT: usize = 3;
+// Glyphs recorded in the steps string, indexed by direction
+pub const STEP_GLYPHS: [char; 4] = ['H', 'P', 'D', 'L'];
+
+// Directions in the order of the move operand, y grows downwards
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    // Only the lowest two bits are used
+    pub fn from_index(index: usize) -> Direction {
+        return Direction::ALL[index & 3];
+    }
+
+    pub fn index(self) -> usize {
+        return self as usize;
+    }
+
+    pub fn offset(self) -> (isize, isize) {
+        return match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        };
+    }
+
+    pub fn opposite(self) -> Direction {
+        return Direction::from_index(self.index() + 2);
+    }
+
+    // Glyph recorded in the steps string
+    pub fn glyph(self) -> char {
+        return STEP_GLYPHS[self.index()];
+    }
+}
+
+// Tile coordinates, negative or too large values are outside of the map
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Position {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Position {
+    pub fn new(x: isize, y: isize) -> Position {
+        return Position { x, y };
+    }
+
+    pub fn step(self, direction: Direction) -> Position {
+        let (dx, dy) = direction.offset();
+        return Position { x: self.x + dx, y: self.y + dy };
+    }
+
+    // Wrapped around the edges of a map of the given size
+    pub fn wrapped(self, width: usize, height: usize) -> Position {
+        return Position { x: self.x.rem_euclid(width as isize), y: self.y.rem_euclid(height as isize) };
+    }
+
+    // Only meaningful inside of the map
+    pub fn tile(self) -> (usize, usize) {
+        return (self.x as usize, self.y as usize);
+    }
+}
 
 pub const NUM_OF_CHILDREN: i32 = 2;
 
 pub type INSTR = u8;
 
-pub struct Chromosome {
-    pub genes: Vec<INSTR>,
+// Representation evolved by the engine, programs of the machine unless another one is given
+pub trait Genome: Clone + fmt::Debug + Send + Sync {
+    // Bytes the diversity of a population is measured on, empty without a byte form
+    fn bytes(&self) -> &[u8];
+}
+
+impl Genome for Vec<INSTR> {
+    fn bytes(&self) -> &[u8] {
+        return self;
+    }
+}
+
+// Program of the machine stored inline, so that chromosomes and their offspring need no allocation for it.
+// Reads and writes like a slice of at most MAX_MEMORY_SIZE bytes, serialized as a list of bytes like a vector.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Genes {
+    bytes: [INSTR; MAX_MEMORY_SIZE],
+    len: u8,
+}
+
+impl Genes {
+    // Panics beyond MAX_MEMORY_SIZE bytes
+    pub fn from_slice(instructions: &[INSTR]) -> Genes {
+        return Genes::try_from_slice(instructions)
+            .unwrap_or_else(|| panic!("a program of {} bytes is longer than {}", instructions.len(), MAX_MEMORY_SIZE));
+    }
+
+    // None beyond MAX_MEMORY_SIZE bytes
+    pub fn try_from_slice(instructions: &[INSTR]) -> Option<Genes> {
+        if instructions.len() > MAX_MEMORY_SIZE {
+            return None;
+        }
+        let mut bytes: [INSTR; MAX_MEMORY_SIZE] = [0; MAX_MEMORY_SIZE];
+        bytes[..instructions.len()].copy_from_slice(instructions);
+        return Some(Genes { bytes, len: instructions.len() as u8 });
+    }
+
+    // Zeroed
+    pub fn zeroed(len: usize) -> Genes {
+        assert!(len <= MAX_MEMORY_SIZE, "a program of {} bytes is longer than {}", len, MAX_MEMORY_SIZE);
+        return Genes { bytes: [0; MAX_MEMORY_SIZE], len: len as u8 };
+    }
+
+    pub fn to_vec(&self) -> Vec<INSTR> {
+        return self[..].to_vec();
+    }
+}
+
+impl ::core::ops::Deref for Genes {
+    type Target = [INSTR];
+
+    fn deref(&self) -> &[INSTR] {
+        return &self.bytes[..usize::from(self.len)];
+    }
+}
+
+impl ::core::ops::DerefMut for Genes {
+    fn deref_mut(&mut self) -> &mut [INSTR] {
+        return &mut self.bytes[..usize::from(self.len)];
+    }
+}
+
+impl From<&[INSTR]> for Genes {
+    fn from(instructions: &[INSTR]) -> Genes {
+        return Genes::from_slice(instructions);
+    }
+}
+
+impl From<Vec<INSTR>> for Genes {
+    fn from(instructions: Vec<INSTR>) -> Genes {
+        return Genes::from_slice(&instructions);
+    }
+}
+
+impl fmt::Debug for Genes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return fmt::Debug::fmt(&self[..], f);
+    }
+}
+
+impl Serialize for Genes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return self[..].serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for Genes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Genes, D::Error> {
+        let instructions: Vec<INSTR> = Vec::deserialize(deserializer)?;
+        return Genes::try_from_slice(&instructions).ok_or_else(|| {
+            serde::de::Error::custom(format!("a program of {} bytes is longer than {}", instructions.len(), MAX_MEMORY_SIZE))
+        });
+    }
+}
+
+impl Genome for Genes {
+    fn bytes(&self) -> &[u8] {
+        return self;
+    }
+}
+
+// Glyphs used when printing or exporting steps, indexed by direction
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StepAlphabet(pub [char; 4]);
+
+impl StepAlphabet {
+    pub const SLOVAK: StepAlphabet = StepAlphabet(STEP_GLYPHS);
+    pub const ENGLISH: StepAlphabet = StepAlphabet(['U', 'R', 'D', 'L']);
+    pub const ARROWS: StepAlphabet = StepAlphabet(['↑', '→', '↓', '←']);
+    pub const COMPASS: StepAlphabet = StepAlphabet(['N', 'E', 'S', 'W']);
+
+    // Accepts a preset name or four glyphs in the order up, right, down, left
+    pub fn parse(text: &str) -> Option<StepAlphabet> {
+        match text.to_ascii_lowercase().as_str() {
+            "hpdl" | "slovak" => return Some(StepAlphabet::SLOVAK),
+            // Presets spelled in any order, e.g. UDLR or NSEW, are not taken as four glyphs
+            "urdl" | "udlr" | "english" => return Some(StepAlphabet::ENGLISH),
+            "nesw" | "nsew" | "compass" => return Some(StepAlphabet::COMPASS),
+            "arrows" => return Some(StepAlphabet::ARROWS),
+            _ => {}
+        }
+        let glyphs: Vec<char> = text.chars().collect();
+        return match glyphs[..] {
+            [up, right, down, left] => Some(StepAlphabet([up, right, down, left])),
+            _ => None,
+        };
+    }
+
+    pub fn format(&self, steps: &str) -> String {
+        return steps.chars()
+            .map(|step| match STEP_GLYPHS.iter().position(|&glyph| glyph == step) {
+                Some(direction) => self.0[direction],
+                None => step,
+            })
+            .collect();
+    }
+}
+
+impl Default for StepAlphabet {
+    fn default() -> Self {
+        return StepAlphabet::SLOVAK;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Selection {
+    Roulette,
+    Tournament,
+    Alias,
+}
+
+impl Selection {
+    pub fn parse(text: &str) -> Option<Selection> {
+        return match text.to_ascii_lowercase().as_str() {
+            "roulette" | "0" => Some(Selection::Roulette),
+            "tournament" | "1" => Some(Selection::Tournament),
+            "alias" | "2" => Some(Selection::Alias),
+            _ => None,
+        };
+    }
+
+    pub fn strategy<G>(&self) -> Arc<dyn SelectionStrategy<G>> {
+        return match self {
+            Selection::Roulette => Arc::new(RouletteSelection),
+            Selection::Tournament => Arc::new(TournamentSelection),
+            Selection::Alias => Arc::new(AliasSelection),
+        };
+    }
+}
+
+// How two chromosomes are ranked against each other
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Comparison {
+    Fitness,
+    // More treasures, then fewer steps, then fewer iterations
+    Lexicographic,
+}
+
+impl Comparison {
+    // Greater means that the first chromosome is better
+    pub fn compare<G>(&self, a: &Chromosome<G>, b: &Chromosome<G>) -> Ordering {
+        return match self {
+            Comparison::Fitness => compare_fitness(a.fitness, b.fitness),
+            Comparison::Lexicographic => a.found_treasures.cmp(&b.found_treasures)
+                .then(b.steps.len().cmp(&a.steps.len()))
+                .then(b.iterations.cmp(&a.iterations)),
+        };
+    }
+
+    // Same as compare, but uses the scaled fitness
+    pub fn compare_for_selection<G>(&self, a: &Chromosome<G>, b: &Chromosome<G>) -> Ordering {
+        return match self {
+            Comparison::Fitness => compare_fitness(a.selection_fitness, b.selection_fitness),
+            Comparison::Lexicographic => self.compare(a, b),
+        };
+    }
+}
+
+// NaN, e.g. from a fitness script, is worse than any number so sorting stays a total order
+fn compare_fitness(a: f64, b: f64) -> Ordering {
+    return match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (a_nan, b_nan) => b_nan.cmp(&a_nan),
+    };
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Chromosome<G = Genes> {
+    pub genes: G,
     pub found_treasures: u32,
     pub fitness: f64,
+    // Fitness after scaling, used only by selection
+    pub selection_fitness: f64,
     pub iterations: u32,
+    // Distinct instructions executed
+    pub complexity: u32,
     pub steps: String,
+    // Fitness on every evaluated case before aggregation
+    pub case_fitness: Vec<f64>,
+    // Aggregated fitness of every evaluation with a different random stream
+    pub fitness_stats: SampleStats,
 }
 
 impl Chromosome {
     pub fn with_instructions(instructions: Vec<INSTR>) -> Chromosome {
+        return Chromosome::with_genes(Genes::from(instructions));
+    }
+}
+
+impl<G> Chromosome<G> {
+    // Not evaluated yet
+    pub fn with_genes(genes: G) -> Chromosome<G> {
         return Chromosome {
-            genes: instructions,
+            genes,
             found_treasures: 0,
             fitness: 0.0,
+            selection_fitness: 0.0,
             iterations: 0,
+            complexity: 0,
             steps: String::new(),
+            case_fitness: Vec::new(),
+            fitness_stats: SampleStats::default(),
         };
     }
 }
 
-pub fn random_instructions(rng: &mut Pcg64) -> Vec<INSTR> {
-    let mut output: Vec<INSTR> = vec![0; 64];
-    for i in 0..16 {
-        output[i] = rng.gen_range(0..=u8::MAX);
+// Only the first quarter of the program is random, the rest is zeroed
+pub fn random_instructions(rng: &mut Pcg64, size: usize) -> Vec<INSTR> {
+    let mut output: Vec<INSTR> = vec![0; size];
+    for instruction in output.iter_mut().take(size.div_ceil(4)) {
+        *instruction = rng.gen_range(0..=u8::MAX);
     }
     return output;
 }
 
-pub fn run_virtual_machine(instructions: &Vec<u8>, original_game_area: &Vec<Vec<u8>>,
-        steps: &mut String, mut player_x: isize, mut player_y: isize, treasures: u32) -> (u32, u32) {
-    let rows = original_game_area.len();
-    let columns = original_game_area[0].len();
-
-    let mut game_area = original_game_area.clone();
-    let mut machine_memory: Vec<u8> = instructions.clone();
-    let mut curr_instr_index: usize = 0;
-    let mut iterations: u32 = 0;
-    let mut found_treasures: u32 = 0;
-    while iterations < 500 && curr_instr_index < 64 && found_treasures < treasures {
-        let instruction: u8 = machine_memory[curr_instr_index];
-
-        let operation: u8 = instruction & 0xC0;
-        let data: usize = usize::from(instruction & 0x3F);
-        let mut jump: bool = false;
-        match operation {
-            0 => {
-                // Increment
-                machine_memory[data] = machine_memory[data].wrapping_add(1);
-            }
-            64 => {
-                // Decrement
-                machine_memory[data] = machine_memory[data].wrapping_add(1);
-            }
-            128 => {
-                // Jump
-                curr_instr_index = data;
-                jump = true;
-            }
-            192 => {
-                // Move (print)
-                match data & 3 {
-                    DIR_UP => {
-                        //print!("H");
-                        steps.push('H');
-                        player_y -= 1;
-                    }
-                    DIR_RIGHT => {
-                        //print!("P");
-                        steps.push('P');
-                        player_x += 1;
-                    }
-                    DIR_DOWN => {
-                        //print!("D");
-                        steps.push('D');
-                        player_y += 1;
-                    }
-                    DIR_LEFT => {
-                        //print!("L");
-                        steps.push('L');
-                        player_x -= 1;
-                    }
-                    _ => {}
-                }
-                if !(player_x >= 0 && player_x < (columns as isize) && player_y >= 0 && player_y < (rows as isize)) {
-                    break;
-                }
-                if game_area[player_y as usize][player_x as usize] == AREA_TILE_TREASURE {
-                    game_area[player_y as usize][player_x as usize] = 0;
-                    found_treasures += 1;
-                }
-            }
-            _ => {}
-        }
-        iterations += 1;
-        if !jump {
-            curr_instr_index += 1;
-        }
+pub fn reproduce<G>(parent1: &Chromosome<G>, parent2: &Chromosome<G>, crossover: &dyn CrossoverOperator<G>,
+                    mutation: &dyn MutationOperator<G>, mutation_probability: f64, rng: &mut Pcg64) -> G {
+    let (mut genes, _) = crossover.crossover(&parent1.genes, &parent2.genes, rng);
+    mutation.mutate(&mut genes, mutation_probability, rng);
+    return genes;
+}
+
+// Child genes with the bits taken from the second parent and the flipped bits, one mask per byte
+pub struct Inheritance {
+    pub genes: Genes,
+    pub from_parent2: Vec<u8>,
+    pub mutated: Vec<u8>,
+}
+
+// Same as reproduce, records where every bit of the child came from
+pub fn reproduce_traced(parent1: &Chromosome, parent2: &Chromosome, crossover: &dyn CrossoverOperator, mutation: &dyn MutationOperator,
+                        mutation_probability: f64, rng: &mut Pcg64) -> Inheritance {
+    let (mut genes, from_parent2) = crossover.crossover(&parent1.genes, &parent2.genes, rng);
+    let mutated = mutation.mutate(&mut genes, mutation_probability, rng);
+    return Inheritance { genes, from_parent2, mutated };
+}
+
+// Index of the chosen chromosome and the point the wheel stopped at, None when the choice was uniform
+pub fn roulette_pick<G>(chromosomes: &[Chromosome<G>], total_fitness: f64, rng: &mut Pcg64) -> (usize, Option<f64>) {
+    // Without positive fitness (e.g. unclamped penalties) every chromosome is equally likely
+    if !(total_fitness > 0.0 && total_fitness.is_finite()) {
+        return (rng.gen_range(0..chromosomes.len()), None);
     }
-    return (iterations, found_treasures);
-}
-
-pub fn reproduce(parent1: &Chromosome, parent2: &Chromosome, mutation_probability: f64, rng: &mut Pcg64) -> Vec<INSTR> {
-    let mut output_vector = Vec::new();
-    for i in 0..64 {
-        let mut mask: u8 = 128;
-        let mut number: u8 = 0;
-        for _ in 0..8 {
-            debug_assert_eq!(number & mask, 0);
-            if rng.gen_bool(0.5) {  // Parent 1
-                number |= parent1.genes[i] & mask;
-            } else {    // Parent 2
-                number |= parent2.genes[i] & mask;
-            }
-
-            // Mutation
-            if rng.gen_bool(mutation_probability) {
-                number ^= mask;
-            }
-            mask >>= 1;
-        }
-        output_vector.push(number);
-    }
-    return output_vector;
-}
-
-pub fn selection_roulette<'a>(chromosomes: &'a Vec<Chromosome>, total_fitness: f64, rng: &mut Pcg64) -> (&'a Chromosome, &'a Chromosome) {
-    let mut v: Vec<&Chromosome> = Vec::with_capacity(2);
-    for _ in 0..2 {
-        let r: f64 = rng.gen_range(0f64..=total_fitness);
-        let mut curr_fitness: f64 = 0f64;
-        let mut selected_parent: Option<&Chromosome> = Option::None;
-        for c in chromosomes {
-            curr_fitness += c.fitness;
-            if curr_fitness > r {
-                selected_parent = Option::Some(c);
-                break;
-            }
+    let r: f64 = rng.gen_range(0f64..=total_fitness);
+    let mut curr_fitness: f64 = 0f64;
+    for (i, c) in chromosomes.iter().enumerate() {
+        curr_fitness += c.selection_fitness;
+        if curr_fitness > r {
+            return (i, Some(r));
         }
-        if selected_parent.is_none() {
-            selected_parent = chromosomes.last();
-        }
-        v.push(selected_parent.unwrap());
     }
-    return (v[0], v[1]);
+    return (chromosomes.len() - 1, Some(r));
 }
 
-pub fn selection_tournament<'a>(chromosomes: &'a Vec<Chromosome>, rng: &mut Pcg64) -> (&'a Chromosome, &'a Chromosome) {
-    let mut v: Vec<&Chromosome> = Vec::with_capacity(2);
-    for _ in 0..2 {
-        let index1 = rng.gen_range(0..chromosomes.len());
-        let index2 = rng.gen_range(0..chromosomes.len());
-        if chromosomes[index1].fitness > chromosomes[index2].fitness {
-            v.push(&chromosomes[index1]);
-        } else {
-            v.push(&chromosomes[index2]);
-        }
+// Index of the winner and both contestants of a tournament of two
+pub fn tournament_pick<G>(chromosomes: &[Chromosome<G>], comparison: Comparison, rng: &mut Pcg64) -> (usize, (usize, usize)) {
+    let index1 = rng.gen_range(0..chromosomes.len());
+    let index2 = rng.gen_range(0..chromosomes.len());
+    if comparison.compare_for_selection(&chromosomes[index1], &chromosomes[index2]) == Ordering::Greater {
+        return (index1, (index1, index2));
     }
-    return (v[0], v[1]);
+    return (index2, (index1, index2));
 }
 
-pub fn calculate_fitness(steps: usize, found_treasures: u32, all_treasures: u32) -> f64 {
-    let mut fitness: f64 = found_treasures as f64 / all_treasures as f64;
-    fitness -= steps as f64 * 0.005;
-    if fitness < 0.0 {
-        fitness = 0.0;
+// FNV-1a hash, identical on every platform
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    return fnv1a_bytes(bytes.iter().copied());
+}
+
+// Same as fnv1a for bytes which are not stored together
+pub fn fnv1a_bytes(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
     }
-    return fitness;
+    return hash;
 }
 
-pub fn build_game_area() -> Vec<Vec<u8>> {
-    let mut game_area: Vec<Vec<u8>> = vec![vec![AREA_TILE_NOTHING; 7]; 7];
-    game_area[1][4] = AREA_TILE_TREASURE;
-    game_area[2][2] = AREA_TILE_TREASURE;
-    game_area[3][6] = AREA_TILE_TREASURE;
-    game_area[4][1] = AREA_TILE_TREASURE;
-    game_area[5][4] = AREA_TILE_TREASURE;
-    game_area[6][3] = AREA_TILE_PLAYER;
+pub fn build_game_area() -> GameArea {
+    let mut game_area = GameArea::new(7, 7);
+    game_area.set(4, 1, AREA_TILE_TREASURE);
+    game_area.set(2, 2, AREA_TILE_TREASURE);
+    game_area.set(6, 3, AREA_TILE_TREASURE);
+    game_area.set(1, 4, AREA_TILE_TREASURE);
+    game_area.set(4, 5, AREA_TILE_TREASURE);
+    game_area.set(3, 6, AREA_TILE_PLAYER);
     return game_area;
-}
\ No newline at end of file
+}
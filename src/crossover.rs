@@ -0,0 +1,46 @@
+use ::core::fmt;
+use alloc::vec::Vec;
+
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+use crate::core::Genes;
+
+// Combines the genes of two parents into the genes of a child
+pub trait CrossoverOperator<G = Genes>: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Child genes and for every byte a mask of the bits taken from the second parent, empty for other genomes
+    fn crossover(&self, parent1: &G, parent2: &G, rng: &mut Pcg64) -> (G, Vec<u8>);
+}
+
+impl<G> fmt::Debug for dyn CrossoverOperator<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.name());
+    }
+}
+
+// Every bit is taken from either parent with the same probability
+#[derive(Clone, Copy, Debug)]
+pub struct UniformCrossover;
+
+impl CrossoverOperator for UniformCrossover {
+    fn name(&self) -> &'static str {
+        return "uniform";
+    }
+
+    fn crossover(&self, parent1: &Genes, parent2: &Genes, rng: &mut Pcg64) -> (Genes, Vec<u8>) {
+        let mut genes: Genes = Genes::zeroed(parent1.len().min(parent2.len()));
+        let mut from_parent2: Vec<u8> = Vec::with_capacity(genes.len());
+        // One random word holds the masks of eight bytes
+        let mut mask: [u8; 8] = [0; 8];
+        for (i, ((gene, &gene1), &gene2)) in genes.iter_mut().zip(parent1.iter()).zip(parent2.iter()).enumerate() {
+            if i % 8 == 0 {
+                mask = rng.gen::<u64>().to_le_bytes();
+            }
+            let second: u8 = mask[i % 8];
+            *gene = gene1 & !second | gene2 & second;
+            from_parent2.push(second);
+        }
+        return (genes, from_parent2);
+    }
+}
@@ -0,0 +1,72 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use crate::core::{Direction, Position};
+use crate::map::GameArea;
+
+pub const UNREACHABLE: u32 = u32::MAX;
+
+// Number of moves from the given tile to every tile of the map, row after row
+pub fn distance_field(game_area: &GameArea, from: (usize, usize)) -> Vec<u32> {
+    let columns = game_area.width();
+    let mut field: Vec<u32> = vec![UNREACHABLE; columns * game_area.height()];
+    let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+    field[from.1 * columns + from.0] = 0;
+    queue.push_back(from);
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = field[y * columns + x] + 1;
+        for direction in Direction::ALL {
+            let next = Position::new(x as isize, y as isize).step(direction);
+            let (nx, ny) = next.tile();
+            if game_area.contains(next) && field[ny * columns + nx] == UNREACHABLE {
+                field[ny * columns + nx] = distance;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    return field;
+}
+
+// Distance fields from every treasure of a map, computed once per map and shared by everything scoring distances
+pub struct TreasureDistances {
+    pub treasures: Vec<(usize, usize)>,
+    width: usize,
+    height: usize,
+    // The field of every treasure one after another
+    fields: Vec<u32>,
+}
+
+impl TreasureDistances {
+    pub fn new(game_area: &GameArea) -> TreasureDistances {
+        let treasures: Vec<(usize, usize)> = game_area.treasures().collect();
+        let fields = treasures.iter().flat_map(|&treasure| distance_field(game_area, treasure)).collect();
+        return TreasureDistances { treasures, width: game_area.width(), height: game_area.height(), fields };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    // Moves from the tile to the treasure with the given index
+    pub fn distance(&self, treasure: usize, x: usize, y: usize) -> u32 {
+        return self.fields[(treasure * self.height + y) * self.width + x];
+    }
+
+    // Smallest distance reached to every treasure which was not collected
+    pub fn closest_uncollected<'a>(&'a self, trajectory: &'a [(usize, usize)], collected: &'a [(usize, usize)]) -> impl Iterator<Item = u32> + 'a {
+        return self.uncollected(collected)
+            .map(|treasure| trajectory.iter().map(|&(x, y)| self.distance(treasure, x, y)).min().unwrap_or(UNREACHABLE));
+    }
+
+    // Distance from the position to the closest treasure which was not collected
+    pub fn nearest_uncollected(&self, x: usize, y: usize, collected: &[(usize, usize)]) -> Option<u32> {
+        return self.uncollected(collected).map(|treasure| self.distance(treasure, x, y)).min();
+    }
+
+    fn uncollected<'a>(&'a self, collected: &'a [(usize, usize)]) -> impl Iterator<Item = usize> + 'a {
+        return (0..self.treasures.len()).filter(|&treasure| !collected.contains(&self.treasures[treasure]));
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::core::StepAlphabet;
+use crate::instruction::Instruction;
+use crate::vm::{InstructionSet, VmState};
+
+pub fn format_state(state: &VmState, instruction_set: InstructionSet, step_alphabet: StepAlphabet) -> String {
+    let mut output = String::new();
+    writeln!(output, "Iteration: {}", state.iteration).unwrap();
+    writeln!(output, "Instruction index: {}", state.instruction_index).unwrap();
+    writeln!(output, "Next instruction: {} ({:#04x})",
+             Instruction::from_byte(state.memory[state.instruction_index], instruction_set).name(),
+             state.memory[state.instruction_index]).unwrap();
+    writeln!(output, "Position: {}, {}", state.player.x, state.player.y).unwrap();
+    writeln!(output, "Found treasures: {}", state.found_treasures).unwrap();
+    writeln!(output, "Steps: {} ({})", step_alphabet.format(state.steps), state.steps.len()).unwrap();
+    writeln!(output, "Memory:").unwrap();
+    for (row, chunk) in state.memory.chunks(8).enumerate() {
+        write!(output, "{:02}:", row * 8).unwrap();
+        for byte in chunk {
+            write!(output, " {:02x}", byte).unwrap();
+        }
+        writeln!(output).unwrap();
+    }
+    return output;
+}
+
+pub fn write_state(directory: &Path, state: &VmState, instruction_set: InstructionSet,
+                   step_alphabet: StepAlphabet) -> std::io::Result<PathBuf> {
+    let path = directory.join(format!("dump_{}.txt", state.iteration));
+    std::fs::write(&path, format_state(state, instruction_set, step_alphabet))?;
+    return Ok(path);
+}
+
+// Parses a comma separated list of iterations, e.g. "10,50,100"
+pub fn parse_iterations(text: &str) -> Result<Vec<u32>, std::num::ParseIntError> {
+    let mut iterations: Vec<u32> = text.split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| value.trim().parse::<u32>())
+        .collect::<Result<_, _>>()?;
+    iterations.sort_unstable();
+    iterations.dedup();
+    return Ok(iterations);
+}
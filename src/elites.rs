@@ -0,0 +1,83 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+use crate::core::{Chromosome, Comparison};
+use crate::map::GameArea;
+
+// Best chromosome for every combination of path length and map coverage
+pub struct Archive {
+    pub bins: usize,
+    // Path lengths at or above the limit share the last bin
+    pub max_steps: usize,
+    pub cells: Vec<Option<Chromosome>>,
+}
+
+impl Archive {
+    pub fn new(bins: usize, max_steps: usize) -> Archive {
+        let bins = bins.max(1);
+        return Archive { bins, max_steps: max_steps.max(1), cells: (0..bins * bins).map(|_| None).collect() };
+    }
+
+    // Row is the coverage bin, column the path length bin
+    pub fn cell_index(&self, steps: usize, coverage: f64) -> usize {
+        let column = (steps * self.bins / self.max_steps).min(self.bins - 1);
+        let row = ((coverage * self.bins as f64) as usize).min(self.bins - 1);
+        return row * self.bins + column;
+    }
+
+    // Keeps the chromosome if its cell is empty or holds a worse one
+    pub fn insert(&mut self, chromosome: Chromosome, coverage: f64, comparison: Comparison) -> bool {
+        let index = self.cell_index(chromosome.steps.len(), coverage);
+        let better = match &self.cells[index] {
+            None => true,
+            Some(elite) => comparison.compare(&chromosome, elite) == Ordering::Greater,
+        };
+        if better {
+            self.cells[index] = Some(chromosome);
+        }
+        return better;
+    }
+
+    pub fn elites(&self) -> impl Iterator<Item = &Chromosome> {
+        return self.cells.iter().flatten();
+    }
+
+    pub fn random_elite(&self, rng: &mut Pcg64) -> Option<&Chromosome> {
+        // Counted and walked again instead of collected, parents are drawn for every child
+        let filled = self.elites().count();
+        if filled == 0 {
+            return None;
+        }
+        return self.elites().nth(rng.gen_range(0..filled));
+    }
+
+    // Sum of elite fitness, the usual quality-diversity score
+    pub fn qd_score(&self) -> f64 {
+        return self.elites().map(|elite| elite.fitness).sum();
+    }
+}
+
+// Share of map tiles the player stood on
+pub fn coverage(trajectory: &[(usize, usize)], game_area: &GameArea) -> f64 {
+    let mut visited: Vec<(usize, usize)> = trajectory.to_vec();
+    visited.sort_unstable();
+    visited.dedup();
+    return visited.len() as f64 / (game_area.height() * game_area.width()) as f64;
+}
+
+pub fn print_archive(archive: &Archive) {
+    let filled = archive.elites().count();
+    println!("Filled cells: {}/{}, QD score: {:.4}", filled, archive.cells.len(), archive.qd_score());
+    println!("Rows: map coverage (highest first), Columns: path length up to {} steps", archive.max_steps);
+    for row in (0..archive.bins).rev() {
+        for column in 0..archive.bins {
+            match &archive.cells[row * archive.bins + column] {
+                Some(elite) => print!("{:5.2} ", elite.fitness),
+                None => print!("  ·   "),
+            }
+        }
+        println!();
+    }
+}
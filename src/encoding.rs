@@ -0,0 +1,57 @@
+use crate::core::{Chromosome, StepAlphabet};
+use crate::program::{self, Program};
+use crate::vm::{self, InstructionSet};
+
+// Ways of printing the genome of a solution
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GenomeFormat {
+    // The printed list of bytes, e.g. "[200, 193, 147]"
+    Bytes,
+    Hex,
+    Base64,
+    Disassembly,
+    Moves,
+}
+
+impl GenomeFormat {
+    pub fn parse(text: &str) -> Option<GenomeFormat> {
+        return match text.to_ascii_lowercase().as_str() {
+            "bytes" => Some(GenomeFormat::Bytes),
+            "hex" => Some(GenomeFormat::Hex),
+            "base64" => Some(GenomeFormat::Base64),
+            "asm" | "disassembly" => Some(GenomeFormat::Disassembly),
+            "moves" => Some(GenomeFormat::Moves),
+            _ => None,
+        };
+    }
+}
+
+// A single format is printed without a label so the output can be pasted back
+pub fn print_genome(chromosome: &Chromosome, formats: &[GenomeFormat], instruction_set: InstructionSet, step_alphabet: StepAlphabet) {
+    print!("{}", format_genome(chromosome, formats, instruction_set, step_alphabet));
+}
+
+// The lines print_genome() prints
+pub fn format_genome(chromosome: &Chromosome, formats: &[GenomeFormat], instruction_set: InstructionSet, step_alphabet: StepAlphabet) -> String {
+    let labeled = formats.len() > 1;
+    let mut lines: Vec<String> = Vec::new();
+    for format in formats {
+        match format {
+            GenomeFormat::Bytes if labeled => lines.push(format!("Genome: {:?}", chromosome.genes)),
+            GenomeFormat::Bytes => lines.push(format!("{:?}", chromosome.genes)),
+            GenomeFormat::Hex if labeled => lines.push(format!("Hex: {}", program::to_hex(&chromosome.genes))),
+            GenomeFormat::Hex => lines.push(program::to_hex(&chromosome.genes)),
+            GenomeFormat::Base64 if labeled => lines.push(format!("Base64: {:#}", Program::new(chromosome.genes.to_vec()))),
+            GenomeFormat::Base64 => lines.push(format!("{:#}", Program::new(chromosome.genes.to_vec()))),
+            GenomeFormat::Moves if labeled => lines.push(format!("Moves: {}", step_alphabet.format(&chromosome.steps))),
+            GenomeFormat::Moves => lines.push(step_alphabet.format(&chromosome.steps)),
+            GenomeFormat::Disassembly => {
+                if labeled {
+                    lines.push(String::from("Disassembly:"));
+                }
+                lines.extend(vm::disassemble(&chromosome.genes, instruction_set, step_alphabet));
+            }
+        }
+    }
+    return lines.into_iter().map(|line| line + "\n").collect();
+}
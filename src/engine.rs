@@ -0,0 +1,1217 @@
+use ::core::cmp::Ordering;
+use ::core::sync::atomic::{self, AtomicBool};
+use ::core::time::Duration;
+use alloc::{boxed::Box, format, string::String, sync::Arc, vec, vec::Vec};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+use crate::core::{self, Chromosome, Comparison, Genes, Genome};
+use crate::crossover::{CrossoverOperator, UniformCrossover};
+use crate::ensemble::{Aggregation, Case};
+use crate::environment::{Environment, EvalResult, GridEnvironment};
+use crate::error::Error;
+use crate::fitness::FitnessFunction;
+use crate::mutation::{BitFlipMutation, MutationOperator};
+use crate::noise::{Resampling, SampleStats};
+use crate::population::Population;
+use crate::reporter::Reporter;
+use crate::scaling::FitnessScaling;
+use crate::selection::{RouletteSelection, SelectionStrategy};
+use crate::stats::{GenerationStats, OperatorStats, Stopwatch, Throughput};
+use crate::vm::{CollisionPolicy, InstructionSet, Vm, VmConfig, MAX_MEMORY_SIZE};
+
+// Settings of the genetic algorithm, the machine and when the search stops
+#[derive(Clone, Debug)]
+pub struct GaConfig<G = Genes> {
+    pub subjects: usize,
+    pub mutation_probability: f64,
+    pub selection: Arc<dyn SelectionStrategy<G>>,
+    pub crossover: Arc<dyn CrossoverOperator<G>>,
+    // Applied to every child with the mutation probability
+    pub mutation: Arc<dyn MutationOperator<G>>,
+    // Best chromosomes copied unchanged into the next generation
+    pub elitism: usize,
+    pub scaling: FitnessScaling,
+    pub comparison: Comparison,
+    pub aggregation: Aggregation,
+    pub resampling: Resampling,
+    pub vm: VmConfig,
+    // Generation budget of run()
+    pub generations: u32,
+    // Time budget of run(), at least one generation is evaluated. Ignored without the std feature.
+    pub max_seconds: Option<f64>,
+    // Evaluation budget of run(), the generation which reaches it evaluates only the chromosomes it leaves room for
+    pub max_evaluations: Option<u64>,
+    // Generations run() searches on for a better solution after the first one, None until a budget is spent
+    pub after_solution: Option<u32>,
+    // Threads evaluating the population, 0 for one per core, the results do not depend on it.
+    // Ignored without the parallel feature.
+    pub threads: usize,
+    // Genomes the environment evaluates together, e.g. programs run in lockstep; 1 evaluates them one by one.
+    // The results do not depend on it.
+    pub batch_size: usize,
+    // Once a full solution is found, evaluations are given up as soon as they cannot beat it any more.
+    // Their fitness is then that of the run so far, still worse than the solution. Ignored with resampling.
+    pub early_exit: bool,
+}
+
+impl GaConfig {
+    pub fn builder() -> GaConfigBuilder {
+        return GaConfigBuilder { config: GaConfig::default() };
+    }
+}
+
+impl<G: Genome> GaConfig<G> {
+    // Genomes other than programs of the machine have no default operators
+    pub fn builder_for(crossover: Arc<dyn CrossoverOperator<G>>, mutation: Arc<dyn MutationOperator<G>>) -> GaConfigBuilder<G> {
+        return GaConfigBuilder { config: GaConfig::with_operators(crossover, mutation) };
+    }
+
+    fn with_operators(crossover: Arc<dyn CrossoverOperator<G>>, mutation: Arc<dyn MutationOperator<G>>) -> GaConfig<G> {
+        return GaConfig {
+            subjects: 100,
+            mutation_probability: 0.01,
+            selection: Arc::new(RouletteSelection),
+            crossover,
+            mutation,
+            elitism: 0,
+            scaling: FitnessScaling::None,
+            comparison: Comparison::Fitness,
+            aggregation: Aggregation::Mean,
+            resampling: Resampling::new(1, None),
+            vm: VmConfig::default(),
+            generations: 1000,
+            max_seconds: None,
+            max_evaluations: None,
+            after_solution: Some(0),
+            threads: 1,
+            batch_size: 1,
+            early_exit: false,
+        };
+    }
+
+    // Hash of every setting but the budgets, the threads and the batch size, which do not change the search.
+    // The seed of the machine is left out as it follows from the seed of the search. The fields are hashed one by one
+    // in a fixed order, a new field does not compile until it is added here or left out.
+    pub fn fingerprint(&self) -> u64 {
+        let GaConfig {
+            subjects, mutation_probability, selection, crossover, mutation, elitism, scaling, comparison, aggregation,
+            resampling, vm, generations: _, max_seconds: _, max_evaluations: _, after_solution: _, threads: _,
+            batch_size: _, early_exit,
+        } = self;
+        let VmConfig {
+            iteration_limit, memory_size, collision_policy, instruction_set, max_moves, move_bound, memory_mapped_inputs,
+            record_trajectory, seed: _,
+        } = vm;
+        let mut bytes: Vec<u8> = Vec::new();
+        let text = |bytes: &mut Vec<u8>, text: &str| {
+            bytes.extend_from_slice(&(text.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(text.as_bytes());
+        };
+        bytes.extend_from_slice(&(*subjects as u64).to_le_bytes());
+        bytes.extend_from_slice(&mutation_probability.to_bits().to_le_bytes());
+        text(&mut bytes, selection.name());
+        text(&mut bytes, crossover.name());
+        text(&mut bytes, mutation.name());
+        bytes.extend_from_slice(&(*elitism as u64).to_le_bytes());
+        let (scaling, scaling_parameter): (u8, f64) = match *scaling {
+            FitnessScaling::None => (0, 0.0),
+            FitnessScaling::Linear { multiple } => (1, multiple),
+            FitnessScaling::SigmaTruncation { c } => (2, c),
+            FitnessScaling::Rank => (3, 0.0),
+        };
+        bytes.push(scaling);
+        bytes.extend_from_slice(&scaling_parameter.to_bits().to_le_bytes());
+        bytes.push(match comparison {
+            Comparison::Fitness => 0,
+            Comparison::Lexicographic => 1,
+        });
+        let weights: &[f64] = match aggregation {
+            Aggregation::Mean => {
+                bytes.push(0);
+                &[]
+            }
+            Aggregation::Minimum => {
+                bytes.push(1);
+                &[]
+            }
+            Aggregation::WeightedSum(weights) => {
+                bytes.push(2);
+                weights
+            }
+        };
+        bytes.extend_from_slice(&(weights.len() as u64).to_le_bytes());
+        weights.iter().for_each(|weight| bytes.extend_from_slice(&weight.to_bits().to_le_bytes()));
+        bytes.extend_from_slice(&resampling.samples.to_le_bytes());
+        bytes.extend_from_slice(&resampling.max_samples.to_le_bytes());
+        bytes.extend_from_slice(&iteration_limit.to_le_bytes());
+        bytes.extend_from_slice(&(*memory_size as u64).to_le_bytes());
+        bytes.push(match collision_policy {
+            CollisionPolicy::Terminate => 0,
+            CollisionPolicy::Block => 1,
+            CollisionPolicy::Wrap => 2,
+        });
+        bytes.push(match instruction_set {
+            InstructionSet::Classic => 0,
+            InstructionSet::Extended => 1,
+        });
+        bytes.extend_from_slice(&max_moves.to_le_bytes());
+        bytes.extend_from_slice(&move_bound.to_le_bytes());
+        bytes.extend_from_slice(&[u8::from(*memory_mapped_inputs), u8::from(*record_trajectory), u8::from(*early_exit)]);
+        return core::fnv1a(&bytes);
+    }
+}
+
+impl Default for GaConfig {
+    fn default() -> Self {
+        return GaConfig::with_operators(Arc::new(UniformCrossover), Arc::new(BitFlipMutation));
+    }
+}
+
+pub struct GaConfigBuilder<G = Genes> {
+    config: GaConfig<G>,
+}
+
+impl<G> GaConfigBuilder<G> {
+    pub fn subjects(mut self, subjects: usize) -> Self {
+        self.config.subjects = subjects.max(2);
+        return self;
+    }
+
+    pub fn mutation_probability(mut self, mutation_probability: f64) -> Self {
+        self.config.mutation_probability = mutation_probability.clamp(0.0, 1.0);
+        return self;
+    }
+
+    // Selection::strategy() for the built-in strategies
+    pub fn selection(mut self, selection: Arc<dyn SelectionStrategy<G>>) -> Self {
+        self.config.selection = selection;
+        return self;
+    }
+
+    pub fn crossover(mut self, crossover: Arc<dyn CrossoverOperator<G>>) -> Self {
+        self.config.crossover = crossover;
+        return self;
+    }
+
+    pub fn mutation(mut self, mutation: Arc<dyn MutationOperator<G>>) -> Self {
+        self.config.mutation = mutation;
+        return self;
+    }
+
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.config.elitism = elitism;
+        return self;
+    }
+
+    pub fn scaling(mut self, scaling: FitnessScaling) -> Self {
+        self.config.scaling = scaling;
+        return self;
+    }
+
+    pub fn comparison(mut self, comparison: Comparison) -> Self {
+        self.config.comparison = comparison;
+        return self;
+    }
+
+    pub fn aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.config.aggregation = aggregation;
+        return self;
+    }
+
+    pub fn resampling(mut self, resampling: Resampling) -> Self {
+        self.config.resampling = resampling;
+        return self;
+    }
+
+    pub fn vm(mut self, vm: VmConfig) -> Self {
+        self.config.vm = vm;
+        return self;
+    }
+
+    pub fn generations(mut self, generations: u32) -> Self {
+        self.config.generations = generations;
+        return self;
+    }
+
+    pub fn max_seconds(mut self, max_seconds: Option<f64>) -> Self {
+        self.config.max_seconds = max_seconds;
+        return self;
+    }
+
+    pub fn max_evaluations(mut self, max_evaluations: Option<u64>) -> Self {
+        self.config.max_evaluations = max_evaluations;
+        return self;
+    }
+
+    pub fn after_solution(mut self, after_solution: Option<u32>) -> Self {
+        self.config.after_solution = after_solution;
+        return self;
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        return self;
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.config.batch_size = batch_size;
+        return self;
+    }
+
+    pub fn early_exit(mut self, early_exit: bool) -> Self {
+        self.config.early_exit = early_exit;
+        return self;
+    }
+
+    pub fn build(self) -> GaConfig<G> {
+        let mut config = self.config;
+        if config.comparison == Comparison::Lexicographic && config.scaling != FitnessScaling::Rank {
+            // Roulette needs numbers consistent with the lexicographic order
+            config.scaling = FitnessScaling::Rank;
+        }
+        return config;
+    }
+}
+
+// Outcome of one generation
+#[derive(Clone, Debug)]
+pub struct GenerationReport<G = Genes> {
+    pub generation: u32,
+    // Best chromosome of the generation
+    pub best: Chromosome<G>,
+    // Whether it is better than every chromosome before it
+    pub improved: bool,
+    pub stats: GenerationStats,
+    pub throughput: Throughput,
+}
+
+// Why run() ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopReason {
+    // Every treasure was collected, after_solution generations before
+    Solved,
+    GenerationLimit,
+    TimeLimit,
+    EvaluationLimit,
+    // An observer asked to stop
+    Stopped,
+    Cancelled,
+}
+
+impl StopReason {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            StopReason::Solved => "solved",
+            StopReason::GenerationLimit => "generation_limit",
+            StopReason::TimeLimit => "time_limit",
+            StopReason::EvaluationLimit => "evaluation_limit",
+            StopReason::Stopped => "stopped",
+            StopReason::Cancelled => "cancelled",
+        };
+    }
+}
+
+// Outcome of run()
+#[derive(Clone, Debug)]
+pub struct RunResult<G = Genes> {
+    // Best chromosome so far, None only when the generation budget is 0
+    pub best: Option<Chromosome<G>>,
+    pub generations: u32,
+    pub evaluations: u64,
+    // Zero without the std feature
+    pub wall_time: Duration,
+    pub stop_reason: StopReason,
+    // None for environments without maps
+    pub map_fingerprint: Option<u64>,
+    pub config_fingerprint: u64,
+}
+
+// State of the search between two generations, enough to continue it exactly.
+// Serializable with the std feature, which the random state needs.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Snapshot<G = Genes> {
+    // Generations evaluated before it was taken
+    pub generation: u32,
+    pub evaluations: u64,
+    pub rng: Pcg64,
+    // Genomes of the next generation to evaluate
+    pub population: Vec<G>,
+    pub best: Option<Chromosome<G>>,
+    // Settings changed while the search ran, missing in snapshots of older versions
+    #[cfg_attr(feature = "std", serde(default))]
+    pub mutation_probability: Option<f64>,
+    #[cfg_attr(feature = "std", serde(default))]
+    pub elitism: Option<usize>,
+    // restore() refuses a snapshot of other settings or maps
+    #[cfg_attr(feature = "std", serde(default))]
+    pub config_fingerprint: Option<u64>,
+    #[cfg_attr(feature = "std", serde(default))]
+    pub map_fingerprint: Option<u64>,
+    // Generation of the first full solution, which the search after it counts from
+    #[cfg_attr(feature = "std", serde(default))]
+    pub first_solution: Option<u32>,
+}
+
+// Notified after every generation stepped by the engine
+pub trait Observer<G = Genes>: Send {
+    // Statistics of the evaluated population and the best chromosome so far, false stops run()
+    fn generation(&mut self, stats: &GenerationStats, best: &Chromosome<G>) -> bool;
+}
+
+impl<G, F: FnMut(&GenerationStats, &Chromosome<G>) -> bool + Send> Observer<G> for F {
+    fn generation(&mut self, stats: &GenerationStats, best: &Chromosome<G>) -> bool {
+        return self(stats, best);
+    }
+}
+
+fn validate_grid(config: &GaConfig, cases: &[Case]) -> Result<(), Error> {
+    if cases.is_empty() {
+        return Err(Error::InvalidConfig(String::from("at least one case is needed")));
+    }
+    if config.vm.memory_size == 0 || config.vm.memory_size > MAX_MEMORY_SIZE {
+        return Err(Error::InvalidConfig(format!("memory size {} is not between 1 and {}", config.vm.memory_size, MAX_MEMORY_SIZE)));
+    }
+    return Ok(());
+}
+
+// Holds the place of the environment while wrap_environment() moves it, never evaluates anything
+struct Detached;
+
+impl<G> Environment<G> for Detached {
+    fn evaluate(&self, _genome: &G, _seed: Option<u64>) -> EvalResult {
+        unreachable!("the environment is being wrapped");
+    }
+
+    fn random_genome(&self, _rng: &mut Pcg64) -> G {
+        unreachable!("the environment is being wrapped");
+    }
+
+    fn is_solution(&self, _chromosome: &Chromosome<G>) -> bool {
+        unreachable!("the environment is being wrapped");
+    }
+}
+
+// Statistics of the last generation and the best chromosome so far
+#[derive(Clone, Debug)]
+pub struct Progress<G = Genes> {
+    pub stats: GenerationStats,
+    pub best: Chromosome<G>,
+}
+
+// Observer shared with other threads, e.g. a user interface polling an engine stepped on a worker thread
+#[cfg(feature = "std")]
+pub struct SharedProgress<G = Genes>(Arc<std::sync::Mutex<Option<Progress<G>>>>);
+
+#[cfg(feature = "std")]
+impl<G: Genome> SharedProgress<G> {
+    pub fn new() -> SharedProgress<G> {
+        return SharedProgress(Arc::new(std::sync::Mutex::new(None)));
+    }
+
+    // None before the first generation
+    pub fn latest(&self) -> Option<Progress<G>> {
+        return self.0.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G: Genome> Default for SharedProgress<G> {
+    fn default() -> Self {
+        return SharedProgress::new();
+    }
+}
+
+// Clones share the same progress
+#[cfg(feature = "std")]
+impl<G> Clone for SharedProgress<G> {
+    fn clone(&self) -> Self {
+        return SharedProgress(Arc::clone(&self.0));
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G: Genome> Observer<G> for SharedProgress<G> {
+    fn generation(&mut self, stats: &GenerationStats, best: &Chromosome<G>) -> bool {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(Progress { stats: stats.clone(), best: best.clone() });
+        return true;
+    }
+}
+
+// Sent by the engine to the receiver of events(), for user interfaces and servers reading them on another thread
+#[derive(Clone, Debug)]
+pub enum Event<G = Genes> {
+    GenerationCompleted(GenerationStats),
+    // Better than every chromosome before it
+    NewBest { generation: u32, best: Chromosome<G> },
+    // A new best chromosome which is a solution
+    SolutionFound { generation: u32, solution: Chromosome<G> },
+    // Random population keeping the best chromosome
+    Restarted { generation: u32 },
+    // Cancelled before the next generation, followed by Finished
+    Interrupted { generation: u32 },
+    // The search stopped with the best chromosome so far, None only when the generation budget is 0.
+    // Raising a budget continues it until the next one.
+    Finished { generation: u32, reason: StopReason, best: Option<Chromosome<G>> },
+}
+
+// Stops an engine from another thread or a signal handler, checked before every generation
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        return CancellationToken::default();
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        return self.0.load(atomic::Ordering::SeqCst);
+    }
+}
+
+// Evolves genomes for an environment one generation at a time
+pub struct Engine<'a, G: Genome = Genes> {
+    config: GaConfig<G>,
+    environment: Box<dyn Environment<G> + 'a>,
+    rng: Pcg64,
+    population: Population<G>,
+    // Emptied buffer of the previous generation, the next one is bred into it instead of a new allocation
+    spare: Vec<Chromosome<G>>,
+    generation: u32,
+    evaluations: u64,
+    best: Option<Chromosome<G>>,
+    // Generation the first solution was found in
+    first_solution: Option<u32>,
+    observers: Vec<Box<dyn Observer<G> + 'a>>,
+    // Set once an observer asked to stop
+    stop_requested: bool,
+    cancellation: CancellationToken,
+    #[cfg(feature = "std")]
+    events: Option<std::sync::mpsc::Sender<Event<G>>>,
+    reporters: Vec<Box<dyn Reporter<G> + 'a>>,
+    // Of the generation last evaluated, completed by breed()
+    throughput: Throughput,
+    // Fitness of the better parent and whether the child was mutated, in the order of the bred population until
+    // it is evaluated; None for elites
+    lineage: Vec<Option<(f64, bool)>>,
+    // Of the generation last evaluated
+    operator_stats: OperatorStats,
+    // None with a single thread
+    #[cfg(feature = "parallel")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl<'a> Engine<'a> {
+    // Programs of the machine on the cases, the seed of the machine is drawn from the same stream as the population
+    pub fn new(mut config: GaConfig, fitness_function: &'a dyn FitnessFunction, cases: &'a [Case], seed: u64) -> Result<Engine<'a>, Error> {
+        validate_grid(&config, cases)?;
+        let mut rng = Pcg64::seed_from_u64(seed);
+        config.vm.seed = rng.gen();
+        let environment = GridEnvironment::new(Vm::new(config.vm.clone()), fitness_function, cases, config.aggregation.clone());
+        return Engine::with_rng(config, Box::new(environment), rng);
+    }
+
+    // Same as new(), but the engine keeps the fitness function and the cases, e.g. behind a handle of another language
+    pub fn owned(mut config: GaConfig, fitness_function: Box<dyn FitnessFunction>, cases: Vec<Case>, seed: u64) -> Result<Engine<'static>, Error> {
+        validate_grid(&config, &cases)?;
+        let mut rng = Pcg64::seed_from_u64(seed);
+        config.vm.seed = rng.gen();
+        let environment = GridEnvironment::owned(Vm::new(config.vm.clone()), fitness_function, cases, config.aggregation.clone());
+        return Engine::with_rng(config, Box::new(environment), rng);
+    }
+}
+
+impl<'a, G: Genome> Engine<'a, G> {
+    // Any other problem or genome, the machine settings of the configuration are not used
+    pub fn with_environment(config: GaConfig<G>, environment: Box<dyn Environment<G> + 'a>, seed: u64) -> Result<Engine<'a, G>, Error> {
+        return Engine::with_rng(config, environment, Pcg64::seed_from_u64(seed));
+    }
+
+    // Random population
+    fn with_rng(config: GaConfig<G>, environment: Box<dyn Environment<G> + 'a>, mut rng: Pcg64) -> Result<Engine<'a, G>, Error> {
+        if config.subjects < 2 {
+            return Err(Error::InvalidConfig(format!("{} subjects cannot have offspring, at least 2 are needed", config.subjects)));
+        }
+        if !(0.0..=1.0).contains(&config.mutation_probability) {
+            return Err(Error::InvalidConfig(format!("mutation probability {} is not between 0 and 1", config.mutation_probability)));
+        }
+        let population: Population<G> = Population::from_genomes((0..config.subjects).map(|_| environment.random_genome(&mut rng)));
+        #[cfg(feature = "parallel")]
+        let pool = match config.threads {
+            1 => None,
+            threads => Some(rayon::ThreadPoolBuilder::new().num_threads(threads).build()
+                .map_err(|e| Error::InvalidConfig(format!("cannot start {} threads: {}", threads, e)))?),
+        };
+        return Ok(Engine {
+            config,
+            environment,
+            rng,
+            population,
+            spare: Vec::new(),
+            generation: 0,
+            evaluations: 0,
+            best: None,
+            first_solution: None,
+            observers: Vec::new(),
+            stop_requested: false,
+            cancellation: CancellationToken::new(),
+            #[cfg(feature = "std")]
+            events: None,
+            reporters: Vec::new(),
+            throughput: Throughput::default(),
+            lineage: Vec::new(),
+            operator_stats: OperatorStats::default(),
+            #[cfg(feature = "parallel")]
+            pool,
+        });
+    }
+
+    // Replaces the environment with one built around it, e.g. to evaluate elsewhere, which has to give the same results
+    pub fn wrap_environment(&mut self, wrap: impl FnOnce(Box<dyn Environment<G> + 'a>) -> Box<dyn Environment<G> + 'a>) {
+        let environment = ::core::mem::replace(&mut self.environment, Box::new(Detached));
+        self.environment = wrap(environment);
+    }
+
+    // Only valid before evaluate() or after breed(), when the population is not evaluated yet
+    pub fn snapshot(&self) -> Snapshot<G> {
+        return Snapshot {
+            generation: self.generation,
+            evaluations: self.evaluations,
+            rng: self.rng.clone(),
+            population: self.population.chromosomes().iter().map(|chromosome| chromosome.genes.clone()).collect(),
+            best: self.best.clone(),
+            mutation_probability: Some(self.config.mutation_probability),
+            elitism: Some(self.config.elitism),
+            config_fingerprint: Some(self.config.fingerprint()),
+            map_fingerprint: self.environment.map_fingerprint(),
+            first_solution: self.first_solution,
+        };
+    }
+
+    // Continues a search where the snapshot was taken, bit for bit the same as if it had not stopped.
+    // The engine is left unchanged when the snapshot was taken with other settings or maps.
+    pub fn restore(&mut self, snapshot: Snapshot<G>) -> Result<(), Error> {
+        let mut config = self.config.clone();
+        config.mutation_probability = snapshot.mutation_probability.unwrap_or(config.mutation_probability);
+        config.elitism = snapshot.elitism.unwrap_or(config.elitism);
+        if snapshot.config_fingerprint.is_some_and(|fingerprint| fingerprint != config.fingerprint()) {
+            return Err(Error::InvalidConfig(String::from("the snapshot was taken with other settings")));
+        }
+        if snapshot.map_fingerprint.is_some() && snapshot.map_fingerprint != self.environment.map_fingerprint() {
+            return Err(Error::InvalidMap(String::from("the snapshot was taken on other maps")));
+        }
+        self.config = config;
+        self.rng = snapshot.rng;
+        self.population = Population::from_genomes(snapshot.population);
+        self.generation = snapshot.generation;
+        self.evaluations = snapshot.evaluations;
+        self.best = snapshot.best;
+        // Snapshots of older versions lack it, the search after a solution then counts from the snapshot
+        self.first_solution = snapshot.first_solution.or_else(|| Some(self.generation).filter(|_| self.solved()));
+        self.lineage.clear();
+        return Ok(());
+    }
+
+    // Replaces the population of the next evaluation from its start, e.g. with a saved one, the rest stays random.
+    // Genomes beyond the size of the population are ignored, returns how many were taken.
+    pub fn seed_population(&mut self, genomes: impl IntoIterator<Item = G>) -> usize {
+        let mut seeded: usize = 0;
+        for (chromosome, genes) in self.population.chromosomes_mut().iter_mut().zip(genomes) {
+            *chromosome = Chromosome::with_genes(genes);
+            seeded += 1;
+        }
+        self.lineage.clear();
+        return seeded;
+    }
+
+    pub fn config(&self) -> &GaConfig<G> {
+        return &self.config;
+    }
+
+    pub fn environment(&self) -> &dyn Environment<G> {
+        return self.environment.as_ref();
+    }
+
+    pub fn rng(&self) -> &Pcg64 {
+        return &self.rng;
+    }
+
+    // Generations evaluated so far
+    pub fn generation(&self) -> u32 {
+        return self.generation;
+    }
+
+    pub fn evaluations(&self) -> u64 {
+        return self.evaluations;
+    }
+
+    pub fn population(&self) -> &Population<G> {
+        return &self.population;
+    }
+
+    pub fn best(&self) -> Option<&Chromosome<G>> {
+        return self.best.as_ref();
+    }
+
+    pub fn solved(&self) -> bool {
+        return self.best.as_ref().is_some_and(|best| self.environment.is_solution(best));
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        return self.stop_requested;
+    }
+
+    // Cancels run() and iter() of this engine, the best chromosome found so far stays available
+    pub fn cancellation_token(&self) -> CancellationToken {
+        return self.cancellation.clone();
+    }
+
+    // Shares one token between several engines
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    pub fn cancelled(&self) -> bool {
+        return self.cancellation.is_cancelled();
+    }
+
+    // Called in the order they were added after every step()
+    pub fn add_observer(&mut self, observer: impl Observer<G> + 'a) {
+        self.observers.push(Box::new(observer));
+    }
+
+    // Refused like by new() unless between 0 and 1
+    pub fn set_mutation_probability(&mut self, mutation_probability: f64) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&mutation_probability) {
+            return Err(Error::InvalidConfig(format!("mutation probability {} is not between 0 and 1", mutation_probability)));
+        }
+        self.config.mutation_probability = mutation_probability;
+        return Ok(());
+    }
+
+    // Refused unless some subjects are left for offspring
+    pub fn set_elitism(&mut self, elitism: usize) -> Result<(), Error> {
+        if elitism >= self.config.subjects {
+            return Err(Error::InvalidConfig(format!("elitism {} leaves no offspring among {} subjects", elitism, self.config.subjects)));
+        }
+        self.config.elitism = elitism;
+        return Ok(());
+    }
+
+    // E.g. to search on once the budget is spent
+    pub fn set_generations(&mut self, generations: u32) {
+        self.config.generations = generations;
+    }
+
+    pub fn set_after_solution(&mut self, after_solution: Option<u32>) {
+        self.config.after_solution = after_solution;
+    }
+
+    pub fn set_max_evaluations(&mut self, max_evaluations: Option<u64>) {
+        self.config.max_evaluations = max_evaluations;
+    }
+
+    // Ends run() and iter() before the next generation, as an observer returning false does
+    pub fn stop(&mut self) {
+        self.stop_requested = true;
+    }
+
+    // New random population, the best chromosome survives
+    pub fn restart(&mut self) {
+        for (i, chromosome) in self.population.chromosomes_mut().iter_mut().enumerate() {
+            chromosome.genes = match &self.best {
+                Some(best) if i == 0 => best.genes.clone(),
+                _ => self.environment.random_genome(&mut self.rng),
+            };
+        }
+        self.lineage.clear();
+        self.emit(|engine| Event::Restarted { generation: engine.generation });
+    }
+
+    // Events of every following step(), restart() and end of iter() or run(), a second call replaces the receiver of the first one
+    #[cfg(feature = "std")]
+    pub fn events(&mut self) -> std::sync::mpsc::Receiver<Event<G>> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.events = Some(sender);
+        return receiver;
+    }
+
+    // Reports the events of every following step(), restart() and end of iter() or run()
+    pub fn add_reporter(&mut self, reporter: impl Reporter<G> + 'a) {
+        self.reporters.push(Box::new(reporter));
+    }
+
+    // Built only when someone receives it, a dropped receiver is ignored and a failing reporter only logged
+    fn emit(&mut self, event: impl FnOnce(&Self) -> Event<G>) {
+        #[cfg(feature = "std")]
+        let receiving = self.events.is_some();
+        #[cfg(not(feature = "std"))]
+        let receiving = false;
+        if !receiving && self.reporters.is_empty() {
+            return;
+        }
+        let event = event(self);
+        for reporter in &mut self.reporters {
+            if let Err(e) = reporter.report(&event) {
+                tracing::warn!(error = %e, "reporter failed");
+            }
+        }
+        #[cfg(feature = "std")]
+        if let Some(sender) = &self.events {
+            sender.send(event).ok();
+        }
+    }
+
+    // Evaluates the current population and sorts it from the best. Without resampling and with a selection strategy
+    // which does not need the order only the elites, at least the best chromosome, are sorted to the front.
+    pub fn evaluate(&mut self) -> &Population<G> {
+        self.generation += 1;
+        let resampling = self.config.resampling;
+        let mut throughput = Throughput::default();
+        let evaluation = Stopwatch::start();
+        if self.config.early_exit && !resampling.enabled() {
+            let solution = self.best.as_ref().filter(|best| self.environment.is_solution(best));
+            self.environment.bound_by(solution, self.config.comparison);
+        }
+        let budget_left: Option<u64> = self.config.max_evaluations.map(|max_evaluations| max_evaluations.saturating_sub(self.evaluations));
+        if let Some(budget_left) = budget_left {
+            // At least one chromosome so that there is a best solution, the rest is dropped before breeding
+            let room: u64 = budget_left.div_ceil(u64::from(resampling.samples.max(1)));
+            self.population.truncate(usize::try_from(room).unwrap_or(usize::MAX).max(1));
+            self.lineage.truncate(self.population.len());
+        }
+        let jobs: Vec<(usize, &G, Option<u64>)> = self.population.chromosomes().iter().enumerate()
+            .map(|(i, chromosome)| (i, &chromosome.genes, None))
+            .collect();
+        let results = self.evaluate_batch(jobs);
+        // Seeds of the extra samples are drawn in the order of the population, whichever thread evaluates them
+        let mut sample_jobs: Vec<(usize, &G, Option<u64>)> = Vec::new();
+        for (i, (chromosome, result)) in self.population.chromosomes().iter().zip(&results).enumerate() {
+            if result.evaluated {
+                sample_jobs.extend((1..resampling.samples).map(|_| (i, &chromosome.genes, Some(self.rng.gen()))));
+            }
+        }
+        if let Some(budget_left) = budget_left {
+            let evaluated: u64 = results.iter().filter(|result| result.evaluated).count() as u64;
+            sample_jobs.truncate(usize::try_from(budget_left.saturating_sub(evaluated)).unwrap_or(usize::MAX));
+        }
+        let sampled: Vec<usize> = sample_jobs.iter().map(|&(i, _, _)| i).collect();
+        let mut sample_fitness: Vec<Vec<f64>> = vec![Vec::new(); results.len()];
+        for (i, result) in sampled.into_iter().zip(self.evaluate_batch(sample_jobs)) {
+            throughput.iterations += u64::from(result.iterations);
+            sample_fitness[i].push(result.fitness);
+        }
+        let evaluated = self.population.chromosomes_mut().iter_mut().zip(results).zip(sample_fitness);
+        for ((chromosome, result), samples) in evaluated {
+            chromosome.found_treasures = result.found_treasures;
+            chromosome.iterations = result.iterations;
+            chromosome.complexity = result.complexity;
+            chromosome.fitness = result.fitness;
+            chromosome.steps = result.steps;
+            chromosome.case_fitness = result.case_fitness;
+            chromosome.fitness_stats = SampleStats::default();
+            if !result.evaluated {
+                continue;
+            }
+
+            self.evaluations += 1;
+            throughput.evaluations += 1;
+            throughput.iterations += u64::from(result.iterations);
+            if result.bounded {
+                throughput.bounded += 1;
+                throughput.skipped_iterations += u64::from(result.skipped_iterations);
+            }
+            chromosome.fitness_stats.push(result.fitness);
+            for fitness in samples {
+                self.evaluations += 1;
+                throughput.evaluations += 1;
+                chromosome.fitness_stats.push(fitness);
+            }
+            chromosome.fitness = chromosome.fitness_stats.mean;
+            tracing::trace!(fitness = chromosome.fitness, treasures = chromosome.found_treasures,
+                            steps = chromosome.steps.len(), iterations = chromosome.iterations, "evaluated");
+        }
+        let mut operator_stats = OperatorStats::default();
+        if self.lineage.len() == self.population.chromosomes().len() {
+            for (chromosome, lineage) in self.population.chromosomes().iter().zip(&self.lineage) {
+                let Some((parent_fitness, mutated)) = *lineage else {
+                    continue;
+                };
+                let improved = u32::from(chromosome.fitness > parent_fitness);
+                if mutated {
+                    operator_stats.mutation_offspring += 1;
+                    operator_stats.mutation_improved += improved;
+                } else {
+                    operator_stats.crossover_offspring += 1;
+                    operator_stats.crossover_improved += improved;
+                }
+            }
+        }
+        self.lineage.clear();
+        self.operator_stats = operator_stats;
+
+        throughput.evaluation_time = evaluation.elapsed();
+
+        let selection = Stopwatch::start();
+        // Extra samples count as evaluation
+        let mut resampling_time = Duration::ZERO;
+        let comparison = self.config.comparison;
+        if resampling.enabled() || self.config.selection.needs_sorted() {
+            self.population.sort_by_fitness(comparison);
+        } else {
+            self.population.sort_best(self.config.elitism.max(1), comparison);
+        }
+        if resampling.enabled() {
+            // Neighbours which cannot be told apart get extra samples before selection
+            let chromosomes = self.population.chromosomes_mut();
+            for i in 0..chromosomes.len() - 1 {
+                if !chromosomes[i].fitness_stats.overlaps(&chromosomes[i + 1].fitness_stats) {
+                    continue;
+                }
+                for chromosome in &mut chromosomes[i..=i + 1] {
+                    while chromosome.fitness_stats.count < resampling.max_samples
+                        && self.config.max_evaluations.is_none_or(|max_evaluations| self.evaluations < max_evaluations) {
+                        let resample = Stopwatch::start();
+                        let result = self.environment.evaluate(&chromosome.genes, Some(self.rng.gen()));
+                        self.evaluations += 1;
+                        throughput.evaluations += 1;
+                        throughput.iterations += u64::from(result.iterations);
+                        resampling_time += resample.elapsed();
+                        chromosome.fitness_stats.push(result.fitness);
+                    }
+                    chromosome.fitness = chromosome.fitness_stats.mean;
+                }
+            }
+            self.population.sort_by_fitness(comparison);
+        }
+        self.population.scale(self.config.scaling, comparison);
+        self.config.selection.prepare(&mut self.population);
+        throughput.evaluation_time += resampling_time;
+        throughput.selection_time = selection.elapsed().saturating_sub(resampling_time);
+        self.throughput = throughput;
+        return &self.population;
+    }
+
+    // Every genome with its seed, on the thread pool of the engine when there is one, results in the order of the jobs.
+    // Consecutive jobs with the same seed are evaluated batch_size at a time.
+    fn evaluate_batch(&self, jobs: Vec<(usize, &G, Option<u64>)>) -> Vec<EvalResult> {
+        let environment = &*self.environment;
+        let evaluate = |chunk: &[(usize, &G, Option<u64>)]| -> Vec<EvalResult> {
+            let (first, _, seed) = chunk[0];
+            if chunk.len() > 1 && chunk.iter().all(|job| job.2 == seed) {
+                let _evaluation = tracing::trace_span!("evaluation", chromosomes = chunk.len(), first).entered();
+                let genomes: Vec<&G> = chunk.iter().map(|job| job.1).collect();
+                return environment.evaluate_many(&genomes, seed);
+            }
+            return chunk.iter().map(|&(i, genes, seed)| {
+                let _evaluation = tracing::trace_span!("evaluation", chromosome = i).entered();
+                environment.evaluate(genes, seed)
+            }).collect();
+        };
+        let batch_size = self.config.batch_size.max(1);
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            use rayon::prelude::*;
+            return pool.install(|| jobs.par_chunks(batch_size).flat_map_iter(evaluate).collect());
+        }
+        return jobs.chunks(batch_size).flat_map(evaluate).collect();
+    }
+
+    // Of the last evaluated generation, the reproduction time once it is bred
+    pub fn throughput(&self) -> Throughput {
+        return self.throughput;
+    }
+
+    // Of the last evaluated generation
+    pub fn operator_stats(&self) -> OperatorStats {
+        return self.operator_stats;
+    }
+
+    // Replaces the evaluated population with its offspring, true if its best chromosome is the best so far
+    pub fn breed(&mut self) -> bool {
+        let subjects_num = self.config.subjects;
+        let comparison = self.config.comparison;
+        let mut new_generation: Vec<Chromosome<G>> = ::core::mem::take(&mut self.spare);
+        new_generation.reserve(subjects_num);
+        self.lineage.clear();
+        for elite in self.population.chromosomes().iter().take(self.config.elitism.min(subjects_num)) {
+            new_generation.push(Chromosome::with_genes(elite.genes.clone()));
+            self.lineage.push(None);
+        }
+        let reproduction = Stopwatch::start();
+        let mut selection_time = Duration::ZERO;
+        while new_generation.len() < subjects_num {
+            let selection = Stopwatch::start();
+            let strategy = self.config.selection.as_ref();
+            let parent1 = &self.population.chromosomes()[self.population.select(strategy, comparison, &mut self.rng)];
+            let parent2 = &self.population.chromosomes()[self.population.select(strategy, comparison, &mut self.rng)];
+            selection_time += selection.elapsed();
+
+            let mut iterations = subjects_num - new_generation.len();
+            if iterations > core::NUM_OF_CHILDREN as usize {
+                iterations = core::NUM_OF_CHILDREN as usize;
+            }
+            let parent_fitness = parent1.fitness.max(parent2.fitness);
+            for _ in 0..iterations {
+                // Same as core::reproduce, but tells whether a bit was flipped
+                let (mut genes, _) = self.config.crossover.crossover(&parent1.genes, &parent2.genes, &mut self.rng);
+                let mutated = self.config.mutation.mutate(&mut genes, self.config.mutation_probability, &mut self.rng);
+                new_generation.push(Chromosome::with_genes(genes));
+                self.lineage.push(Some((parent_fitness, mutated.iter().any(|&mask| mask != 0))));
+            }
+        }
+
+        debug_assert_eq!(new_generation.len(), subjects_num);
+        self.throughput.selection_time += selection_time;
+        self.throughput.reproduction_time = reproduction.elapsed().saturating_sub(selection_time);
+        let mut evaluated: Vec<Chromosome<G>> = self.population.replace(new_generation);
+        let local_best = &evaluated[0];
+        let improved = match &self.best {
+            None => true,
+            Some(value) => comparison.compare(local_best, value) == Ordering::Greater,
+        };
+        if improved {
+            self.best = Some(local_best.clone());
+        }
+        evaluated.clear();
+        self.spare = evaluated;
+        return improved;
+    }
+
+    // Evaluates one generation and breeds the next one
+    pub fn step(&mut self) -> GenerationReport<G> {
+        let best = self.evaluate().best().unwrap().clone();
+        let stats = self.population.stats(self.generation);
+        let improved = self.breed();
+        if let Some(best_so_far) = &self.best {
+            for observer in &mut self.observers {
+                if !observer.generation(&stats, best_so_far) {
+                    self.stop_requested = true;
+                }
+            }
+        }
+        self.emit(|_| Event::GenerationCompleted(stats.clone()));
+        if improved {
+            self.emit(|engine| Event::NewBest { generation: engine.generation, best: engine.best.clone().unwrap() });
+            if self.solved() {
+                self.first_solution.get_or_insert(self.generation);
+                self.emit(|engine| Event::SolutionFound { generation: engine.generation, solution: engine.best.clone().unwrap() });
+            }
+        }
+        return GenerationReport { generation: self.generation, best, improved, stats, throughput: self.throughput };
+    }
+
+    // Steps one generation per item, ends like run()
+    pub fn iter(&mut self) -> Generations<'_, 'a, G> {
+        return Generations {
+            engine: self,
+            finished: false,
+            #[cfg(feature = "std")]
+            start_time: std::time::Instant::now(),
+        };
+    }
+
+    // Steps until every treasure is collected, a budget of the configuration is spent, an observer stops it or it is cancelled
+    pub fn run(&mut self) -> RunResult<G> {
+        return self.run_with(|_| {});
+    }
+
+    // Same as run(), every stepped generation is passed to the callback
+    pub fn run_with(&mut self, mut on_generation: impl FnMut(GenerationReport<G>)) -> RunResult<G> {
+        let mut generations = self.iter();
+        for report in &mut generations {
+            on_generation(report);
+        }
+        let stop_reason = generations.stop_reason().unwrap();
+        let wall_time = generations.elapsed();
+        return RunResult {
+            best: self.best.clone(),
+            generations: self.generation,
+            evaluations: self.evaluations,
+            wall_time,
+            stop_reason,
+            map_fingerprint: self.environment.map_fingerprint(),
+            config_fingerprint: self.config.fingerprint(),
+        };
+    }
+}
+
+// Iterator over the generations of an engine
+pub struct Generations<'e, 'a, G: Genome = Genes> {
+    engine: &'e mut Engine<'a, G>,
+    // Whether Finished was emitted since the last generation
+    finished: bool,
+    #[cfg(feature = "std")]
+    start_time: std::time::Instant,
+}
+
+impl<'a, G: Genome> Generations<'_, 'a, G> {
+    // Why the search ends before the next generation, None while it goes on
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        let engine = &*self.engine;
+        if let (Some(found), Some(after_solution)) = (engine.first_solution, engine.config.after_solution) {
+            if engine.generation >= found.saturating_add(after_solution) {
+                return Some(StopReason::Solved);
+            }
+        }
+        if engine.cancelled() {
+            return Some(StopReason::Cancelled);
+        }
+        if engine.stop_requested {
+            return Some(StopReason::Stopped);
+        }
+        if engine.generation >= engine.config.generations {
+            return Some(StopReason::GenerationLimit);
+        }
+        // At least one generation is evaluated so there is a best solution
+        #[cfg(feature = "std")]
+        if engine.best.is_some()
+            && engine.config.max_seconds.is_some_and(|max_seconds| self.start_time.elapsed().as_secs_f64() >= max_seconds) {
+            return Some(StopReason::TimeLimit);
+        }
+        if engine.best.is_some() && engine.config.max_evaluations.is_some_and(|max_evaluations| engine.evaluations >= max_evaluations) {
+            return Some(StopReason::EvaluationLimit);
+        }
+        return None;
+    }
+
+    // Between two generations, e.g. to change its settings or take a snapshot
+    pub fn engine(&mut self) -> &mut Engine<'a, G> {
+        return self.engine;
+    }
+
+    // Since iter(), zero without the std feature
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        return self.start_time.elapsed();
+        #[cfg(not(feature = "std"))]
+        return Duration::ZERO;
+    }
+}
+
+impl<G: Genome> Iterator for Generations<'_, '_, G> {
+    type Item = GenerationReport<G>;
+
+    fn next(&mut self) -> Option<GenerationReport<G>> {
+        if let Some(reason) = self.stop_reason() {
+            if !self.finished {
+                self.finished = true;
+                if reason == StopReason::Cancelled {
+                    self.engine.emit(|engine| Event::Interrupted { generation: engine.generation });
+                }
+                self.engine.emit(|engine| Event::Finished { generation: engine.generation, reason, best: engine.best.clone() });
+            }
+            return None;
+        }
+        self.finished = false;
+        return Some(self.engine.step());
+    }
+}
+
+// A host application can move the engine to a worker thread
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Engine<'static>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::fitness::TreasureFitness;
+    use crate::map;
+
+    // Generation, best fitness so far and the statistics of every stepped generation
+    type Trace = Vec<(u32, f64, String)>;
+
+    fn cases() -> Vec<Case> {
+        let game_area = map::parse("P . . T\n. . . .\nT . . .\n. . T .").unwrap();
+        return vec![Case { name: String::from("map"), game_area }];
+    }
+
+    fn config() -> GaConfig {
+        return GaConfig::builder().subjects(40).mutation_probability(0.05).elitism(2).generations(500).after_solution(Some(6)).build();
+    }
+
+    fn trace(engine: &mut Engine, trace: &mut Trace) -> RunResult {
+        return engine.run_with(|report| trace.push((report.generation, report.best.fitness, format!("{:?}", report.stats))));
+    }
+
+    #[test]
+    fn restored_snapshot_continues_like_the_uninterrupted_run() {
+        let (fitness, cases) = (TreasureFitness, cases());
+        let mut uninterrupted = Engine::new(config(), &fitness, &cases, 42).unwrap();
+        let mut expected: Trace = Vec::new();
+        let result = trace(&mut uninterrupted, &mut expected);
+        assert_eq!(result.stop_reason, StopReason::Solved);
+        let first_solution = uninterrupted.first_solution.unwrap();
+        assert_eq!(result.generations, first_solution + 6);
+
+        // Stopped between the first solution and the end of the search after it
+        let mut interrupted = Engine::new(config(), &fitness, &cases, 42).unwrap();
+        while interrupted.generation() < first_solution + 2 {
+            interrupted.step();
+        }
+        let snapshot = interrupted.snapshot();
+        #[cfg(feature = "std")]
+        let snapshot: Snapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        let mut resumed = Engine::new(config(), &fitness, &cases, 42).unwrap();
+        resumed.restore(snapshot).unwrap();
+        let mut continued: Trace = Vec::new();
+        let resumed_result = trace(&mut resumed, &mut continued);
+        assert_eq!(continued, expected[(first_solution + 2) as usize..]);
+        assert_eq!(resumed_result.generations, result.generations);
+        assert_eq!(resumed_result.evaluations, result.evaluations);
+        assert_eq!(resumed_result.stop_reason, StopReason::Solved);
+    }
+
+    #[test]
+    fn restore_refuses_other_settings() {
+        let (fitness, cases) = (TreasureFitness, cases());
+        let snapshot = Engine::new(config(), &fitness, &cases, 1).unwrap().snapshot();
+        let other = GaConfig::builder().subjects(41).build();
+        assert!(Engine::new(other, &fitness, &cases, 1).unwrap().restore(snapshot).is_err());
+    }
+
+    #[test]
+    fn fingerprint_covers_the_search_settings() {
+        // Checkpoints and manifests of earlier runs are compared against it, it must not change unnoticed
+        let fingerprint = config().fingerprint();
+        assert_eq!(fingerprint, 0x2217_7236_b3c8_47a8);
+        let mut same = config();
+        same.generations = 7;
+        same.max_seconds = Some(1.0);
+        same.max_evaluations = Some(100);
+        same.after_solution = None;
+        same.threads = 4;
+        same.batch_size = 8;
+        same.vm.seed = 3;
+        assert_eq!(same.fingerprint(), fingerprint);
+
+        let changes: [fn(&mut GaConfig); 9] = [
+            |config| config.subjects += 1,
+            |config| config.mutation_probability = 0.5,
+            |config| config.selection = crate::core::Selection::Tournament.strategy(),
+            |config| config.elitism = 0,
+            |config| config.scaling = FitnessScaling::Linear { multiple: 2.0 },
+            |config| config.aggregation = Aggregation::WeightedSum(vec![1.0]),
+            |config| config.vm.memory_size = 32,
+            |config| config.vm.instruction_set = InstructionSet::Extended,
+            |config| config.early_exit = !config.early_exit,
+        ];
+        for change in changes {
+            let mut other = config();
+            change(&mut other);
+            assert_ne!(other.fingerprint(), fingerprint);
+        }
+    }
+}
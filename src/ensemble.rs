@@ -0,0 +1,165 @@
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::core::{self, AREA_TILE_NOTHING, AREA_TILE_PLAYER, AREA_TILE_TREASURE};
+use crate::error::Error;
+use crate::map::{self, GameArea};
+
+// One map a chromosome is evaluated on
+pub struct Case {
+    pub name: String,
+    pub game_area: GameArea,
+}
+
+// How per-case fitness values are combined into one
+#[derive(Clone, Debug, PartialEq)]
+pub enum Aggregation {
+    Mean,
+    // Worst case
+    Minimum,
+    WeightedSum(Vec<f64>),
+}
+
+impl Aggregation {
+    // Accepts "mean", "min" or "weighted:<w1>,<w2>,..."
+    pub fn parse(text: &str) -> Option<Aggregation> {
+        if let Some(weights) = text.strip_prefix("weighted:") {
+            let weights: Vec<f64> = weights.split(',').map(|weight| weight.trim().parse::<f64>().ok()).collect::<Option<_>>()?;
+            return Some(Aggregation::WeightedSum(weights));
+        }
+        return match text.to_ascii_lowercase().as_str() {
+            "mean" => Some(Aggregation::Mean),
+            "min" | "minimum" => Some(Aggregation::Minimum),
+            _ => None,
+        };
+    }
+
+    pub fn combine(&self, values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        return match self {
+            Aggregation::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregation::Minimum => values.iter().copied().fold(f64::INFINITY, f64::min),
+            // Missing weights count as 1
+            Aggregation::WeightedSum(weights) => values.iter().enumerate()
+                .map(|(i, value)| value * weights.get(i).copied().unwrap_or(1.0))
+                .sum(),
+        };
+    }
+}
+
+// The map itself followed by a copy of it for every additional start position
+pub fn start_cases(game_area: &GameArea, starts: &[(usize, usize)]) -> Result<Vec<Case>, Error> {
+    let mut cases: Vec<Case> = vec![Case { name: String::from("default start"), game_area: game_area.clone() }];
+    for &(x, y) in starts {
+        if x >= game_area.width() || y >= game_area.height() {
+            return Err(Error::InvalidMap(format!("start {},{} is outside of the map", x, y)));
+        }
+        if game_area.get(x, y) == AREA_TILE_TREASURE {
+            return Err(Error::InvalidMap(format!("start {},{} is a treasure", x, y)));
+        }
+        let mut case_area = game_area.clone();
+        let (player_x, player_y) = case_area.player().tile();
+        case_area.set(player_x, player_y, AREA_TILE_NOTHING);
+        case_area.set(x, y, AREA_TILE_PLAYER);
+        cases.push(Case { name: format!("start {},{}", x, y), game_area: case_area });
+    }
+    return Ok(cases);
+}
+
+// Cases of start_cases for every named map, the case names start with the map name when there are several.
+// The maps need as many treasures, so that the worst case tells whether every treasure was collected on all of them.
+pub fn map_cases(maps: &[(String, GameArea)], starts: &[(usize, usize)]) -> Result<Vec<Case>, Error> {
+    if let [(_, game_area)] = maps {
+        return start_cases(game_area, starts);
+    }
+    let treasures = maps.first().map_or(0, |(_, game_area)| game_area.treasure_count());
+    let mut cases: Vec<Case> = Vec::new();
+    for (name, game_area) in maps {
+        if game_area.treasure_count() != treasures {
+            return Err(Error::InvalidMap(format!("{} has {} treasures and the first map {}, the maps need as many",
+                                                 name, game_area.treasure_count(), treasures)));
+        }
+        let map_cases = start_cases(game_area, starts).map_err(|e| Error::InvalidMap(format!("{}: {}", name, e)))?;
+        cases.extend(map_cases.into_iter().map(|case| Case { name: format!("{} {}", name, case.name), game_area: case.game_area }));
+    }
+    return Ok(cases);
+}
+
+// Hash of the maps, that of map::fingerprint for a single one
+pub fn maps_fingerprint<'m>(maps: impl IntoIterator<Item = &'m GameArea>) -> u64 {
+    let fingerprints: Vec<u64> = maps.into_iter().map(map::fingerprint).collect();
+    if let [fingerprint] = fingerprints[..] {
+        return fingerprint;
+    }
+    return core::fnv1a_bytes(fingerprints.into_iter().flat_map(u64::to_le_bytes));
+}
+
+// Parses "x,y;x,y;..."
+pub fn parse_starts(text: &str) -> Option<Vec<(usize, usize)>> {
+    return text.split(';')
+        .filter(|start| !start.trim().is_empty())
+        .map(|start| {
+            let (x, y) = start.split_once(',')?;
+            return Some((x.trim().parse::<usize>().ok()?, y.trim().parse::<usize>().ok()?));
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::{Environment, GridEnvironment};
+    use crate::fitness::TreasureFitness;
+    use crate::instruction::Instruction;
+    use crate::vm::{Vm, VmConfig};
+
+    fn maps() -> Vec<(String, GameArea)> {
+        return vec![
+            (String::from("right.txt"), map::parse("P T .\n. . .").unwrap()),
+            (String::from("down.txt"), map::parse("P . .\nT . .").unwrap()),
+        ];
+    }
+
+    #[test]
+    fn cases_of_every_map_and_start() {
+        let cases = map_cases(&maps(), &[(2, 1)]).unwrap();
+        let names: Vec<&str> = cases.iter().map(|case| case.name.as_str()).collect();
+        assert_eq!(names, ["right.txt default start", "right.txt start 2,1", "down.txt default start", "down.txt start 2,1"]);
+        assert_eq!(cases[3].game_area.player().tile(), (2, 1));
+        // A single map keeps the names of its start positions
+        assert_eq!(map_cases(&maps()[..1], &[]).unwrap()[0].name, "default start");
+
+        let mut uneven = maps();
+        uneven[1].1 = map::parse("P . T\nT . .").unwrap();
+        assert!(map_cases(&uneven, &[]).is_err());
+        // Every map has to have the start position
+        assert!(map_cases(&maps(), &[(1, 0)]).is_err());
+    }
+
+    #[test]
+    fn aggregation_across_maps() {
+        let cases = map_cases(&maps(), &[]).unwrap();
+        // Moves right twice: the treasure of the first map only
+        let right = Instruction::Move(core::Direction::Right).to_byte();
+        let program = [right; 2];
+        let vm = Vm::new(VmConfig::builder().memory_size(program.len()).iteration_limit(2).build());
+        for (aggregation, fitness) in [(Aggregation::Mean, 0.5), (Aggregation::Minimum, 0.0), (Aggregation::WeightedSum(vec![2.0, 1.0]), 2.0)] {
+            let environment = GridEnvironment::new(vm.reseeded(0), &TreasureFitness, &cases, aggregation.clone());
+            let result = environment.evaluate(&core::Genes::from(program.to_vec()), None);
+            assert_eq!(result.case_fitness, [1.0, 0.0], "{:?}", aggregation);
+            assert_eq!(result.fitness, fitness, "{:?}", aggregation);
+            // The worst case counts, the program does not solve the ensemble
+            assert_eq!(result.found_treasures, 0);
+            let evaluated = environment.evaluate_many(&[&core::Genes::from(program.to_vec())], None);
+            assert_eq!(evaluated[0].fitness, fitness);
+        }
+    }
+
+    #[test]
+    fn fingerprint_of_one_map_is_its_own() {
+        let maps = maps();
+        assert_eq!(maps_fingerprint([&maps[0].1]), map::fingerprint(&maps[0].1));
+        assert_ne!(maps_fingerprint(maps.iter().map(|(_, game_area)| game_area)), map::fingerprint(&maps[0].1));
+    }
+}
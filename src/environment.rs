@@ -0,0 +1,281 @@
+use ::core::ops::Deref;
+use ::core::sync::atomic::{AtomicU32, Ordering};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{self, Chromosome, Comparison, Genes, INSTR};
+use crate::lockstep;
+use crate::ensemble::{Aggregation, Case};
+use crate::fitness::{FitnessContext, FitnessFunction};
+use crate::map;
+use crate::verifier;
+use crate::vm::{InstructionSet, Scratch, Termination, Vm, VmResult};
+
+// Outcome of evaluating one genome
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EvalResult {
+    // False when the genome was rejected without running it
+    pub evaluated: bool,
+    pub fitness: f64,
+    // Fitness of every case the fitness combines
+    pub case_fitness: Vec<f64>,
+    pub found_treasures: u32,
+    pub iterations: u32,
+    pub complexity: u32,
+    pub steps: String,
+    // Given up on at least one case as it could not beat the bound, see Environment::bound_by
+    pub bounded: bool,
+    // Iteration limit left over by the cases given up on
+    pub skipped_iterations: u32,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    // Machine buffers of the evaluations on this thread, one set per worker of a parallel evaluation
+    static SCRATCH: ::core::cell::RefCell<Scratch> = ::core::cell::RefCell::new(Scratch::new());
+}
+
+#[cfg(feature = "std")]
+fn with_scratch<R>(evaluation: impl FnOnce(&mut Scratch) -> R) -> R {
+    return SCRATCH.with_borrow_mut(evaluation);
+}
+
+// Without threads the buffers are only reused among the cases of one evaluation
+#[cfg(not(feature = "std"))]
+fn with_scratch<R>(evaluation: impl FnOnce(&mut Scratch) -> R) -> R {
+    return evaluation(&mut Scratch::new());
+}
+
+// Problem the genetic algorithm searches genomes for
+pub trait Environment<G = Genes>: Send + Sync {
+    // Noisy environments use the seed instead of their own random stream, for resampling
+    fn evaluate(&self, genome: &G, seed: Option<u64>) -> EvalResult;
+    fn random_genome(&self, rng: &mut Pcg64) -> G;
+    // Whether the search can stop at this evaluated chromosome
+    fn is_solution(&self, chromosome: &Chromosome<G>) -> bool;
+
+    // Results in the order of the genomes, environments which can share work between genomes override it
+    fn evaluate_many(&self, genomes: &[&G], seed: Option<u64>) -> Vec<EvalResult> {
+        return genomes.iter().map(|genome| self.evaluate(genome, seed)).collect();
+    }
+
+    // Identifies the maps of the problem, None for problems without maps
+    fn map_fingerprint(&self) -> Option<u64> {
+        return None;
+    }
+
+    // Later evaluations may be given up as soon as they cannot beat the full solution any more, their results are
+    // then worse than it. None evaluates everything again.
+    fn bound_by(&self, _solution: Option<&Chromosome<G>>, _comparison: Comparison) {}
+}
+
+// Borrowed from the caller, or owned when nothing outlives the environment (C and Python handles)
+enum Held<'a, T: ?Sized> {
+    Borrowed(&'a T),
+    Owned(Box<T>),
+}
+
+impl<T: ?Sized> Deref for Held<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        return match self {
+            Held::Borrowed(value) => value,
+            Held::Owned(value) => value,
+        };
+    }
+}
+
+// Programs of the machine collecting treasures on every case
+pub struct GridEnvironment<'a> {
+    vm: Vm,
+    fitness_function: Held<'a, dyn FitnessFunction + 'a>,
+    cases: Held<'a, [Case]>,
+    fitness_context: FitnessContext,
+    aggregation: Aggregation,
+    // Moves after which the machine gives up, u32::MAX unless bound_by() set a bound
+    move_bound: AtomicU32,
+}
+
+impl<'a> GridEnvironment<'a> {
+    pub fn new(vm: Vm, fitness_function: &'a dyn FitnessFunction, cases: &'a [Case], aggregation: Aggregation) -> GridEnvironment<'a> {
+        return GridEnvironment::with_parts(vm, Held::Borrowed(fitness_function), Held::Borrowed(cases), aggregation);
+    }
+
+    pub fn owned(vm: Vm, fitness_function: Box<dyn FitnessFunction>, cases: Vec<Case>, aggregation: Aggregation) -> GridEnvironment<'static> {
+        return GridEnvironment::with_parts(vm, Held::Owned(fitness_function), Held::Owned(cases.into_boxed_slice()), aggregation);
+    }
+
+    fn with_parts(vm: Vm, fitness_function: Held<'a, dyn FitnessFunction + 'a>, cases: Held<'a, [Case]>,
+                  aggregation: Aggregation) -> GridEnvironment<'a> {
+        let treasures = cases[0].game_area.treasure_count();
+        let fitness_context = FitnessContext::new(treasures, vm.config());
+        return GridEnvironment { vm, fitness_function, cases, fitness_context, aggregation, move_bound: AtomicU32::new(u32::MAX) };
+    }
+
+    pub fn vm(&self) -> &Vm {
+        return &self.vm;
+    }
+
+    pub fn fitness_context(&self) -> &FitnessContext {
+        return &self.fitness_context;
+    }
+
+    // The machine of one evaluation, None when it is the machine of the environment itself
+    fn machine(&self, seed: Option<u64>) -> Option<Vm> {
+        let move_bound = self.move_bound.load(Ordering::Relaxed);
+        return match seed {
+            Some(seed) => Some(self.vm.reseeded(seed).bounded(move_bound)),
+            None if move_bound != u32::MAX => Some(self.vm.bounded(move_bound)),
+            None => None,
+        };
+    }
+}
+
+impl Environment for GridEnvironment<'_> {
+    fn evaluate(&self, genome: &Genes, seed: Option<u64>) -> EvalResult {
+        let vm_config = self.vm.config();
+        if verifier::verify_genome(genome, vm_config.instruction_set, vm_config.memory_size).is_err() {
+            // Invalid programs are not evaluated at all
+            return EvalResult { case_fitness: vec![0.0; self.cases.len()], ..EvalResult::default() };
+        }
+        let machine = self.machine(seed);
+        let vm = machine.as_ref().unwrap_or(&self.vm);
+        return with_scratch(|scratch| {
+            let (fitness, case_fitness, result, skipped_iterations) = evaluate_cases(vm, genome, &self.cases, &*self.fitness_function,
+                                                                                  &self.fitness_context, &self.aggregation, scratch);
+            // A copy of the exact length, the buffer goes back to the scratch
+            let evaluation = EvalResult {
+                evaluated: true,
+                fitness,
+                case_fitness,
+                found_treasures: result.found_treasures,
+                iterations: result.iterations,
+                complexity: result.executed_instructions,
+                steps: result.steps.clone(),
+                bounded: skipped_iterations.is_some(),
+                skipped_iterations: skipped_iterations.unwrap_or(0),
+            };
+            scratch.recycle(result);
+            evaluation
+        });
+    }
+
+    // Valid genomes run in lockstep on every case
+    fn evaluate_many(&self, genomes: &[&Genes], seed: Option<u64>) -> Vec<EvalResult> {
+        let vm_config = self.vm.config();
+        let valid: Vec<bool> = genomes.iter()
+            .map(|genome| verifier::verify_genome(genome, vm_config.instruction_set, vm_config.memory_size).is_ok())
+            .collect();
+        let programs: Vec<&[INSTR]> = genomes.iter().zip(&valid).filter(|(_, &valid)| valid).map(|(genome, _)| &genome[..]).collect();
+        let machine = self.machine(seed);
+        let vm = machine.as_ref().unwrap_or(&self.vm);
+        let mut case_results: Vec<vec::IntoIter<VmResult>> = self.cases.iter()
+            .map(|case| lockstep::run_batch(vm, &programs, &case.game_area).into_iter())
+            .collect();
+
+        let mut evaluations: Vec<EvalResult> = Vec::with_capacity(genomes.len());
+        for valid in valid {
+            if !valid {
+                evaluations.push(EvalResult { case_fitness: vec![0.0; self.cases.len()], ..EvalResult::default() });
+                continue;
+            }
+            // Same as evaluate_cases
+            let mut case_fitness: Vec<f64> = Vec::with_capacity(case_results.len());
+            let mut worst: Option<VmResult> = Option::None;
+            let mut skipped_iterations: Option<u32> = None;
+            for results in &mut case_results {
+                let result = results.next().unwrap();
+                case_fitness.push(self.fitness_function.evaluate(&result, &self.fitness_context));
+                if result.termination == Termination::Bounded {
+                    let skipped = skipped_iterations.get_or_insert(0);
+                    *skipped = skipped.saturating_add(vm.config().iteration_limit - result.iterations);
+                }
+                if worst.as_ref().is_none_or(|worst| result.found_treasures < worst.found_treasures) {
+                    worst = Some(result);
+                }
+            }
+            let result = worst.unwrap();
+            evaluations.push(EvalResult {
+                evaluated: true,
+                fitness: self.aggregation.combine(&case_fitness),
+                case_fitness,
+                found_treasures: result.found_treasures,
+                iterations: result.iterations,
+                complexity: result.executed_instructions,
+                steps: result.steps,
+                bounded: skipped_iterations.is_some(),
+                skipped_iterations: skipped_iterations.unwrap_or(0),
+            });
+        }
+        return evaluations;
+    }
+
+    fn random_genome(&self, rng: &mut Pcg64) -> Genes {
+        return Genes::from(random_genome(rng, self.vm.config().instruction_set, self.vm.config().memory_size));
+    }
+
+    fn is_solution(&self, chromosome: &Chromosome) -> bool {
+        return chromosome.found_treasures == self.fitness_context.treasures;
+    }
+
+    // One case keeps the fingerprint of its map, the fingerprints of several are hashed together
+    fn map_fingerprint(&self) -> Option<u64> {
+        if let [case] = &*self.cases {
+            return Some(map::fingerprint(&case.game_area));
+        }
+        let bytes: Vec<u8> = self.cases.iter().flat_map(|case| map::fingerprint(&case.game_area).to_le_bytes()).collect();
+        return Some(core::fnv1a(&bytes));
+    }
+
+    // A bound on the fitness of every case is one on the combined fitness only for a single case or the worst case
+    fn bound_by(&self, solution: Option<&Chromosome>, comparison: Comparison) {
+        let move_bound = match (solution, comparison) {
+            (None, _) => None,
+            // More steps lose to the solution, the same number may still win on fewer iterations
+            (Some(solution), Comparison::Lexicographic) => Some(solution.steps.len() as u32),
+            (Some(solution), Comparison::Fitness) if self.cases.len() == 1 || self.aggregation == Aggregation::Minimum => {
+                self.fitness_function.max_moves_reaching(solution.fitness, &self.fitness_context)
+            }
+            (Some(_), Comparison::Fitness) => None,
+        };
+        self.move_bound.store(move_bound.unwrap_or(u32::MAX), Ordering::Relaxed);
+    }
+}
+
+// Runs the genome on every case, statistics come from the case with the fewest found treasures.
+// Results of the other cases give their buffers back to the scratch. The iterations left over by the cases the machine
+// gave up on at its move bound come last, None when it gave up on none.
+pub fn evaluate_cases(vm: &Vm, genes: &[u8], cases: &[Case], fitness_function: &dyn FitnessFunction,
+                      fitness_context: &FitnessContext, aggregation: &Aggregation, scratch: &mut Scratch) -> (f64, Vec<f64>, VmResult, Option<u32>) {
+    let mut case_fitness: Vec<f64> = Vec::with_capacity(cases.len());
+    let mut worst: Option<VmResult> = Option::None;
+    let mut skipped_iterations: Option<u32> = None;
+    for case in cases {
+        let result = vm.run_in(genes, &case.game_area, scratch);
+        case_fitness.push(fitness_function.evaluate(&result, fitness_context));
+        if result.termination == Termination::Bounded {
+            let skipped = skipped_iterations.get_or_insert(0);
+            *skipped = skipped.saturating_add(vm.config().iteration_limit - result.iterations);
+        }
+        if worst.as_ref().is_none_or(|worst| result.found_treasures < worst.found_treasures) {
+            if let Some(previous) = worst.replace(result) {
+                scratch.recycle(previous);
+            }
+        } else {
+            scratch.recycle(result);
+        }
+    }
+    return (aggregation.combine(&case_fitness), case_fitness, worst.unwrap(), skipped_iterations);
+}
+
+// Smaller memory sizes make many random programs jump past their end
+pub fn random_genome(rng: &mut Pcg64, instruction_set: InstructionSet, program_size: usize) -> Vec<INSTR> {
+    let mut genes = core::random_instructions(rng, program_size);
+    while verifier::verify_genome(&genes, instruction_set, program_size).is_err() {
+        genes = core::random_instructions(rng, program_size);
+    }
+    return genes;
+}
@@ -0,0 +1,46 @@
+use ::core::fmt;
+use alloc::string::String;
+
+// Recoverable errors of the library, the binary decides how to report them
+#[derive(Debug)]
+pub enum Error {
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    // Text which is not in the expected format
+    Parse(String),
+    // Map without a player or treasures, or a start position it does not have
+    InvalidMap(String),
+    // Settings the search cannot run with
+    InvalidConfig(String),
+    // Values a file format has no room for
+    TooLarge(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            #[cfg(feature = "std")]
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(message) | Error::InvalidMap(message) | Error::InvalidConfig(message) | Error::TooLarge(message) => {
+                write!(f, "{}", message)
+            }
+        };
+    }
+}
+
+impl ::core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn ::core::error::Error + 'static)> {
+        return match self {
+            #[cfg(feature = "std")]
+            Error::Io(e) => Some(e),
+            _ => None,
+        };
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        return Error::Io(e);
+    }
+}
@@ -0,0 +1,132 @@
+// C API of the engine, built as a shared library with
+// `cargo rustc --lib --release --features ffi --crate-type cdylib`, declared in include/treasure_search.h
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+
+use crate::engine::Engine;
+use crate::error::Error;
+use crate::spec::EngineSpec;
+use crate::stats::GenerationStats;
+
+thread_local! {
+    // Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// Opaque to C
+pub struct TsEngine {
+    engine: Engine<'static>,
+    stats: Option<GenerationStats>,
+}
+
+// Statistics of the last stepped generation
+#[repr(C)]
+#[derive(Default)]
+pub struct TsStats {
+    pub generation: u32,
+    pub evaluations: u64,
+    pub best_fitness: f64,
+    pub mean_fitness: f64,
+    pub worst_fitness: f64,
+    pub diversity: f64,
+    // Of the best chromosome so far
+    pub best_so_far_fitness: f64,
+    pub best_so_far_treasures: u32,
+    pub best_so_far_steps: u32,
+    pub solved: bool,
+}
+
+fn set_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+}
+
+fn create(config_json: &str) -> Result<TsEngine, Error> {
+    let spec: EngineSpec = serde_json::from_str(config_json).map_err(|e| Error::Parse(e.to_string()))?;
+    return Ok(TsEngine { engine: spec.build()?, stats: None });
+}
+
+// Null when the configuration is invalid, ts_last_error() tells why
+#[no_mangle]
+pub unsafe extern "C" fn ts_engine_new(config_json: *const c_char) -> *mut TsEngine {
+    if config_json.is_null() {
+        set_error(String::from("the configuration is null"));
+        return std::ptr::null_mut();
+    }
+    let Ok(text) = CStr::from_ptr(config_json).to_str() else {
+        set_error(String::from("the configuration is not UTF-8"));
+        return std::ptr::null_mut();
+    };
+    return match create(text) {
+        Ok(engine) => Box::into_raw(Box::new(engine)),
+        Err(e) => {
+            set_error(e.to_string());
+            std::ptr::null_mut()
+        }
+    };
+}
+
+// Steps at most the given number of generations, fewer once every treasure is collected.
+// Returns the number of stepped generations, -1 for a null engine.
+#[no_mangle]
+pub unsafe extern "C" fn ts_engine_step(engine: *mut TsEngine, generations: u32) -> i64 {
+    let Some(handle) = engine.as_mut() else {
+        return -1;
+    };
+    let mut stepped: i64 = 0;
+    while stepped < generations as i64 && !handle.engine.solved() {
+        handle.stats = Some(handle.engine.step().stats);
+        stepped += 1;
+    }
+    return stepped;
+}
+
+// Copies at most the capacity of the buffer and returns the length of the best genome so far, 0 before the first generation
+#[no_mangle]
+pub unsafe extern "C" fn ts_engine_best_genome(engine: *const TsEngine, buffer: *mut u8, capacity: usize) -> usize {
+    let Some(best) = engine.as_ref().and_then(|handle| handle.engine.best()) else {
+        return 0;
+    };
+    if !buffer.is_null() {
+        std::ptr::copy_nonoverlapping(best.genes.as_ptr(), buffer, best.genes.len().min(capacity));
+    }
+    return best.genes.len();
+}
+
+// False for a null engine or before the first generation
+#[no_mangle]
+pub unsafe extern "C" fn ts_engine_stats(engine: *const TsEngine, stats: *mut TsStats) -> bool {
+    let (Some(handle), Some(output)) = (engine.as_ref(), stats.as_mut()) else {
+        return false;
+    };
+    let (Some(last), Some(best)) = (&handle.stats, handle.engine.best()) else {
+        return false;
+    };
+    *output = TsStats {
+        generation: last.generation,
+        evaluations: handle.engine.evaluations(),
+        best_fitness: last.best_fitness,
+        mean_fitness: last.mean_fitness,
+        worst_fitness: last.worst_fitness,
+        diversity: last.diversity,
+        best_so_far_fitness: best.fitness,
+        best_so_far_treasures: best.found_treasures,
+        best_so_far_steps: best.steps.len() as u32,
+        solved: handle.engine.solved(),
+    };
+    return true;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ts_engine_free(engine: *mut TsEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+// Valid until the next failing call on the same thread, null when nothing failed
+#[no_mangle]
+pub extern "C" fn ts_last_error() -> *const c_char {
+    return LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()));
+}
@@ -0,0 +1,327 @@
+use alloc::{boxed::Box, sync::Arc};
+
+use crate::distance::TreasureDistances;
+use crate::map::GameArea;
+use crate::vm::{VmConfig, VmResult};
+
+// Map and machine settings the evaluated result was produced with
+pub struct FitnessContext {
+    pub treasures: u32,
+    pub iteration_limit: u32,
+    pub memory_size: usize,
+}
+
+impl FitnessContext {
+    pub fn new(treasures: u32, vm_config: &VmConfig) -> FitnessContext {
+        return FitnessContext {
+            treasures,
+            iteration_limit: vm_config.iteration_limit,
+            memory_size: vm_config.memory_size,
+        };
+    }
+}
+
+pub trait FitnessFunction: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64;
+
+    // Whether the machine has to record the trajectory of the player
+    fn needs_trajectory(&self) -> bool {
+        return false;
+    }
+
+    // Most moves after which a run can still reach the threshold, whatever else it does; None when moves do not bound it
+    fn max_moves_reaching(&self, _threshold: f64, _context: &FitnessContext) -> Option<u32> {
+        return None;
+    }
+}
+
+// Penalty per step on the 7x7 map, scaled by area for other maps
+pub const REFERENCE_STEP_PENALTY: f64 = 0.005;
+pub const REFERENCE_AREA: usize = 49;
+
+// Share of found treasures minus a penalty for every step
+#[derive(Clone, Copy, Debug)]
+pub struct StepPenaltyFitness {
+    pub coefficient: f64,
+    // Negative fitness is raised to zero
+    pub clamp: bool,
+}
+
+impl StepPenaltyFitness {
+    pub fn for_map(game_area: &GameArea) -> StepPenaltyFitness {
+        let area = game_area.width() * game_area.height();
+        return StepPenaltyFitness {
+            coefficient: REFERENCE_STEP_PENALTY * REFERENCE_AREA as f64 / area as f64,
+            clamp: true,
+        };
+    }
+}
+
+impl FitnessFunction for StepPenaltyFitness {
+    fn name(&self) -> &'static str {
+        return "steps";
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        let mut fitness: f64 = result.found_treasures as f64 / context.treasures as f64;
+        fitness -= result.steps.len() as f64 * self.coefficient;
+        if self.clamp && fitness < 0.0 {
+            fitness = 0.0;
+        }
+        return fitness;
+    }
+
+    // Every treasure found and nothing but the steps counting against it
+    fn max_moves_reaching(&self, threshold: f64, _context: &FitnessContext) -> Option<u32> {
+        if self.coefficient <= 0.0 || (self.clamp && threshold <= 0.0) {
+            return None;
+        }
+        // Rounding errors may only allow one move more
+        return Some(libm::floor((1.0 - threshold) / self.coefficient + 1e-9).max(0.0) as u32);
+    }
+}
+
+// Share of found treasures only, path length does not matter
+pub struct TreasureFitness;
+
+impl FitnessFunction for TreasureFitness {
+    fn name(&self) -> &'static str {
+        return "treasures";
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        return result.found_treasures as f64 / context.treasures as f64;
+    }
+}
+
+// Food pellets eaten, the treasures found as a count as the artificial ant benchmarks report it (89 for the whole
+// Santa Fe trail), see map::parse_trail
+pub struct FoodFitness;
+
+impl FitnessFunction for FoodFitness {
+    fn name(&self) -> &'static str {
+        return "food";
+    }
+
+    fn evaluate(&self, result: &VmResult, _context: &FitnessContext) -> f64 {
+        return result.found_treasures as f64;
+    }
+}
+
+// Adds partial credit for ending close to a treasure which was not collected
+pub struct DistanceShaping {
+    inner: Box<dyn FitnessFunction>,
+    // Bonus for ending next to a treasure, relative to the value of one treasure
+    weight: f64,
+    distances: Arc<TreasureDistances>,
+    max_distance: f64,
+}
+
+impl DistanceShaping {
+    pub fn new(inner: Box<dyn FitnessFunction>, weight: f64, distances: Arc<TreasureDistances>) -> DistanceShaping {
+        let max_distance = (distances.width() + distances.height()) as f64;
+        return DistanceShaping { inner, weight, distances, max_distance };
+    }
+}
+
+impl FitnessFunction for DistanceShaping {
+    fn name(&self) -> &'static str {
+        return self.inner.name();
+    }
+
+    fn needs_trajectory(&self) -> bool {
+        return self.inner.needs_trajectory();
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        let fitness = self.inner.evaluate(result, context);
+        let nearest = self.distances.nearest_uncollected(
+            result.player.x as usize, result.player.y as usize, &result.collected_treasures);
+        return match nearest {
+            Some(distance) if (distance as f64) < self.max_distance => {
+                fitness + self.weight / context.treasures as f64 * (1.0 - distance as f64 / self.max_distance)
+            }
+            _ => fitness,
+        };
+    }
+
+    fn max_moves_reaching(&self, threshold: f64, context: &FitnessContext) -> Option<u32> {
+        return self.inner.max_moves_reaching(threshold - self.weight.max(0.0) / context.treasures as f64, context);
+    }
+}
+
+// Adds partial credit for every treasure which was not collected based on the closest the player got to it
+pub struct ProximityCredit {
+    inner: Box<dyn FitnessFunction>,
+    // Credit for passing right next to a treasure, relative to the value of one treasure
+    weight: f64,
+    distances: Arc<TreasureDistances>,
+    max_distance: f64,
+}
+
+impl ProximityCredit {
+    pub fn new(inner: Box<dyn FitnessFunction>, weight: f64, distances: Arc<TreasureDistances>) -> ProximityCredit {
+        let max_distance = (distances.width() + distances.height()) as f64;
+        return ProximityCredit { inner, weight, distances, max_distance };
+    }
+}
+
+impl FitnessFunction for ProximityCredit {
+    fn name(&self) -> &'static str {
+        return self.inner.name();
+    }
+
+    fn needs_trajectory(&self) -> bool {
+        return true;
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        let mut fitness = self.inner.evaluate(result, context);
+        for distance in self.distances.closest_uncollected(&result.trajectory, &result.collected_treasures) {
+            if (distance as f64) < self.max_distance {
+                fitness += self.weight / context.treasures as f64 * (1.0 - distance as f64 / self.max_distance);
+            }
+        }
+        return fitness;
+    }
+
+    // At most the weight of one treasure for every treasure
+    fn max_moves_reaching(&self, threshold: f64, context: &FitnessContext) -> Option<u32> {
+        return self.inner.max_moves_reaching(threshold - self.weight.max(0.0), context);
+    }
+}
+
+// Among full solutions prefers programs which need fewer iterations
+pub struct IterationBonus {
+    inner: Box<dyn FitnessFunction>,
+    // Bonus for a solution which used no iterations at all
+    weight: f64,
+}
+
+impl IterationBonus {
+    pub fn new(inner: Box<dyn FitnessFunction>, weight: f64) -> IterationBonus {
+        return IterationBonus { inner, weight };
+    }
+}
+
+impl FitnessFunction for IterationBonus {
+    fn name(&self) -> &'static str {
+        return self.inner.name();
+    }
+
+    fn needs_trajectory(&self) -> bool {
+        return self.inner.needs_trajectory();
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        let fitness = self.inner.evaluate(result, context);
+        if result.found_treasures < context.treasures || context.iteration_limit == 0 {
+            return fitness;
+        }
+        let unused = context.iteration_limit.saturating_sub(result.iterations) as f64;
+        return fitness + self.weight * unused / context.iteration_limit as f64;
+    }
+
+    fn max_moves_reaching(&self, threshold: f64, context: &FitnessContext) -> Option<u32> {
+        return self.inner.max_moves_reaching(threshold - self.weight.max(0.0), context);
+    }
+}
+
+// Among equally good programs prefers the ones executing fewer distinct instructions
+pub struct ParsimonyPressure {
+    inner: Box<dyn FitnessFunction>,
+    // Penalty for a program executing every memory cell
+    weight: f64,
+}
+
+impl ParsimonyPressure {
+    pub fn new(inner: Box<dyn FitnessFunction>, weight: f64) -> ParsimonyPressure {
+        return ParsimonyPressure { inner, weight };
+    }
+}
+
+impl FitnessFunction for ParsimonyPressure {
+    fn name(&self) -> &'static str {
+        return self.inner.name();
+    }
+
+    fn needs_trajectory(&self) -> bool {
+        return self.inner.needs_trajectory();
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        let fitness = self.inner.evaluate(result, context);
+        return fitness - self.weight * result.executed_instructions as f64 / context.memory_size as f64;
+    }
+
+    fn max_moves_reaching(&self, threshold: f64, context: &FitnessContext) -> Option<u32> {
+        return self.inner.max_moves_reaching(threshold + self.weight.min(0.0), context);
+    }
+}
+
+// Fitness computed by a rhai expression over the run statistics
+#[cfg(feature = "script")]
+pub struct ScriptFitness {
+    engine: rhai::Engine,
+    ast: rhai::AST,
+    // Whether a failing evaluation was logged, only the first one is
+    failed: std::sync::atomic::AtomicBool,
+}
+
+#[cfg(feature = "script")]
+impl ScriptFitness {
+    pub fn new(script: &str) -> Result<ScriptFitness, String> {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(100_000);
+        let ast = engine.compile_expression(script).or_else(|_| engine.compile(script)).map_err(|e| e.to_string())?;
+        return Ok(ScriptFitness { engine, ast, failed: std::sync::atomic::AtomicBool::new(false) });
+    }
+}
+
+#[cfg(feature = "script")]
+impl FitnessFunction for ScriptFitness {
+    fn name(&self) -> &'static str {
+        return "script";
+    }
+
+    fn evaluate(&self, result: &VmResult, context: &FitnessContext) -> f64 {
+        let mut scope = rhai::Scope::new();
+        scope.push_constant("treasures", result.found_treasures as f64);
+        scope.push_constant("all_treasures", context.treasures as f64);
+        scope.push_constant("steps", result.steps.len() as f64);
+        scope.push_constant("iterations", result.iterations as f64);
+        scope.push_constant("iteration_limit", context.iteration_limit as f64);
+        // Iterations the program had left when it ended
+        scope.push_constant("energy", context.iteration_limit.saturating_sub(result.iterations) as f64);
+        scope.push_constant("complexity", result.executed_instructions as f64);
+        scope.push_constant("memory_size", context.memory_size as f64);
+        let fitness = match self.engine.eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &self.ast) {
+            Ok(value) => value.as_float().or_else(|_| value.as_int().map(|value| value as f64))
+                .map_err(|type_name| format!("the script returned {} instead of a number", type_name)),
+            Err(e) => Err(e.to_string()),
+        };
+        // Failing scripts and NaN score below every chromosome they did not fail on
+        return match fitness {
+            Ok(fitness) if !fitness.is_nan() => fitness,
+            Ok(_) => f64::NEG_INFINITY,
+            Err(e) => {
+                if !self.failed.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                    tracing::warn!(error = %e, "fitness script failed, such chromosomes score -inf");
+                }
+                f64::NEG_INFINITY
+            }
+        };
+    }
+}
+
+pub const FITNESS_FUNCTIONS: [&str; 3] = ["steps", "treasures", "food"];
+
+pub fn by_name(name: &str, step_penalty: StepPenaltyFitness) -> Option<Box<dyn FitnessFunction>> {
+    return match name.to_ascii_lowercase().as_str() {
+        "steps" => Some(Box::new(step_penalty)),
+        "treasures" => Some(Box::new(TreasureFitness)),
+        "food" => Some(Box::new(FoodFitness)),
+        _ => None,
+    };
+}
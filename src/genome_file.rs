@@ -0,0 +1,208 @@
+use alloc::{format, string::String, vec::Vec};
+
+use crate::core::INSTR;
+use crate::error::Error;
+use crate::program::Program;
+use crate::vm::InstructionSet;
+
+// Binary file of one genome, all numbers little endian:
+//   magic "TSGN", version u16, instruction set u8 (0 classic, 1 extended), flags u8 (1: with a map fingerprint),
+//   program length u32, map fingerprint u64 (0 without one), metadata entries u16, every entry a key and a value
+//   each as u16 length and UTF-8, then the program.
+// Files are always written in the newest version, every older one stays readable.
+pub const MAGIC: [u8; 4] = *b"TSGN";
+pub const VERSION: u16 = 1;
+
+const FLAG_MAP_FINGERPRINT: u8 = 1;
+
+// Everything in a genome file but the program
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GenomeHeader {
+    // Version the file was written in
+    pub version: u16,
+    pub instruction_set: InstructionSet,
+    // See map::fingerprint, of the map the genome was found on
+    pub map_fingerprint: Option<u64>,
+    // Free key and value pairs in the order they were added, e.g. the fitness or the seed of the run
+    pub metadata: Vec<(String, String)>,
+}
+
+impl GenomeHeader {
+    pub fn new(instruction_set: InstructionSet) -> GenomeHeader {
+        return GenomeHeader { version: VERSION, instruction_set, map_fingerprint: None, metadata: Vec::new() };
+    }
+
+    pub fn map_fingerprint(mut self, map_fingerprint: u64) -> GenomeHeader {
+        self.map_fingerprint = Some(map_fingerprint);
+        return self;
+    }
+
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> GenomeHeader {
+        self.metadata.push((key.into(), value.into()));
+        return self;
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        return self.metadata.iter().find(|(k, _)| k == key).map(|(_, value)| value.as_str());
+    }
+}
+
+// Tells genome files from the text forms of a genome
+pub fn is_genome_file(bytes: &[u8]) -> bool {
+    return bytes.starts_with(&MAGIC);
+}
+
+pub fn encode(header: &GenomeHeader, program: &[INSTR]) -> Result<Vec<u8>, Error> {
+    let length = u32::try_from(program.len())
+        .map_err(|_| Error::TooLarge(format!("a program of {} instructions does not fit a genome file", program.len())))?;
+    let mut bytes: Vec<u8> = Vec::with_capacity(24 + program.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.push(match header.instruction_set {
+        InstructionSet::Classic => 0,
+        InstructionSet::Extended => 1,
+    });
+    bytes.push(if header.map_fingerprint.is_some() { FLAG_MAP_FINGERPRINT } else { 0 });
+    bytes.extend_from_slice(&length.to_le_bytes());
+    bytes.extend_from_slice(&header.map_fingerprint.unwrap_or(0).to_le_bytes());
+    let metadata: Vec<&(String, String)> = header.metadata.iter().take(u16::MAX as usize).collect();
+    bytes.extend_from_slice(&(metadata.len() as u16).to_le_bytes());
+    for (key, value) in metadata {
+        for text in [key, value] {
+            let text = truncate(text, u16::MAX as usize);
+            bytes.extend_from_slice(&(text.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(text.as_bytes());
+        }
+    }
+    bytes.extend_from_slice(program);
+    return Ok(bytes);
+}
+
+pub fn decode(bytes: &[u8]) -> Result<(GenomeHeader, Program), Error> {
+    if !is_genome_file(bytes) {
+        return Err(Error::Parse(String::from("not a genome file")));
+    }
+    let mut reader = Reader { bytes, position: MAGIC.len() };
+    let version = u16::from_le_bytes(reader.array()?);
+    return match version {
+        1 => decode_v1(&mut reader),
+        0 => Err(Error::Parse(String::from("genome file version 0 does not exist"))),
+        _ => Err(Error::Parse(format!("genome file version {} is newer than this program, which reads up to {}", version, VERSION))),
+    };
+}
+
+fn decode_v1(reader: &mut Reader) -> Result<(GenomeHeader, Program), Error> {
+    let [instruction_set] = reader.array()?;
+    let instruction_set = match instruction_set {
+        0 => InstructionSet::Classic,
+        1 => InstructionSet::Extended,
+        id => return Err(Error::Parse(format!("unknown instruction set {}", id))),
+    };
+    let [flags] = reader.array()?;
+    let length = u32::from_le_bytes(reader.array()?) as usize;
+    let map_fingerprint = u64::from_le_bytes(reader.array()?);
+    let entries = u16::from_le_bytes(reader.array()?);
+    let mut metadata: Vec<(String, String)> = Vec::with_capacity(entries as usize);
+    for _ in 0..entries {
+        let key = reader.text()?;
+        let value = reader.text()?;
+        metadata.push((key, value));
+    }
+    let program = reader.take(length)?;
+    if reader.position != reader.bytes.len() {
+        return Err(Error::Parse(format!("{} bytes after the program", reader.bytes.len() - reader.position)));
+    }
+    let header = GenomeHeader {
+        version: 1,
+        instruction_set,
+        map_fingerprint: Some(map_fingerprint).filter(|_| flags & FLAG_MAP_FINGERPRINT != 0),
+        metadata,
+    };
+    return Ok((header, Program::new(program.to_vec())));
+}
+
+// Longest prefix of whole characters
+fn truncate(text: &str, length: usize) -> &str {
+    let mut end = text.len().min(length);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    return &text[..end];
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let taken = self.bytes.get(self.position..self.position.saturating_add(length))
+            .ok_or_else(|| Error::Parse(String::from("the genome file is truncated")))?;
+        self.position += length;
+        return Ok(taken);
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        return Ok(self.take(N)?.try_into().unwrap());
+    }
+
+    fn text(&mut self) -> Result<String, Error> {
+        let length = u16::from_le_bytes(self.array()?) as usize;
+        return String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| Error::Parse(String::from("metadata of the genome file is not UTF-8")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> GenomeHeader {
+        return GenomeHeader::new(InstructionSet::Extended)
+            .map_fingerprint(0x0123_4567_89ab_cdef)
+            .metadata("seed", "42")
+            .metadata("steps", "PPDD");
+    }
+
+    #[test]
+    fn round_trip() {
+        let program: Vec<INSTR> = (0..64).collect();
+        let (decoded_header, decoded) = decode(&encode(&header(), &program).unwrap()).unwrap();
+        assert_eq!(decoded_header, header());
+        assert_eq!(decoded_header.get("steps"), Some("PPDD"));
+        assert_eq!(&decoded[..], &program[..]);
+
+        let (decoded_header, decoded) = decode(&encode(&GenomeHeader::new(InstructionSet::Classic), &[]).unwrap()).unwrap();
+        assert_eq!(decoded_header, GenomeHeader::new(InstructionSet::Classic));
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_files_and_trailing_bytes() {
+        let bytes = encode(&header(), &[1, 2, 3]).unwrap();
+        for length in MAGIC.len()..bytes.len() {
+            assert!(decode(&bytes[..length]).is_err(), "{} bytes", length);
+        }
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(decode(&longer).unwrap_err().to_string(), "1 bytes after the program");
+        assert!(decode(b"TSG").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_instruction_sets() {
+        let bytes = encode(&header(), &[1, 2, 3]).unwrap();
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(decode(&newer).unwrap_err().to_string().contains("newer than this program"));
+        let mut zero = bytes.clone();
+        zero[4..6].copy_from_slice(&0u16.to_le_bytes());
+        assert!(decode(&zero).is_err());
+
+        let mut unknown = bytes;
+        unknown[6] = 2;
+        assert_eq!(decode(&unknown).unwrap_err().to_string(), "unknown instruction set 2");
+        assert_eq!(decode(&[0; 32]).unwrap_err().to_string(), "not a genome file");
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::Write;
+
+use crate::core::{Direction, StepAlphabet, AREA_TILE_NOTHING, AREA_TILE_TREASURE};
+use crate::map::GameArea;
+use crate::render;
+use crate::vm::{CollisionPolicy, Termination, VmConfig, VmResult};
+
+// Walks the map with moves chosen by a person, the result is scored like the run of a program.
+// Every move counts as one iteration, collisions and the limits follow the machine settings.
+pub fn play(game_area: &GameArea, vm_config: &VmConfig, step_alphabet: StepAlphabet, color: bool) -> Result<VmResult, String> {
+    let mut player = game_area.player();
+    let treasures = game_area.treasure_count();
+
+    let mut remaining = game_area.clone();
+    let mut input = Input::open()?;
+    let mut steps: String = String::new();
+    let mut collected_treasures: Vec<(usize, usize)> = Vec::new();
+    let mut trajectory: Vec<(usize, usize)> = vec![player.tile()];
+    let termination = loop {
+        if collected_treasures.len() as u32 >= treasures {
+            break Termination::AllTreasures;
+        }
+        if steps.len() as u32 >= vm_config.iteration_limit {
+            break Termination::IterationLimit;
+        }
+        if steps.len() as u32 >= vm_config.max_moves {
+            break Termination::MoveLimit;
+        }
+        let frame = format!("{}Treasures: {}/{}, Steps: {} ({})\n", render::render_frame(game_area, &trajectory, color),
+                            collected_treasures.len(), treasures, step_alphabet.format(&steps), steps.len());
+        let Some(direction) = input.next_move(&frame, step_alphabet)? else {
+            break Termination::EndOfProgram;
+        };
+        let mut new_position = player.step(direction);
+        steps.push(direction.glyph());
+        if !game_area.contains(new_position) {
+            match vm_config.collision_policy {
+                CollisionPolicy::Terminate => break Termination::LeftMap,
+                CollisionPolicy::Block => new_position = player,
+                CollisionPolicy::Wrap => new_position = new_position.wrapped(game_area.width(), game_area.height()),
+            }
+        }
+        player = new_position;
+        let (x, y) = player.tile();
+        trajectory.push((x, y));
+        if remaining.get(x, y) == AREA_TILE_TREASURE {
+            remaining.set(x, y, AREA_TILE_NOTHING);
+            collected_treasures.push((x, y));
+        }
+    };
+    input.finish();
+
+    return Ok(VmResult {
+        iterations: steps.len() as u32,
+        found_treasures: collected_treasures.len() as u32,
+        steps,
+        termination,
+        player,
+        collected_treasures,
+        executed_instructions: 0,
+        trajectory,
+    });
+}
+
+// Direction of a typed glyph of the step alphabet, letters in either case
+fn typed_direction(glyph: char, step_alphabet: StepAlphabet) -> Option<Direction> {
+    return step_alphabet.0.iter().position(|known| known.to_lowercase().eq(glyph.to_lowercase())).map(Direction::from_index);
+}
+
+// Arrow keys in raw terminal mode, the map is redrawn in place
+#[cfg(feature = "tui")]
+struct Input {
+    drawn_lines: usize,
+}
+
+#[cfg(feature = "tui")]
+impl Input {
+    fn open() -> Result<Input, String> {
+        crossterm::terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+        return Ok(Input { drawn_lines: 0 });
+    }
+
+    fn next_move(&mut self, frame: &str, step_alphabet: StepAlphabet) -> Result<Option<Direction>, String> {
+        use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+        let frame = format!("{}Arrow keys or {} to move, q to stop\n", frame, step_alphabet.0.iter().collect::<String>());
+        if self.drawn_lines > 0 {
+            print!("\x1b[{}F", self.drawn_lines);
+        }
+        // Raw mode does not return the cursor to the start of the line
+        print!("{}", frame.replace('\n', "\x1b[K\r\n"));
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+        self.drawn_lines = frame.lines().count();
+        loop {
+            let key = match event::read().map_err(|e| e.to_string())? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Up => return Ok(Some(Direction::Up)),
+                KeyCode::Right => return Ok(Some(Direction::Right)),
+                KeyCode::Down => return Ok(Some(Direction::Down)),
+                KeyCode::Left => return Ok(Some(Direction::Left)),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                KeyCode::Char(glyph) => {
+                    if let Some(direction) = typed_direction(glyph, step_alphabet) {
+                        return Ok(Some(direction));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        crossterm::terminal::disable_raw_mode().ok();
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Drop for Input {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+// Without the "tui" feature moves are typed as glyphs of the step alphabet, one or more per line
+#[cfg(not(feature = "tui"))]
+struct Input {
+    pending: std::collections::VecDeque<Direction>,
+}
+
+#[cfg(not(feature = "tui"))]
+impl Input {
+    fn open() -> Result<Input, String> {
+        return Ok(Input { pending: std::collections::VecDeque::new() });
+    }
+
+    fn next_move(&mut self, frame: &str, step_alphabet: StepAlphabet) -> Result<Option<Direction>, String> {
+        while self.pending.is_empty() {
+            print!("{}Moves ({}), empty line to stop: ", frame, step_alphabet.0.iter().collect::<String>());
+            std::io::stdout().flush().map_err(|e| e.to_string())?;
+            let mut line = String::new();
+            if std::io::stdin().read_line(&mut line).map_err(|e| e.to_string())? == 0 || line.trim().is_empty() {
+                return Ok(None);
+            }
+            for glyph in line.chars().filter(|glyph| !glyph.is_whitespace()) {
+                match typed_direction(glyph, step_alphabet) {
+                    Some(direction) => self.pending.push_back(direction),
+                    None => {
+                        println!("Unknown move '{}'", glyph);
+                        self.pending.clear();
+                        break;
+                    }
+                }
+            }
+        }
+        return Ok(self.pending.pop_front());
+    }
+
+    fn finish(&mut self) {}
+}
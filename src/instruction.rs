@@ -0,0 +1,83 @@
+use crate::core::{Direction, INSTR};
+use crate::vm::InstructionSet;
+
+// The opcode is in the upper two bits of a byte, the operand in the lower six
+const OPCODE_MASK: INSTR = 0xC0;
+pub const OPERAND_MASK: INSTR = 0x3F;
+const OPCODE_INC: INSTR = 0x00;
+const OPCODE_DEC: INSTR = 0x40;
+const OPCODE_JUMP: INSTR = 0x80;
+const OPCODE_MOVE: INSTR = 0xC0;
+
+// Extended instruction set: sub-operations encoded in the upper operand bits of the move opcode
+pub const EXT_OP_MOVE: usize = 0;
+pub const EXT_OP_RAND: usize = 1;
+pub const EXT_OP_ADD: usize = 2;
+pub const EXT_OP_SUB: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Instruction {
+    // Operand is the address of the changed cell
+    Inc(u8),
+    Dec(u8),
+    Jump(u8),
+    Move(Direction),
+    // Extended set only, the target address is the operand of the following byte
+    Rand,
+    // Immediate 1..=16
+    Add(u8),
+    Sub(u8),
+}
+
+impl Instruction {
+    pub fn from_byte(byte: INSTR, instruction_set: InstructionSet) -> Instruction {
+        let data: INSTR = byte & OPERAND_MASK;
+        return match byte & OPCODE_MASK {
+            OPCODE_INC => Instruction::Inc(data),
+            OPCODE_DEC => Instruction::Dec(data),
+            OPCODE_JUMP => Instruction::Jump(data),
+            _ if instruction_set == InstructionSet::Extended => match usize::from(data >> 4) {
+                EXT_OP_RAND => Instruction::Rand,
+                EXT_OP_ADD => Instruction::Add((byte & 0x0F) + 1),
+                EXT_OP_SUB => Instruction::Sub((byte & 0x0F) + 1),
+                _ => Instruction::Move(Direction::from_index(usize::from(data))),
+            },
+            _ => Instruction::Move(Direction::from_index(usize::from(data))),
+        };
+    }
+
+    // Bits which the instruction does not use are zero
+    pub fn to_byte(&self) -> INSTR {
+        return match *self {
+            Instruction::Inc(address) => OPCODE_INC | address & OPERAND_MASK,
+            Instruction::Dec(address) => OPCODE_DEC | address & OPERAND_MASK,
+            Instruction::Jump(target) => OPCODE_JUMP | target & OPERAND_MASK,
+            Instruction::Move(direction) => OPCODE_MOVE | direction.index() as INSTR,
+            Instruction::Rand => OPCODE_MOVE | (EXT_OP_RAND as INSTR) << 4,
+            Instruction::Add(immediate) => OPCODE_MOVE | (EXT_OP_ADD as INSTR) << 4 | (immediate.clamp(1, 16) - 1),
+            Instruction::Sub(immediate) => OPCODE_MOVE | (EXT_OP_SUB as INSTR) << 4 | (immediate.clamp(1, 16) - 1),
+        };
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Instruction::Inc(_) => "INC",
+            Instruction::Dec(_) => "DEC",
+            Instruction::Jump(_) => "JMP",
+            Instruction::Move(_) => "MOVE",
+            Instruction::Rand => "RAND",
+            Instruction::Add(_) => "ADD",
+            Instruction::Sub(_) => "SUB",
+        };
+    }
+
+    // Whether the following byte is the operand instead of the next instruction
+    pub fn has_operand_byte(&self) -> bool {
+        return matches!(self, Instruction::Rand | Instruction::Add(_) | Instruction::Sub(_));
+    }
+}
+
+// Address held by the operand byte of an extended instruction
+pub fn operand_address(byte: INSTR) -> usize {
+    return usize::from(byte & OPERAND_MASK);
+}
@@ -0,0 +1,122 @@
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+use crate::core::{self, INSTR};
+
+pub struct LandscapeReport {
+    pub samples: usize,
+    pub neighbours_per_sample: usize,
+    pub mean_fitness: f64,
+    pub best_fitness: f64,
+    // Share of single-bit neighbours with the same fitness
+    pub neutrality_ratio: f64,
+    pub mean_fitness_change: f64,
+    // Correlation of fitness with the Hamming distance to the best genome found
+    pub fitness_distance_correlation: f64,
+    // Correlation of consecutive fitness values on a random bit-flip walk
+    pub walk_autocorrelation: f64,
+    pub walk_length: usize,
+}
+
+pub fn analyze<F: Fn(&[INSTR]) -> f64>(evaluate: F, program_size: usize, samples: usize, walk_length: usize,
+                                        rng: &mut Pcg64) -> LandscapeReport {
+    let bits = program_size * 8;
+    let mut genomes: Vec<(Vec<INSTR>, f64)> = Vec::with_capacity(samples * (bits + 1));
+    let mut neutral: u64 = 0;
+    let mut total_change: f64 = 0.0;
+    for _ in 0..samples {
+        let genes = core::random_instructions(rng, program_size);
+        let fitness = evaluate(&genes);
+        for bit in 0..bits {
+            let mut neighbour = genes.clone();
+            neighbour[bit / 8] ^= 1 << (bit % 8);
+            let neighbour_fitness = evaluate(&neighbour);
+            if (neighbour_fitness - fitness).abs() < 1e-12 {
+                neutral += 1;
+            }
+            total_change += (neighbour_fitness - fitness).abs();
+            genomes.push((neighbour, neighbour_fitness));
+        }
+        genomes.push((genes, fitness));
+    }
+
+    let evaluations = genomes.len().max(1) as f64;
+    let neighbours = (samples * bits).max(1) as f64;
+    let best_index = (0..genomes.len()).max_by(|&a, &b| genomes[a].1.total_cmp(&genomes[b].1));
+    let (fitness_distance_correlation, best_fitness) = match best_index {
+        Some(best_index) => {
+            let best = &genomes[best_index].0;
+            let fitnesses: Vec<f64> = genomes.iter().map(|(_, fitness)| *fitness).collect();
+            let distances: Vec<f64> = genomes.iter().map(|(genes, _)| hamming_distance(genes, best) as f64).collect();
+            (correlation(&fitnesses, &distances), genomes[best_index].1)
+        }
+        None => (0.0, 0.0),
+    };
+
+    let mut walk: Vec<f64> = Vec::with_capacity(walk_length + 1);
+    let mut genes = core::random_instructions(rng, program_size);
+    walk.push(evaluate(&genes));
+    for _ in 0..walk_length {
+        let bit = rng.gen_range(0..bits);
+        genes[bit / 8] ^= 1 << (bit % 8);
+        walk.push(evaluate(&genes));
+    }
+    let walk_autocorrelation = if walk.len() > 1 {
+        correlation(&walk[..walk.len() - 1], &walk[1..])
+    } else {
+        0.0
+    };
+
+    return LandscapeReport {
+        samples,
+        neighbours_per_sample: bits,
+        mean_fitness: genomes.iter().map(|(_, fitness)| fitness).sum::<f64>() / evaluations,
+        best_fitness,
+        neutrality_ratio: neutral as f64 / neighbours,
+        mean_fitness_change: total_change / neighbours,
+        fitness_distance_correlation,
+        walk_autocorrelation,
+        walk_length,
+    };
+}
+
+pub fn print_report(report: &LandscapeReport) {
+    println!("Samples: {}, Neighbours per sample: {}", report.samples, report.neighbours_per_sample);
+    println!("Mean fitness: {:.4}, Best fitness: {:.4}", report.mean_fitness, report.best_fitness);
+    println!("Neutrality ratio: {:.4}", report.neutrality_ratio);
+    println!("Mean fitness change per bit flip: {:.4}", report.mean_fitness_change);
+    println!("Fitness-distance correlation: {:.4}", report.fitness_distance_correlation);
+    let correlation_length = if report.walk_autocorrelation.abs() > 0.0 && report.walk_autocorrelation.abs() < 1.0 {
+        -1.0 / report.walk_autocorrelation.abs().ln()
+    } else {
+        f64::INFINITY
+    };
+    println!("Random walk ({} steps) autocorrelation: {:.4}, Correlation length: {:.2}",
+             report.walk_length, report.walk_autocorrelation, correlation_length);
+}
+
+pub fn hamming_distance(a: &[INSTR], b: &[INSTR]) -> u32 {
+    return a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum();
+}
+
+// Pearson correlation coefficient, zero when either side is constant
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len()) as f64;
+    if n < 2.0 {
+        return 0.0;
+    }
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let mut covariance: f64 = 0.0;
+    let mut variance_a: f64 = 0.0;
+    let mut variance_b: f64 = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        return 0.0;
+    }
+    return covariance / (variance_a.sqrt() * variance_b.sqrt());
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+// Best known solution of every map, keyed by the map hash (see map::fingerprint)
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Leaderboard {
+    pub maps: BTreeMap<String, Record>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Record {
+    pub genome: Vec<u8>,
+    pub instruction_set: String,
+    // In the glyphs of the run which set the record
+    pub steps: String,
+    pub step_count: usize,
+    pub found_treasures: u32,
+    pub treasures: u32,
+    pub fitness: f64,
+    pub fitness_function: String,
+    pub seed: u64,
+    // UTC, e.g. 2024-05-01T12:30:00Z
+    pub date: String,
+    pub program: String,
+}
+
+impl Record {
+    // Fitness values only compare under the same fitness function, other records by treasures, then steps
+    pub fn beats(&self, record: &Record) -> bool {
+        if self.fitness_function == record.fitness_function {
+            return self.fitness > record.fitness;
+        }
+        return (self.found_treasures, std::cmp::Reverse(self.step_count)) > (record.found_treasures, std::cmp::Reverse(record.step_count));
+    }
+}
+
+// A missing file is an empty leaderboard
+pub fn load(path: &Path) -> Result<Leaderboard, String> {
+    return match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+        Err(e) => Err(e.to_string()),
+    };
+}
+
+// Read again right before writing so runs finishing at the same time only lose to better records.
+// Returns the record the new one replaced, the record which stays when it is not beaten.
+pub fn submit(path: &Path, map_hash: &str, record: Record) -> Result<Submission, String> {
+    let mut leaderboard = load(path)?;
+    let previous = leaderboard.maps.get(map_hash).cloned();
+    if let Some(previous) = previous.as_ref().filter(|previous| !record.beats(previous)) {
+        return Ok(Submission::Kept(previous.clone()));
+    }
+    leaderboard.maps.insert(String::from(map_hash), record);
+    let json = serde_json::to_string(&leaderboard).map_err(|e| e.to_string())?;
+    let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&temporary, json + "\n").map_err(|e| e.to_string())?;
+    std::fs::rename(&temporary, path).map_err(|e| e.to_string())?;
+    return Ok(Submission::Set(previous));
+}
+
+pub enum Submission {
+    // With the record it replaced
+    Set(Option<Record>),
+    Kept(Record),
+}
+
+pub fn now() -> String {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    // Days since 1970-01-01 to the civil date, proleptic Gregorian
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let time = seconds % 86400;
+    return format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time / 60 % 60, time % 60);
+}
@@ -0,0 +1,37 @@
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+#![cfg_attr(not(any(feature = "std", feature = "wasm")), no_std)]
+
+extern crate alloc;
+
+pub mod core;
+pub mod crossover;
+pub mod distance;
+pub mod engine;
+pub mod ensemble;
+pub mod environment;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fitness;
+pub mod genome_file;
+pub mod instruction;
+pub mod lockstep;
+pub mod map;
+pub mod mutation;
+pub mod noise;
+pub mod population;
+pub mod program;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod reporter;
+pub mod scaling;
+pub mod selection;
+pub mod solver;
+pub mod spec;
+pub mod stats;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+pub mod verifier;
+pub mod vm;
+#[cfg(feature = "wasm")]
+pub mod wasm;
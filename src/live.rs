@@ -0,0 +1,312 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::core::Chromosome;
+use crate::engine::Event;
+use crate::error::Error;
+use crate::map::GameArea;
+use crate::reporter::{self, Reporter};
+use crate::vm::{Vm, VmConfig};
+
+// Pushes the events of a search to WebSocket clients, e.g. a browser page animating it. Every message is a JSON
+// object in one text frame, told apart by "event":
+//   map        first message of every connection: "width", "height", "rows" of map glyphs and "treasures"
+//   generation statistics of every evaluated generation as in reporter::event_json
+//   new_best   "generation" and "best", plus "path": the [x, y] positions the best chromosome walks through
+//   solution   same as new_best for a best chromosome collecting every treasure
+//   restarted  "generation" of a restart with a random population
+//   finished   last message: "generation", "solved", "best" and "path"
+// A client connecting late gets the map and the last best path before the following events.
+#[derive(Clone)]
+pub struct LiveServer {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    // One sender per connected client, dropped once its connection failed, None once the search finished
+    clients: Mutex<Option<Vec<Sender<Arc<str>>>>>,
+    // Threads of the connections, finish() waits for them to send what is queued
+    connections: Mutex<Vec<JoinHandle<()>>>,
+    map: Arc<str>,
+    last_best: Mutex<Option<Arc<str>>>,
+    // Records the paths
+    vm: Vm,
+    game_area: GameArea,
+}
+
+// Accept key of the handshake, RFC 6455
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+impl LiveServer {
+    pub fn listen(address: &str, game_area: &GameArea, vm_config: &VmConfig) -> std::io::Result<LiveServer> {
+        let listener = TcpListener::bind(address)?;
+        let rows: Vec<String> = game_area.to_string().lines().map(String::from).collect();
+        let map = serde_json::json!({
+            "event": "map",
+            "width": game_area.width(),
+            "height": game_area.height(),
+            "rows": rows,
+            "treasures": game_area.treasure_count(),
+        });
+        let shared = Arc::new(Shared {
+            clients: Mutex::new(Some(Vec::new())),
+            connections: Mutex::new(Vec::new()),
+            map: Arc::from(map.to_string()),
+            last_best: Mutex::new(None),
+            vm: Vm::new(VmConfig { record_trajectory: true, ..vm_config.clone() }),
+            game_area: game_area.clone(),
+        });
+        let accepting = shared.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = accepting.clone();
+                let handle = std::thread::spawn(move || {
+                    let Ok(stream) = handshake(stream) else {
+                        return;
+                    };
+                    let (sender, receiver) = mpsc::channel();
+                    // Queued while no event can be broadcast so nothing is missed or sent out of order
+                    sender.send(shared.map.clone()).ok();
+                    let mut clients = shared.clients.lock().unwrap();
+                    let Some(connected) = clients.as_mut() else {
+                        return;
+                    };
+                    if let Some(best) = shared.last_best.lock().unwrap().clone() {
+                        sender.send(best).ok();
+                    }
+                    connected.push(sender);
+                    drop(clients);
+                    serve(stream, receiver);
+                });
+                let mut connections = accepting.connections.lock().unwrap();
+                connections.retain(|connection| !connection.is_finished());
+                connections.push(handle);
+            }
+        });
+        return Ok(LiveServer { shared });
+    }
+
+    // The last message, sent before the connections are closed
+    pub fn finish(&self, generation: u32, best: Option<&Chromosome>, solved: bool) {
+        let message = serde_json::json!({
+            "event": "finished",
+            "generation": generation,
+            "solved": solved,
+            "best": best.map(reporter::chromosome_json),
+            "path": best.map(|best| self.path(best)),
+        });
+        self.broadcast(Arc::from(message.to_string()));
+        self.shared.clients.lock().unwrap().take();
+        for connection in self.shared.connections.lock().unwrap().drain(..) {
+            connection.join().ok();
+        }
+    }
+
+    fn path(&self, chromosome: &Chromosome) -> Vec<[usize; 2]> {
+        let trajectory = self.shared.vm.run(&chromosome.genes, &self.shared.game_area).trajectory;
+        return trajectory.into_iter().map(|(x, y)| [x, y]).collect();
+    }
+
+    fn broadcast(&self, message: Arc<str>) {
+        if let Some(clients) = self.shared.clients.lock().unwrap().as_mut() {
+            clients.retain(|client| client.send(message.clone()).is_ok());
+        }
+    }
+}
+
+impl Reporter for LiveServer {
+    fn report(&mut self, event: &Event) -> Result<(), Error> {
+        if self.shared.clients.lock().unwrap().as_ref().is_none_or(Vec::is_empty) && !matches!(event, Event::NewBest { .. } | Event::SolutionFound { .. }) {
+            return Ok(());
+        }
+        let mut message = reporter::event_json(event);
+        let best = match event {
+            Event::NewBest { best, .. } => Some(best),
+            Event::SolutionFound { solution, .. } => Some(solution),
+            _ => None,
+        };
+        if let Some(best) = best {
+            message["path"] = serde_json::json!(self.path(best));
+        }
+        let message: Arc<str> = Arc::from(message.to_string());
+        if best.is_some() {
+            *self.shared.last_best.lock().unwrap() = Some(message.clone());
+        }
+        self.broadcast(message);
+        return Ok(());
+    }
+}
+
+// Answers the upgrade request of a client
+fn handshake(stream: TcpStream) -> std::io::Result<TcpStream> {
+    // A client which stops reading or answering cannot hold up finish()
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut key: Option<String> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(String::from(value.trim()));
+            }
+        }
+    }
+    let mut stream = stream;
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 426 Upgrade Required\r\nUpgrade: websocket\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "not a WebSocket request"));
+    };
+    write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+           accept_key(&key))?;
+    return Ok(stream);
+}
+
+// Writes the messages of the search as text frames until the client leaves, frames of the client are only read
+// to notice a close
+fn serve(stream: TcpStream, receiver: Receiver<Arc<str>>) {
+    stream.set_nodelay(true).ok();
+    if let Ok(reading) = stream.try_clone() {
+        std::thread::spawn(move || {
+            let mut reading = reading;
+            while let Ok(opcode) = skip_frame(&mut reading) {
+                if opcode == 0x8 {
+                    break;
+                }
+            }
+            reading.shutdown(Shutdown::Both).ok();
+        });
+    }
+    stream.set_read_timeout(None).ok();
+    let mut writer = stream;
+    for message in receiver {
+        if write_frame(&mut writer, 0x1, message.as_bytes()).is_err() {
+            return;
+        }
+    }
+    // Normal closure once the search finished
+    write_frame(&mut writer, 0x8, &1000u16.to_be_bytes()).ok();
+}
+
+// Unmasked and unfragmented as sent by a server
+fn write_frame(writer: &mut TcpStream, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame: Vec<u8> = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    return writer.write_all(&frame);
+}
+
+// Reads past the next frame of a client, returns its opcode
+fn skip_frame(reader: &mut TcpStream) -> std::io::Result<u8> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header)?;
+    let length: u64 = match header[1] & 0x7f {
+        126 => {
+            let mut length = [0u8; 2];
+            reader.read_exact(&mut length)?;
+            u64::from(u16::from_be_bytes(length))
+        }
+        127 => {
+            let mut length = [0u8; 8];
+            reader.read_exact(&mut length)?;
+            u64::from_be_bytes(length)
+        }
+        length => u64::from(length),
+    };
+    let mask: u64 = if header[1] & 0x80 != 0 { 4 } else { 0 };
+    std::io::copy(&mut reader.take(mask + length), &mut std::io::sink())?;
+    return Ok(header[0] & 0x0f);
+}
+
+// Sec-WebSocket-Accept answering the Sec-WebSocket-Key of the request
+fn accept_key(key: &str) -> String {
+    return STANDARD.encode(sha1(format!("{}{}", key, GUID).as_bytes()));
+}
+
+// Only for the handshake, no crate of the build has it
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut padded: Vec<u8> = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+    for block in padded.chunks(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, &word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temporary = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temporary;
+        }
+        for (state, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    return digest;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        return bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    }
+
+    #[test]
+    fn sha1_test_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        // The padding takes a second block
+        assert_eq!(hex(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+    }
+
+    #[test]
+    fn rfc_6455_handshake() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+}
@@ -0,0 +1,110 @@
+// Several programs interpreted in lockstep: every round executes one instruction of each running program.
+// The memories of all programs are kept together, each program is stepped by vm::RunState as Vm::run steps it,
+// so the results are the same as of Vm::run one by one.
+use alloc::{vec, vec::Vec};
+
+use crate::core::INSTR;
+use crate::instruction::Instruction;
+use crate::map::GameArea;
+use crate::vm::{RunState, Scratch, Termination, Vm, VmResult};
+
+pub fn run_batch(vm: &Vm, genomes: &[&[INSTR]], game_area: &GameArea) -> Vec<VmResult> {
+    let config = vm.config();
+    let lanes = genomes.len();
+    let memory_size = config.memory_size;
+
+    // Memory of lane i at i * memory_size
+    let mut memory: Vec<u8> = vec![0; lanes * memory_size];
+    for (lane, genome) in genomes.iter().enumerate() {
+        let length = genome.len().min(memory_size);
+        memory[lane * memory_size..lane * memory_size + length].copy_from_slice(&genome[..length]);
+    }
+    let mut scratch = Scratch::new();
+    let mut states: Vec<RunState> = (0..lanes).map(|_| RunState::new(config, game_area, &mut scratch)).collect();
+    let mut terminations: Vec<Option<Termination>> = vec![None; lanes];
+
+    // Lanes still running, and the instruction byte of each of them in the current round
+    let mut running: Vec<usize> = (0..lanes).collect();
+    let mut opcodes: Vec<INSTR> = Vec::with_capacity(lanes);
+    let mut decoded: Vec<Instruction> = Vec::with_capacity(lanes);
+    while !running.is_empty() {
+        running.retain(|&lane| {
+            let cells = &mut memory[lane * memory_size..(lane + 1) * memory_size];
+            terminations[lane] = states[lane].stopped(config, cells);
+            if terminations[lane].is_some() {
+                return false;
+            }
+            states[lane].write_inputs(config, cells);
+            return true;
+        });
+
+        // Fetch and decode of the whole round over contiguous bytes, which the compiler can vectorize
+        opcodes.clear();
+        opcodes.extend(running.iter().map(|&lane| memory[lane * memory_size + states[lane].instruction_index]));
+        decoded.clear();
+        decoded.extend(opcodes.iter().map(|&byte| Instruction::from_byte(byte, config.instruction_set)));
+
+        for (&lane, &instruction) in running.iter().zip(&decoded) {
+            let cells = &mut memory[lane * memory_size..(lane + 1) * memory_size];
+            terminations[lane] = states[lane].execute(instruction, config, cells);
+        }
+        running.retain(|&lane| terminations[lane].is_none());
+    }
+
+    return states.into_iter().zip(terminations).map(|(state, termination)| state.finish(termination.unwrap(), &mut scratch)).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::core::Position;
+    use crate::map;
+    use crate::vm::{CollisionPolicy, InstructionSet, VmConfig};
+
+    type Outcome = (u32, u32, String, Termination, Position, Vec<(usize, usize)>, u32, Vec<(usize, usize)>);
+
+    fn outcome(result: VmResult) -> Outcome {
+        return (result.iterations, result.found_treasures, result.steps, result.termination, result.player,
+                result.collected_treasures, result.executed_instructions, result.trajectory);
+    }
+
+    #[test]
+    fn same_results_as_the_machine() {
+        let game_area = map::parse("..T...\n.P..T.\n......\nT....T").unwrap();
+        let mut rng = Pcg64::seed_from_u64(3);
+        let genomes: Vec<Vec<INSTR>> = (0..200).map(|_| {
+            let length = rng.gen_range(1..=64);
+            (0..length).map(|_| rng.gen()).collect()
+        }).collect();
+        let genomes: Vec<&[INSTR]> = genomes.iter().map(Vec::as_slice).collect();
+        for instruction_set in [InstructionSet::Classic, InstructionSet::Extended] {
+            for memory_mapped_inputs in [false, true] {
+                for collision_policy in [CollisionPolicy::Terminate, CollisionPolicy::Block, CollisionPolicy::Wrap] {
+                    for memory_size in [64, 20] {
+                        let vm = Vm::new(VmConfig::builder()
+                            .instruction_set(instruction_set)
+                            .memory_mapped_inputs(memory_mapped_inputs)
+                            .collision_policy(collision_policy)
+                            .memory_size(memory_size)
+                            .max_moves(40)
+                            .record_trajectory(true)
+                            .seed(9)
+                            .build());
+                        let batch = run_batch(&vm, &genomes, &game_area);
+                        assert_eq!(batch.len(), genomes.len());
+                        for (genome, result) in genomes.iter().zip(batch) {
+                            assert_eq!(outcome(result), outcome(vm.run(genome, &game_area)),
+                                       "{:?}, inputs {}, {:?}, memory {}: {:02x?}", instruction_set, memory_mapped_inputs,
+                                       collision_policy, memory_size, genome);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
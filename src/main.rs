@@ -1,172 +1,1681 @@
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
+use std::cmp::Ordering;
 use std::io::Write;
-use std::process::exit;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::mpsc::Receiver;
 
-use rand::SeedableRng;
+use clap::Parser;
+use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64;
 
-use crate::core::Chromosome;
+use crate::cli::{AnalyzeArgs, BatchArgs, BenchArgs, Cli, Command, ImportTrailArgs, MapElitesArgs, MapgenArgs, PlayArgs, ProfileArgs, ReplayArgs, ReproduceArgs,
+                 RunArgs, ReportInterval, StopPolicy, TeachArgs, TournamentArgs, Verbosity, WorkerArgs};
+use crate::control::ControlCommand;
+use crate::core::{Chromosome, Genes, Selection, StepAlphabet};
+use crate::crossover::UniformCrossover;
+use crate::engine::{Engine, Event, GaConfig, StopReason};
+use crate::distance::TreasureDistances;
+use crate::encoding::GenomeFormat;
+use crate::ensemble::Case;
+use crate::genome_file::GenomeHeader;
+use crate::environment::GridEnvironment;
+use crate::fitness::{DistanceShaping, FitnessContext, FitnessFunction, IterationBonus, ParsimonyPressure, ProximityCredit,
+                     StepPenaltyFitness};
+use crate::map::GameArea;
+use crate::mutation::BitFlipMutation;
+use crate::noise::Resampling;
+use crate::population::Population;
+use crate::program::Program;
+use crate::reporter::{ConsoleReporter, CsvReporter, JsonLinesReporter, Reporter};
+use crate::scaling::FitnessScaling;
+use crate::vm::{InstructionSet, Vm, VmConfig, VmConfigBuilder};
 
-mod core;
+use treasure_search::{core, crossover, distance, engine, ensemble, environment, error, fitness, genome_file, instruction, map, mutation, noise, population, program, reporter, scaling, solver, stats, verifier, vm};
 
-fn main() {
-    //let mut rng = Pcg64::seed_from_u64(948464);   // Testing seed
-    let mut rng = Pcg64::from_entropy();
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 4 {
-        eprintln!("Too few arguments!");
-        eprintln!("Arguments: <Number of subjects> <Target generation number> <Mutation probability> <Selection method>");
-        eprintln!("Selection methods: 0 - Roulette, 1 - Tournament");
-        return;
+mod animation;
+mod batch;
+mod checkpoint;
+mod cli;
+mod control;
+mod dump;
+mod elites;
+mod encoding;
+mod human;
+mod landscape;
+mod leaderboard;
+mod live;
+mod manifest;
+mod plot;
+mod profiler;
+mod progress;
+mod remote;
+mod render;
+mod report;
+mod status;
+mod svg;
+mod teach;
+mod tensorboard;
+mod tournament;
+mod tui;
+
+fn main() -> ExitCode {
+    let cli = match cli::parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    init_logging(&cli.log_format);
+    // Commands without a map of their own, each validates its inputs for --dry-run
+    match &cli.command {
+        Command::Completions(args) => {
+            cli::print_completions(args.shell);
+            return ExitCode::SUCCESS;
+        }
+        Command::Mapgen(args) => return mapgen(&cli, args),
+        Command::ImportTrail(args) => return import_trail(&cli, args),
+        Command::Batch(args) => return batch(&cli, args),
+        Command::Tournament(args) => return tournament(&cli, args),
+        Command::Reproduce(args) => return reproduce(&cli, args),
+        Command::Worker(args) => return worker(&cli, args),
+        _ => {}
     }
+    let maps: Vec<(String, GameArea)> = match load_maps(&cli.map) {
+        Ok(maps) => maps,
+        Err(e) => {
+            eprintln!("Invalid map {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    // The first map is the one shown, replayed and solved for reference
+    let game_area: &GameArea = &maps[0].1;
+    let Some(fitness_function) = fitness_function(&cli, &maps) else {
+        return ExitCode::from(EXIT_INVALID_INPUT);
+    };
+    let starts: &[(usize, usize)] = cli.starts.as_ref().map_or(&[], |starts| &starts.0);
+    let cases: Vec<Case> = match ensemble::map_cases(&maps, starts) {
+        Ok(cases) => cases,
+        Err(e) => {
+            eprintln!("Invalid maps or start positions: {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    let vm_config = vm_config(&cli).record_trajectory(fitness_function.needs_trajectory());
+    if cli.dry_run {
+        return dry_run(&cli, vm_config.build(), fitness_function.as_ref(), &maps, &cases);
+    }
+    match &cli.command {
+        Command::Run(args) if args.watch && std::env::var_os(WATCHED_ENV).is_none() => {
+            if cli.map.is_empty() {
+                eprintln!("--watch requires --map");
+                return ExitCode::from(EXIT_USAGE);
+            }
+            return watch(&cli.map);
+        }
+        Command::Run(args) => return run(&cli, args, vm_config, fitness_function.as_ref(), &maps, &cases),
+        Command::Replay(args) => return replay(&cli, args, vm_config, fitness_function.as_ref(), &cases),
+        Command::Play(args) => return play(&cli, args, vm_config, fitness_function.as_ref(), game_area),
+        Command::Analyze(args) => analyze(&cli, args, vm_config, fitness_function.as_ref(), game_area),
+        Command::Bench(args) => bench(&cli, args, vm_config, fitness_function.as_ref(), game_area),
+        Command::Profile(args) => return profile(&cli, args, vm_config, game_area),
+        Command::MapElites(args) => map_elites(&cli, args, vm_config, fitness_function.as_ref(), game_area),
+        Command::Teach(args) => teach(&cli, args, vm_config, fitness_function.as_ref(), game_area),
+        Command::Mapgen(_) | Command::ImportTrail(_) | Command::Batch(_) | Command::Worker(_) | Command::Tournament(_) | Command::Reproduce(_) | Command::Completions(_) => {}
+    }
+    return ExitCode::SUCCESS;
+}
 
-    let subjects_num = args[1].parse::<usize>().unwrap_or_else(parse_error_handler);
-    if subjects_num < 20 {
-        eprintln!("Minimum number of subjects is 20!");
-        return;
+// The maps of --map named by their files, the built-in map without any
+fn load_maps(paths: &[std::path::PathBuf]) -> Result<Vec<(String, GameArea)>, String> {
+    if paths.is_empty() {
+        return Ok(vec![(String::from("built-in map"), core::build_game_area())]);
     }
+    return paths.iter()
+        .map(|path| match map::load(path) {
+            Ok(game_area) => Ok((path.display().to_string(), game_area)),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        })
+        .collect();
+}
 
-    let mut target_generations = args[2].parse::<u32>().unwrap_or_else(parse_error_handler);
-    if target_generations < 1 {
-        eprintln!("Minimum number of generations is 1!");
-        return;
+// The maps of a manifest or a coordinator, sent as text
+fn parse_maps<'t>(texts: impl IntoIterator<Item = &'t String>) -> Result<Vec<(String, GameArea)>, String> {
+    return texts.into_iter()
+        .enumerate()
+        .map(|(i, text)| match map::parse(text) {
+            Ok(game_area) => Ok((format!("map {}", i + 1), game_area)),
+            Err(e) => Err(format!("map {}: {}", i + 1, e)),
+        })
+        .collect();
+}
+
+// None after the reason was printed
+fn fitness_function(cli: &Cli, maps: &[(String, GameArea)]) -> Option<Box<dyn FitnessFunction>> {
+    let game_area = &maps[0].1;
+    let mut step_penalty = StepPenaltyFitness::for_map(game_area);
+    if let Some(coefficient) = cli.step_penalty {
+        step_penalty.coefficient = coefficient;
+    }
+    step_penalty.clamp = !cli.no_clamp;
+    // Names are validated by the argument parser
+    let mut fitness_function: Box<dyn FitnessFunction> = fitness::by_name(&cli.fitness, step_penalty).unwrap();
+    if let Some(script) = &cli.fitness_script {
+        fitness_function = fitness_script(script)?;
+    }
+    // Distances are those of one map
+    if maps.len() > 1 && (cli.distance_shaping.is_some() || cli.proximity_credit.is_some()) {
+        eprintln!("--distance-shaping and --proximity-credit need a single map");
+        return None;
     }
+    // Computed once for the map and shared by both shaping terms
+    let distances = (cli.distance_shaping.is_some() || cli.proximity_credit.is_some())
+        .then(|| Arc::new(TreasureDistances::new(game_area)));
+    if let (Some(weight), Some(distances)) = (cli.distance_shaping, &distances) {
+        fitness_function = Box::new(DistanceShaping::new(fitness_function, weight, distances.clone()));
+    }
+    if let (Some(weight), Some(distances)) = (cli.proximity_credit, &distances) {
+        fitness_function = Box::new(ProximityCredit::new(fitness_function, weight, distances.clone()));
+    }
+    if let Some(weight) = cli.iteration_bonus {
+        fitness_function = Box::new(IterationBonus::new(fitness_function, weight));
+    }
+    if let Some(weight) = cli.parsimony {
+        fitness_function = Box::new(ParsimonyPressure::new(fitness_function, weight));
+    }
+    return Some(fitness_function);
+}
 
-    let mutation_probability = args[3].parse::<f64>().unwrap_or_else(parse_error_handler);
-    let selection_method: u8 = args[4].parse().unwrap();    // 0 - roulette, 1 - tournament
-    if selection_method > 1 {
-        eprintln!("Invalid selection method!");
-        return;
+// Exit codes besides success, the argument parser exits with EXIT_USAGE as well
+const EXIT_UNSOLVED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_INVALID_INPUT: u8 = 3;
+const EXIT_FAILURE: u8 = 4;
+
+// Everything is parsed and validated, nothing is run
+fn dry_run(cli: &Cli, vm_config: VmConfig, fitness_function: &dyn FitnessFunction, maps: &[(String, GameArea)], cases: &[Case]) -> ExitCode {
+    let game_area = &maps[0].1;
+    let genome = match &cli.command {
+        Command::Replay(args) => Some(&args.genome),
+        Command::Play(args) => args.against.as_ref(),
+        Command::Profile(args) => Some(&args.genome),
+        _ => None,
+    };
+    if let Some(genome) = genome {
+        if load_genome(genome, &vm_config, game_area).is_none() {
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
     }
+    println!("{:#?}", cli);
+    for (name, game_area) in maps {
+        println!("Map {}: {}x{}, {} treasures, hash {:016x}", name, game_area.width(), game_area.height(),
+                 game_area.treasure_count(), map::fingerprint(game_area));
+    }
+    println!("Cases: {}", cases.iter().map(|case| case.name.as_str()).collect::<Vec<&str>>().join(", "));
+    println!("Fitness function: {}", fitness_function.name());
+    println!("{:#?}", vm_config);
+    println!("Configuration is valid");
+    return ExitCode::SUCCESS;
+}
 
-    let game_area: Vec<Vec<u8>> = core::build_game_area();
-    let mut player_x: isize = 0;
-    let mut player_y: isize = 0;
-    let mut treasures: u32 = 0;
-    for y in 0..game_area.len() {
-        for x in 0..game_area[0].len() {
-            if game_area[y][x] == core::AREA_TILE_PLAYER {
-                player_x = isize::try_from(x).unwrap();
-                player_y = isize::try_from(y).unwrap();
-                print!("P ");
-            } else if game_area[y][x] == core::AREA_TILE_TREASURE {
-                treasures += 1;
-                print!("█ ");
-            } else {
-                print!("░ ");
+// For the commands writing a map
+fn dry_run_map(game_area: &GameArea) -> ExitCode {
+    println!("Map: {}x{}, {} treasures, hash {:016x}", game_area.width(), game_area.height(),
+             game_area.treasure_count(), map::fingerprint(game_area));
+    println!("Configuration is valid");
+    return ExitCode::SUCCESS;
+}
+
+// For the commands running jobs, every job is checked before any is reported valid
+fn dry_run_jobs(jobs: &[batch::Job]) -> ExitCode {
+    let mut valid: bool = true;
+    for job in jobs {
+        match check_run_args(&job.args, None) {
+            Ok(()) => println!("{}: run {}", job.name, job.args.join(" ")),
+            Err(e) => {
+                eprintln!("Invalid job {}: {}", job.name, e);
+                valid = false;
             }
         }
-        println!();
     }
+    if !valid {
+        return ExitCode::from(EXIT_INVALID_INPUT);
+    }
+    println!("{} jobs, configuration is valid", jobs.len());
+    return ExitCode::SUCCESS;
+}
 
-    let mut current_generation: Vec<core::Chromosome> = Vec::with_capacity(subjects_num);
+// Options of a run in another process, e.g. a job of a batch: parsed, with its map, fitness function and start
+// positions built as the run would. The map texts are used instead of --map when given.
+fn check_run_args(args: &[String], map_texts: Option<&[String]>) -> Result<(), String> {
+    let command_line = [String::from(env!("CARGO_PKG_NAME")), String::from("run")];
+    let cli = Cli::try_parse_from(command_line.iter().chain(args))
+        .map_err(|e| e.to_string().lines().next().unwrap_or_default().trim_start_matches("error: ").to_string())?;
+    let maps = match map_texts {
+        Some(texts) => parse_maps(texts),
+        None => load_maps(&cli.map),
+    }.map_err(|e| format!("invalid map {}", e))?;
+    fitness_function(&cli, &maps).ok_or("invalid fitness function")?;
+    let starts: &[(usize, usize)] = cli.starts.as_ref().map_or(&[], |starts| &starts.0);
+    ensemble::map_cases(&maps, starts).map_err(|e| format!("invalid maps or start positions: {}", e))?;
+    return Ok(());
+}
 
-    for _ in 0..subjects_num {
-        current_generation.push(core::Chromosome::with_instructions(core::random_instructions(&mut rng)));
+// Filtered by RUST_LOG, nothing is logged unless it is set
+fn init_logging(format: &str) {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("off"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr)
+        .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stderr()));
+    if format == "json" {
+        subscriber.json().init();
+    } else {
+        subscriber.init();
     }
+}
 
-    let mut generations: u32 = 0;
-    let mut best_so_far: Option<Chromosome> = Option::None;
-    loop {
-        if generations >= target_generations {
-            let best_so_far = best_so_far.as_ref().unwrap();
-            println!("\nTarget generation reached!");
-            println!("\nBest solution so far: Generation: {}, Fitness: {}, Steps: {} ({}), Treasures: {}, Iterations: {}",
-                     generations, best_so_far.fitness, best_so_far.steps, best_so_far.steps.len(), best_so_far.found_treasures, best_so_far.iterations);
-            println!("{:?}", best_so_far.genes);
+// Set by Ctrl-C, the search stops before the next generation
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 
-            if !ask_user("Do you want to keep searching for a better solution? y/N: ") {
-                return;
+fn run(cli: &Cli, args: &RunArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction,
+       maps: &[(String, GameArea)], cases: &[Case]) -> ExitCode {
+    let game_area = &maps[0].1;
+    let step_alphabet = cli.glyphs;
+    let instruction_set = cli.instruction_set;
+    let verbosity = cli.verbosity();
+    let map_hash = format!("{:016x}", ensemble::maps_fingerprint(maps.iter().map(|(_, game_area)| game_area)));
+
+    let resumed = match args.resume.as_deref().map(checkpoint::load).transpose() {
+        Ok(resumed) => resumed,
+        Err(e) => {
+            eprintln!("Failed to load the checkpoint: {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    if resumed.as_ref().is_some_and(|resumed| resumed.map_hash != map_hash) {
+        eprintln!("The checkpoint was saved on a different map");
+        return ExitCode::from(EXIT_INVALID_INPUT);
+    }
+    let initial_population = match args.initial_population.as_deref().map(checkpoint::load_population).transpose() {
+        Ok(initial_population) => initial_population,
+        Err(e) => {
+            eprintln!("Failed to load the population: {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    if initial_population.as_ref().is_some_and(|loaded| loaded.instruction_set.is_some_and(|set| set != instruction_set)) {
+        eprintln!("The genome was saved for another instruction set");
+        return ExitCode::from(EXIT_INVALID_INPUT);
+    }
+    // Genomes run on any map, only worth a warning
+    if initial_population.as_ref().is_some_and(|loaded| loaded.map_hash.as_ref().is_some_and(|hash| *hash != map_hash)) {
+        eprintln!("The population was saved on a different map");
+    }
+
+    // 948464 - Testing seed
+    let seed: u64 = match &resumed {
+        Some(resumed) => resumed.seed,
+        None => cli.seed.unwrap_or_else(rand::random),
+    };
+    // Human text goes to the standard error when the JSON summary takes the standard output
+    let summary_on_stdout = args.output == "json" && args.output_file.is_none();
+    let mut out = human_output(summary_on_stdout);
+    if verbosity >= Verbosity::Normal {
+        writeln!(out, "Seed: {}, Fitness function: {}", seed, fitness_function.name()).ok();
+    }
+    tracing::info!(seed, fitness_function = fitness_function.name(), "search started");
+    if cfg!(not(feature = "parallel")) && args.threads != 1 {
+        eprintln!("Threads require the \"parallel\" feature!");
+        return ExitCode::from(EXIT_USAGE);
+    }
+    let start_time = std::time::Instant::now();
+    let ga_config = GaConfig::builder()
+        .subjects(args.subjects)
+        .mutation_probability(args.mutation)
+        .selection(args.selection.strategy())
+        .elitism(args.elitism)
+        .scaling(args.scaling)
+        .comparison(cli.compare)
+        .aggregation(args.aggregate.clone())
+        .resampling(Resampling::new(args.samples, args.max_samples))
+        .vm(vm_config.build())
+        .generations(args.generations)
+        .max_seconds(args.max_seconds)
+        .max_evaluations(args.max_evaluations)
+        .after_solution(match args.until.unwrap_or(StopPolicy::FirstSolution) {
+            StopPolicy::FirstSolution => Some(0),
+            StopPolicy::Extra(extra) => Some(extra),
+            StopPolicy::Budget => None,
+        })
+        .threads(args.threads)
+        // A generation is shared among the workers as one batch
+        .batch_size(if args.workers.is_empty() { args.batch_size as usize } else { args.subjects })
+        .early_exit(args.early_exit)
+        .build();
+    let resampling = ga_config.resampling;
+    let mut engine = match Engine::new(ga_config, fitness_function, cases, seed) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let comparison = engine.config().comparison;
+    if let Some(loaded) = initial_population {
+        let seeded = engine.seed_population(loaded.into_genomes());
+        if verbosity >= Verbosity::Normal {
+            writeln!(out, "Starting from {} saved genomes", seeded).ok();
+        }
+    }
+    if !args.workers.is_empty() {
+        let setup = remote::Setup {
+            args: cli::resolved_args().unwrap_or_else(|_| std::env::args().collect()),
+            map: game_area.to_string(),
+            extra_maps: maps[1..].iter().map(|(_, game_area)| game_area.to_string()).collect(),
+            vm_seed: engine.config().vm.seed,
+        };
+        let mut connections: Vec<remote::Connection> = Vec::with_capacity(args.workers.len());
+        for address in &args.workers {
+            match remote::Connection::open(address, &setup) {
+                Ok(connection) => connections.push(connection),
+                Err(e) => {
+                    eprintln!("Failed to connect to worker {}: {}", address, e);
+                    return ExitCode::from(EXIT_FAILURE);
+                }
             }
+        }
+        engine.wrap_environment(|local| Box::new(remote::RemoteEnvironment::new(local, connections)));
+    }
 
-            target_generations = u32::MAX;
+    let treasures: u32 = game_area.treasure_count();
+    let fitness_context = FitnessContext::new(treasures, &engine.config().vm);
+    let optimum = optimum(game_area, fitness_function, &fitness_context);
+    if verbosity >= Verbosity::Normal {
+        write!(out, "{}", render::render_map(game_area, &[], cli.color.enabled())).ok();
+        print_optimum(&mut out, optimum.as_ref(), step_alphabet);
+    }
+    if let Some(path) = &args.leaderboard {
+        let record = match leaderboard::load(path) {
+            Ok(leaderboard) => leaderboard.maps.get(&map_hash).cloned(),
+            Err(e) => {
+                eprintln!("Invalid leaderboard {}: {}", path.display(), e);
+                return ExitCode::from(EXIT_INVALID_INPUT);
+            }
+        };
+        if let Some(record) = record.filter(|_| verbosity >= Verbosity::Normal) {
+            writeln!(out, "Record for this map: {}", describe_record(&record)).ok();
         }
+    }
+    // Logs get every event, the console only what the verbosity asks for
+    let mut reporters: Vec<Box<dyn Reporter>> = Vec::new();
+    if let Some(path) = &args.log_csv {
+        match CsvReporter::open(path) {
+            Ok(reporter) => reporters.push(Box::new(reporter)),
+            Err(e) => {
+                eprintln!("Failed to open the CSV log: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let Some(path) = &args.log_jsonl {
+        match JsonLinesReporter::open(path) {
+            Ok(reporter) => reporters.push(Box::new(reporter)),
+            Err(e) => {
+                eprintln!("Failed to open the JSON lines log: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    let live = match args.live.as_deref().map(|address| live::LiveServer::listen(address, game_area, &engine.config().vm)).transpose() {
+        Ok(live) => live,
+        Err(e) => {
+            eprintln!("Failed to start the live stream: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    if let Some(live) = &live {
+        reporters.push(Box::new(live.clone()));
+    }
+    let mut tensorboard: Option<tensorboard::EventWriter> = match &args.tensorboard {
+        Some(directory) => match tensorboard::EventWriter::create(directory) {
+            Ok(writer) => {
+                if verbosity >= Verbosity::Normal {
+                    writeln!(out, "TensorBoard events written to {}", writer.path().display()).ok();
+                }
+                Some(writer)
+            }
+            Err(e) => {
+                eprintln!("Failed to create the TensorBoard event file in {}: {}", directory.display(), e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        },
+        None => None,
+    };
+    let case_names: Vec<String> = cases.iter().map(|case| case.name.clone()).collect();
+    let genome_format = cli.genome_format.clone();
+    let mut console_reporter = ConsoleReporter::new(step_alphabet, treasures)
+        .writer(human_output(summary_on_stdout))
+        .new_best(verbosity >= Verbosity::Verbose)
+        // Quietly the final result is printed once the budget is spent
+        .solutions(verbosity > Verbosity::Quiet || engine.config().after_solution == Some(0))
+        .details(move |chromosome: &Chromosome| solution_details(&case_names, resampling, &genome_format, instruction_set, step_alphabet, chromosome));
+    if let Some((tour, fitness)) = &optimum {
+        console_reporter = console_reporter.optimum(*fitness, tour.steps.len());
+    }
+    let mut dashboard: Option<tui::Dashboard> = None;
+    if args.tui {
+        match tui::Dashboard::start(game_area) {
+            Ok(started) => dashboard = Some(started),
+            Err(e) => {
+                eprintln!("Failed to start the dashboard: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    // Prompts would show more than the final result, an explicit policy replaces them
+    let interactive = !args.non_interactive && args.until.is_none() && dashboard.is_none() && verbosity > Verbosity::Quiet;
+    // Paths shown on the dashboard
+    let trajectory_vm = Vm::new(VmConfig { record_trajectory: true, ..engine.config().vm.clone() });
 
-        generations += 1;
-        if generations % 500 == 0 {
-            print!("\r\t\t\t\t\t\t\t\r");
+    let mut last_report = std::time::Instant::now();
+    // Best and mean fitness of every generation for the chart
+    let mut fitness_history: Vec<(f64, f64)> = Vec::new();
+    // Evaluations given up by --early-exit and the iterations they did not run
+    let mut bounded: (u64, u64) = (0, 0);
 
-            match &best_so_far {
-                Some(best_so_far) => {
-                    print!("Generation {}; F: {:.4}, T: {}, S: {}, I: {}",
-                       generations, best_so_far.fitness,
-                       best_so_far.found_treasures, best_so_far.steps.len(), best_so_far.iterations);
-                },
-                _ => {}
+    if let Some(resumed) = resumed {
+        if verbosity >= Verbosity::Normal {
+            writeln!(out, "Resuming at generation {}", resumed.snapshot.generation).ok();
+        }
+        if let Err(e) = engine.restore(resumed.snapshot) {
+            eprintln!("The checkpoint does not match the options: {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    }
+    let progress = progress::Progress::new(engine.config().generations, dashboard.is_none() && verbosity > Verbosity::Quiet);
+    let console = match args.control_port.map(|port| control::Console::listen(port, engine.config().subjects)).transpose() {
+        Ok(console) => console,
+        Err(e) => {
+            eprintln!("Failed to open the control port: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    let initial_status = status::Status { seed, map_hash: map_hash.clone(), ..status::Status::default() };
+    let status_server = match args.serve.as_deref().map(|address| status::StatusServer::listen(address, initial_status)).transpose() {
+        Ok(status_server) => status_server,
+        Err(e) => {
+            eprintln!("Failed to start the status server: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    let autosave = |generation: u32, best: &Chromosome| {
+        if let Some(path) = &args.autosave {
+            let autosave = report::Autosave {
+                seed,
+                map_hash: map_hash.clone(),
+                generation,
+                best: report::BestSummary::new(best, step_alphabet.format(&best.steps), treasures),
+            };
+            if let Err(e) = report::write_autosave(&autosave, path) {
+                progress.suspend(|| eprintln!("Failed to autosave the best solution: {}", e));
             }
-            std::io::stdout().flush().ok();
         }
+    };
+    let save_checkpoint = |engine: &Engine| {
+        if let Some(path) = &args.checkpoint {
+            let saved = checkpoint::Checkpoint {
+                seed,
+                map_hash: map_hash.clone(),
+                snapshot: engine.snapshot(),
+            };
+            match checkpoint::save(path, &saved) {
+                Ok(()) => tracing::info!(generation = engine.generation(), path = %path.display(), "checkpoint saved"),
+                Err(e) => progress.suspend(|| eprintln!("Failed to save the checkpoint: {}", e)),
+            }
+        }
+    };
+    let events = engine.events();
+    // A second Ctrl-C exits right away, e.g. while waiting for an answer
+    let cancellation = engine.cancellation_token();
+    if let Err(e) = ctrlc::set_handler(move || {
+        if INTERRUPTED.swap(true, atomic::Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        cancellation.cancel();
+    }) {
+        eprintln!("Failed to install the Ctrl-C handler: {}", e);
+    }
+    let mut search = engine.iter();
+    let stop_reason: StopReason = loop {
+        let engine = search.engine();
+        let generations = engine.generation();
+        // A stop requested over HTTP ends the search like Ctrl-C
+        if status_server.as_ref().is_some_and(status::StatusServer::stop_requested) {
+            engine.stop();
+        }
+        if generations > 0 && generations.is_multiple_of(args.checkpoint_every) {
+            save_checkpoint(engine);
+        }
+        for command in console.as_ref().map_or(Vec::new(), control::Console::pending) {
+            match command {
+                ControlCommand::Mutation(probability) => {
+                    if let Err(e) = engine.set_mutation_probability(probability) {
+                        progress.suspend(|| eprintln!("Control command refused: {}", e));
+                        continue;
+                    }
+                }
+                ControlCommand::Elitism(count) => {
+                    if let Err(e) = engine.set_elitism(count) {
+                        progress.suspend(|| eprintln!("Control command refused: {}", e));
+                        continue;
+                    }
+                }
+                ControlCommand::Restart => {
+                    engine.restart();
+                    if let Some(status_server) = &status_server {
+                        status_server.update(|status| status.restarts += 1);
+                    }
+                }
+            }
+            tracing::info!(generation = generations, ?command, "control command applied");
+            if verbosity >= Verbosity::Normal && dashboard.is_none() {
+                progress.suspend(|| console_reporter.note(generations, format!("applied {:?}", command))).ok();
+            }
+        }
+        progress.update(generations + 1, engine.best(), optimum.as_ref().map(|(_, optimal_fitness)| *optimal_fitness), engine.evaluations());
 
-        for i in 0..current_generation.len() {
-            let current_chromosome = current_generation.get_mut(i).unwrap();
-            let mut steps: String = String::new();
-            let (iters, found_treasures) = core::run_virtual_machine(
-                &current_chromosome.genes, &game_area, &mut steps, player_x, player_y, treasures);
+        let stepped = {
+            let _generation = tracing::info_span!("generation", number = generations + 1).entered();
+            search.next()
+        };
+        let Some(stepped) = stepped else {
+            let stop_reason = search.stop_reason().unwrap();
+            // Closed before the final result is printed
+            dashboard = None;
+            forward_events(&events, Some(&mut console_reporter), &mut reporters, &progress, false);
+            // Without prompts the generation budget is final, the time and evaluation budgets always are
+            if interactive && matches!(stop_reason, StopReason::Solved | StopReason::GenerationLimit)
+                && progress.suspend(|| ask_user("Do you want to keep searching for a better solution? y/N: ")) {
+                let engine = search.engine();
+                if stop_reason == StopReason::Solved {
+                    engine.set_after_solution(None);
+                } else {
+                    engine.set_generations(u32::MAX);
+                    progress.unbounded();
+                }
+                continue;
+            }
+            break stop_reason;
+        };
 
-            current_chromosome.found_treasures = found_treasures;
-            current_chromosome.iterations = iters;
-            current_chromosome.fitness = core::calculate_fitness(steps.len(), found_treasures, treasures);
-            current_chromosome.steps = steps;
+        let engine = search.engine();
+        let generations = stepped.generation;
+        let evaluations = engine.evaluations();
+        let target_generations = Some(engine.config().generations).filter(|&target| target != u32::MAX);
+        // Statistics are printed every generation with -vv
+        let report_due = match args.report_every {
+            ReportInterval::Generations(interval) => generations.is_multiple_of(interval),
+            ReportInterval::Seconds(interval) => last_report.elapsed().as_secs_f64() >= interval,
+        };
+        let report_stats = verbosity >= Verbosity::VeryVerbose || (verbosity >= Verbosity::Normal && report_due);
+        if report_stats {
+            last_report = std::time::Instant::now();
+        }
+        let generation_stats = &stepped.stats;
+        if let Some(writer) = &mut tensorboard {
+            if let Err(e) = writer.write_generation(generation_stats, &engine.operator_stats(), &stepped.throughput) {
+                progress.suspend(|| eprintln!("Failed to write the TensorBoard events, they are no longer written: {}", e));
+                tensorboard = None;
+            }
+        }
+        if let Some(status_server) = &status_server {
+            status_server.update(|status| {
+                status.generation = generations;
+                status.target_generations = target_generations;
+                status.evaluations = evaluations;
+                status.evaluations_per_second = stepped.throughput.evaluations_per_second();
+                status.elapsed_seconds = start_time.elapsed().as_secs_f64();
+                status.stats = Some(generation_stats.clone());
+            });
+        }
+        if args.plot.is_some() {
+            fitness_history.push((generation_stats.best_fitness, generation_stats.mean_fitness));
+        }
+        tracing::debug!(max = generation_stats.best_fitness, mean = generation_stats.mean_fitness,
+                        median = generation_stats.median_fitness, min = generation_stats.worst_fitness,
+                        std_dev = generation_stats.fitness_std_dev, diversity = generation_stats.diversity,
+                        best_treasures = generation_stats.best_treasures, best_steps = generation_stats.best_steps,
+                        evaluations, "generation evaluated");
+        if stepped.improved {
+            let best = engine.best().unwrap();
+            tracing::info!(fitness = best.fitness, treasures = best.found_treasures, steps = best.steps.len(), "new best solution");
+            if engine.solved() {
+                tracing::info!(fitness = best.fitness, steps = best.steps.len(), iterations = best.iterations, "solution found");
+            }
+            if let Some(dashboard) = &mut dashboard {
+                dashboard.set_best(trajectory_vm.run(&best.genes, game_area).trajectory);
+            }
+            if let Some(status_server) = &status_server {
+                status_server.update(|status| status.best = Some(report::BestSummary::new(best, step_alphabet.format(&best.steps), treasures)));
+            }
+            autosave(generations, best);
+        }
+        forward_events(&events, dashboard.is_none().then_some(&mut console_reporter), &mut reporters, &progress, report_stats);
+        if let Some(dashboard) = &mut dashboard {
+            let snapshot = tui::Snapshot {
+                generation: generations,
+                target_generations,
+                best: engine.best(),
+                stats: generation_stats,
+                evaluations_per_second: evaluations as f64 / start_time.elapsed().as_secs_f64().max(1e-9),
+            };
+            if dashboard.update(&snapshot) == tui::Control::Stop {
+                engine.stop();
+            }
         }
 
-        current_generation.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
-        let mut total_fitness: f64 = 0f64;
-        for chromosome in &current_generation {
-            total_fitness += chromosome.fitness;
-            if chromosome.found_treasures == treasures && (best_so_far.is_none() || chromosome.fitness > best_so_far.as_ref().unwrap().fitness) {
-                println!("\nSuccessful solution! Generation: {}, Fitness: {}, Steps: {} ({}), Iterations: {}",
-                         generations, chromosome.fitness, chromosome.steps, chromosome.steps.len(), chromosome.iterations);
-                println!("{:?}", chromosome.genes);
+        let throughput = stepped.throughput;
+        tracing::debug!(evaluations_per_second = throughput.evaluations_per_second(), iterations_per_second = throughput.iterations_per_second(),
+                        evaluation_seconds = throughput.evaluation_time.as_secs_f64(), selection_seconds = throughput.selection_time.as_secs_f64(),
+                        reproduction_seconds = throughput.reproduction_time.as_secs_f64(), bounded = throughput.bounded,
+                        skipped_iterations = throughput.skipped_iterations, "generation throughput");
+        bounded = (bounded.0 + throughput.bounded, bounded.1 + throughput.skipped_iterations);
+        if args.throughput && report_stats && dashboard.is_none() {
+            progress.suspend(|| console_reporter.note(generations, throughput)).ok();
+        }
+    };
+    progress.finish();
+    let generations = engine.generation();
+    let evaluations = engine.evaluations();
+    let best_so_far: Option<Chromosome> = engine.best().cloned();
+    let solved = engine.solved();
+    tracing::info!(generations, evaluations, seconds = start_time.elapsed().as_secs_f64(), solved, "search finished");
+    let stop_requested = status_server.as_ref().is_some_and(status::StatusServer::stop_requested);
+    let interrupted = stop_reason == StopReason::Cancelled || stop_requested;
+    if interrupted {
+        save_checkpoint(&engine);
+    }
+    if let Some(live) = &live {
+        live.finish(generations, best_so_far.as_ref(), solved);
+    }
+    if let Some(status_server) = &status_server {
+        status_server.update(|status| {
+            status.generation = generations;
+            status.evaluations = evaluations;
+            status.elapsed_seconds = start_time.elapsed().as_secs_f64();
+            status.best = best_so_far.as_ref().map(|best| report::BestSummary::new(best, step_alphabet.format(&best.steps), treasures));
+            status.finished = true;
+        });
+    }
+    if args.early_exit && verbosity >= Verbosity::Normal {
+        writeln!(out, "\nEarly exit: {} evaluations given up, {} iterations skipped", bounded.0, bounded.1).ok();
+    }
+    if let Some(best) = best_so_far.as_ref().filter(|_| verbosity >= Verbosity::Normal) {
+        writeln!(out, "\nBest path:").ok();
+        write!(out, "{}", render::render_map(game_area, &trajectory_vm.run(&best.genes, game_area).trajectory, cli.color.enabled())).ok();
+    }
 
-                if !ask_user("Do you want to keep searching for a better solution? y/N: ") {
-                    return;
-                }
+    let mut exit_code = if solved { ExitCode::SUCCESS } else { ExitCode::from(EXIT_UNSOLVED) };
+    if let Some(path) = &args.plot {
+        match plot::write_fitness_curve(path, &fitness_history) {
+            Ok(()) if verbosity >= Verbosity::Normal => {
+                writeln!(out, "Fitness chart written to {}", path.display()).ok();
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("Failed to write the chart: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let (Some(path), Some(best)) = (&args.svg, &best_so_far) {
+        let result = trajectory_vm.run(&best.genes, game_area);
+        match svg::write_path(path, game_area, &result.trajectory, &result.collected_treasures) {
+            Ok(()) if verbosity >= Verbosity::Normal => {
+                writeln!(out, "Best path written to {}", path.display()).ok();
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("Failed to write the SVG: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let (Some(path), Some(best)) = (&args.gif, &best_so_far) {
+        let result = trajectory_vm.run(&best.genes, game_area);
+        match animation::write_path(path, game_area, &result.trajectory) {
+            Ok(()) if verbosity >= Verbosity::Normal => {
+                writeln!(out, "Best path animation written to {}", path.display()).ok();
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("Failed to write the GIF: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let (Some(path), Some(best)) = (&args.save_genome, &best_so_far) {
+        let header = GenomeHeader::new(instruction_set)
+            .map_fingerprint(map::fingerprint(game_area))
+            .metadata("program", concat!("treasure-search ", env!("CARGO_PKG_VERSION")))
+            .metadata("seed", seed.to_string())
+            .metadata("generation", generations.to_string())
+            .metadata("fitness function", fitness_function.name())
+            .metadata("fitness", best.fitness.to_string())
+            .metadata("treasures", format!("{}/{}", best.found_treasures, treasures))
+            .metadata("steps", step_alphabet.format(&best.steps));
+        match genome_file::encode(&header, &best.genes).and_then(|bytes| Ok(std::fs::write(path, bytes)?)) {
+            Ok(()) if verbosity >= Verbosity::Normal => {
+                writeln!(out, "Best genome written to {}", path.display()).ok();
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("Failed to write the genome: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let Some(path) = &args.manifest {
+        let written = cli::options_without(manifest::LEFT_OUT).map_err(|e| e.to_string()).and_then(|args| {
+            let manifest = manifest::Manifest {
+                program: manifest::program(),
+                args,
+                seed,
+                instruction_set: format!("{:?}", instruction_set).to_ascii_lowercase(),
+                map: game_area.to_string(),
+                extra_maps: maps[1..].iter().map(|(_, game_area)| game_area.to_string()).collect(),
+                map_hash: map_hash.clone(),
+                config_fingerprint: format!("{:016x}", engine.config().fingerprint()),
+                generations,
+                best_fitness: best_so_far.as_ref().map(|best| best.fitness),
+                solved,
+            };
+            return manifest::save(path, &manifest);
+        });
+        match written {
+            Ok(()) if verbosity >= Verbosity::Normal => {
+                writeln!(out, "Manifest written to {}", path.display()).ok();
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("Failed to write the manifest: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let (Some(path), Some(best)) = (&args.leaderboard, &best_so_far) {
+        let record = leaderboard::Record {
+            genome: best.genes.to_vec(),
+            instruction_set: format!("{:?}", instruction_set).to_ascii_lowercase(),
+            steps: step_alphabet.format(&best.steps),
+            step_count: best.steps.len(),
+            found_treasures: best.found_treasures,
+            treasures,
+            fitness: best.fitness,
+            fitness_function: String::from(fitness_function.name()),
+            seed,
+            date: leaderboard::now(),
+            program: manifest::program(),
+        };
+        match leaderboard::submit(path, &map_hash, record) {
+            Ok(_) if verbosity < Verbosity::Normal => {}
+            Ok(leaderboard::Submission::Set(None)) => {
+                writeln!(out, "First record for this map written to {}", path.display()).ok();
+            }
+            Ok(leaderboard::Submission::Set(Some(previous))) => {
+                writeln!(out, "New record for this map, beating {}", describe_record(&previous)).ok();
+            }
+            Ok(leaderboard::Submission::Kept(record)) => {
+                writeln!(out, "The record for this map stands: {}", describe_record(&record)).ok();
+            }
+            Err(e) => {
+                eprintln!("Failed to update the leaderboard: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    if let Some(path) = &args.save_population {
+        // Offspring bred after the last generation are evaluated too, so the saved population is the current one,
+        // whole even when the evaluation budget is spent. It is saved as of the last generation the run reported,
+        // which evaluate() counts one past.
+        engine.set_max_evaluations(None);
+        engine.evaluate();
+        let mut chromosomes: Vec<Chromosome> = engine.population().chromosomes().to_vec();
+        chromosomes.sort_by(|a, b| comparison.compare(b, a));
+        let saved = checkpoint::SavedPopulation {
+            seed,
+            map_hash: map_hash.clone(),
+            generation: generations,
+            stats: engine.population().stats(generations),
+            chromosomes: &chromosomes,
+        };
+        match checkpoint::save_population(path, &saved) {
+            Ok(()) if verbosity >= Verbosity::Normal => {
+                writeln!(out, "Population written to {}", path.display()).ok();
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("Failed to write the population: {}", e);
+                exit_code = ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+
+    if args.output == "json" || args.output_file.is_some() {
+        let vm_config = &engine.config().vm;
+        let ga_config = engine.config();
+        let summary = report::RunSummary {
+            config: report::RunConfigSummary {
+                subjects: ga_config.subjects,
+                target_generations: ga_config.generations,
+                mutation_probability: ga_config.mutation_probability,
+                selection: String::from(ga_config.selection.name()),
+                scaling: ga_config.scaling.to_string(),
+                comparison: format!("{:?}", comparison).to_ascii_lowercase(),
+                fitness_function: String::from(fitness_function.name()),
+                instruction_set: format!("{:?}", instruction_set).to_ascii_lowercase(),
+                iteration_limit: vm_config.iteration_limit,
+                memory_size: vm_config.memory_size,
+                collision_policy: format!("{:?}", vm_config.collision_policy).to_ascii_lowercase(),
+                max_moves: Some(vm_config.max_moves).filter(|&max_moves| max_moves != u32::MAX),
+                memory_mapped_inputs: vm_config.memory_mapped_inputs,
+                samples: resampling.samples,
+            },
+            seed,
+            map_hash,
+            generations,
+            evaluations,
+            wall_time_seconds: start_time.elapsed().as_secs_f64(),
+            best: best_so_far.as_ref().map(|best| report::BestSummary::new(best, step_alphabet.format(&best.steps), treasures)),
+            optimum: optimum.as_ref().map(|(tour, fitness)| report::OptimumSummary {
+                fitness: *fitness,
+                steps: step_alphabet.format(&tour.steps),
+                step_count: tour.steps.len(),
+                exact: tour.exact,
+            }),
+        };
+        if let Err(e) = report::write_json(&summary, args.output_file.as_deref()) {
+            eprintln!("Failed to write the summary: {}", e);
+            exit_code = ExitCode::from(EXIT_FAILURE);
+        }
+    }
+    return exit_code;
+}
+
+fn describe_record(record: &leaderboard::Record) -> String {
+    return format!("fitness {}, {}/{} treasures in {} steps ({}, seed {}, {})", record.fitness, record.found_treasures,
+                   record.treasures, record.step_count, record.fitness_function, record.seed, record.date);
+}
+
+fn profile(cli: &Cli, args: &ProfileArgs, vm_config: VmConfigBuilder, game_area: &GameArea) -> ExitCode {
+    let vm = Vm::new(vm_config.seed(vm_seed(&mut Pcg64::seed_from_u64(cli.seed.unwrap_or(0)))).record_trajectory(true).build());
+    let genes = match load_genome(&args.genome, vm.config(), game_area) {
+        Some((genes, _)) => genes,
+        None => return ExitCode::from(EXIT_INVALID_INPUT),
+    };
+
+    let profile = profiler::profile_genome(&vm, &genes, game_area, args.runs.max(1));
+    profiler::print_report(&profile, &genes, cli.instruction_set);
+    return ExitCode::SUCCESS;
+}
+
+fn replay(cli: &Cli, args: &ReplayArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction, cases: &[Case]) -> ExitCode {
+    let step_alphabet = cli.glyphs;
+    let instruction_set = cli.instruction_set;
+    let dump_at: Vec<u32> = match args.dump_at.as_deref().map(dump::parse_iterations) {
+        None => Vec::new(),
+        Some(Ok(dump_at)) => dump_at,
+        Some(Err(e)) => {
+            eprintln!("Invalid dump iterations: {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    let dump_dir = &args.dump_dir;
+    let vm = Vm::new(vm_config.seed(vm_seed(&mut Pcg64::seed_from_u64(cli.seed.unwrap_or(0)))).record_trajectory(true).build());
+    // Only the default start is traced
+    let game_area = &cases[0].game_area;
+    let genes = match load_genome(&args.genome, vm.config(), game_area) {
+        Some((genes, header)) => {
+            if let Some(header) = header {
+                let metadata: Vec<String> = header.metadata.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+                println!("Genome file version {}{}{}", header.version, if metadata.is_empty() { "" } else { ", " }, metadata.join(", "));
+            }
+            genes
+        }
+        None => return ExitCode::from(EXIT_INVALID_INPUT),
+    };
+    let treasures = game_area.treasure_count();
+    let fitness_context = FitnessContext::new(treasures, vm.config());
+    let mut dumped: Vec<u32> = Vec::new();
+    let result = vm.run_traced(&genes, game_area, |state| {
+        if dump_at.binary_search(&state.iteration).is_ok() {
+            match dump::write_state(dump_dir, state, instruction_set, step_alphabet) {
+                Ok(path) => println!("Dumped iteration {} to {}", state.iteration, path.display()),
+                Err(e) => eprintln!("Failed to dump iteration {}: {}", state.iteration, e),
+            }
+            dumped.push(state.iteration);
+        }
+    });
+
+    for iteration in dump_at.iter().filter(|iteration| !dumped.contains(iteration)) {
+        eprintln!("Iteration {} was not reached", iteration);
+    }
+    let optimum = optimum(game_area, fitness_function, &fitness_context);
+    print_optimum(&mut std::io::stdout(), optimum.as_ref(), step_alphabet);
+    let fitness = fitness_function.evaluate(&result, &fitness_context);
+    println!("Fitness: {}, Steps: {} ({}), Treasures: {}/{}, Iterations: {}, Termination: {:?}{}",
+             fitness, step_alphabet.format(&result.steps), result.steps.len(),
+             result.found_treasures, treasures, result.iterations, result.termination,
+             relative_to_optimum(optimum.as_ref(), fitness, result.steps.len()));
+    println!("Complexity: {} distinct instructions executed", result.executed_instructions);
+    if args.play {
+        render::play(game_area, &result.trajectory, std::time::Duration::from_millis(args.frame_delay), cli.color.enabled());
+    } else {
+        print!("{}", render::render_map(game_area, &result.trajectory, cli.color.enabled()));
+    }
+    for case in cases.iter().skip(1) {
+        let result = vm.run(&genes, &case.game_area);
+        println!("{}: Fitness: {}, Steps: {} ({}), Treasures: {}/{}, Termination: {:?}",
+                 case.name, fitness_function.evaluate(&result, &fitness_context),
+                 step_alphabet.format(&result.steps), result.steps.len(),
+                 result.found_treasures, treasures, result.termination);
+    }
+    // Solved only if every treasure was collected from the default start
+    if result.found_treasures < treasures {
+        return ExitCode::from(EXIT_UNSOLVED);
+    }
+    return ExitCode::SUCCESS;
+}
+
+// The route of a person scored like a program, optionally next to the route of a genome
+fn play(cli: &Cli, args: &PlayArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction, game_area: &GameArea) -> ExitCode {
+    let step_alphabet = cli.glyphs;
+    let vm = Vm::new(vm_config.seed(vm_seed(&mut Pcg64::seed_from_u64(cli.seed.unwrap_or(0)))).record_trajectory(true).build());
+    let genes = match args.against.as_deref().map(|genome| load_genome(genome, vm.config(), game_area)) {
+        Some(None) => return ExitCode::from(EXIT_INVALID_INPUT),
+        Some(Some((genes, _))) => Some(genes),
+        None => None,
+    };
+    let treasures = game_area.treasure_count();
+    let fitness_context = FitnessContext::new(treasures, vm.config());
+
+    let result = match human::play(game_area, vm.config(), step_alphabet, cli.color.enabled()) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Failed to read the moves: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    let optimum = optimum(game_area, fitness_function, &fitness_context);
+    println!();
+    print_optimum(&mut std::io::stdout(), optimum.as_ref(), step_alphabet);
+    let fitness = fitness_function.evaluate(&result, &fitness_context);
+    println!("You: Fitness: {}, Steps: {} ({}), Treasures: {}/{}, Termination: {:?}{}",
+             fitness, step_alphabet.format(&result.steps), result.steps.len(), result.found_treasures, treasures,
+             result.termination, relative_to_optimum(optimum.as_ref(), fitness, result.steps.len()));
+    print!("{}", render::render_map(game_area, &result.trajectory, cli.color.enabled()));
+    if let Some(genes) = genes {
+        let program = vm.run(&genes, game_area);
+        let program_fitness = fitness_function.evaluate(&program, &fitness_context);
+        println!("Program: Fitness: {}, Steps: {} ({}), Treasures: {}/{}, Termination: {:?}{}",
+                 program_fitness, step_alphabet.format(&program.steps), program.steps.len(), program.found_treasures, treasures,
+                 program.termination, relative_to_optimum(optimum.as_ref(), program_fitness, program.steps.len()));
+        print!("{}", render::render_map(game_area, &program.trajectory, cli.color.enabled()));
+        println!("{}", match fitness.total_cmp(&program_fitness) {
+            Ordering::Greater => "You beat the program!",
+            Ordering::Equal => "A tie with the program",
+            Ordering::Less => "The program wins",
+        });
+    }
+    if result.found_treasures < treasures {
+        return ExitCode::from(EXIT_UNSOLVED);
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn analyze(cli: &Cli, args: &AnalyzeArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction, game_area: &GameArea) {
+    let instruction_set = cli.instruction_set;
+    let seed: u64 = cli.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}, Fitness function: {}", seed, fitness_function.name());
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let vm = Vm::new(vm_config.seed(vm_seed(&mut rng)).build());
+    let fitness_context = FitnessContext::new(game_area.treasure_count(), vm.config());
+    // Breadth-first distances between the start and the treasures, Held-Karp over their orders up to
+    // solver::EXACT_TREASURE_LIMIT treasures
+    match optimum(game_area, fitness_function, &fitness_context) {
+        Some(optimum) => {
+            print_optimum(&mut std::io::stdout(), Some(&optimum), cli.glyphs);
+            let order: Vec<String> = optimum.0.order.iter().map(|(x, y)| format!("({}, {})", x, y)).collect();
+            println!("Treasure order: {}", order.join(", "));
+        }
+        None => println!("Some treasure cannot be reached, there is no tour collecting every treasure"),
+    }
+    if args.tour_only {
+        return;
+    }
+    let evaluate = |genes: &[u8]| -> f64 {
+        if verifier::verify_genome(genes, instruction_set, vm.config().memory_size).is_err() {
+            return 0.0;
+        }
+        return fitness_function.evaluate(&vm.run(genes, game_area), &fitness_context);
+    };
+    let report = landscape::analyze(evaluate, vm.config().memory_size, args.samples, args.walk_length, &mut rng);
+    landscape::print_report(&report);
+}
+
+fn bench(cli: &Cli, args: &BenchArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction, game_area: &GameArea) {
+    let instruction_set = cli.instruction_set;
+    let mut rng = Pcg64::seed_from_u64(cli.seed.unwrap_or(0));
+    let vm = Vm::new(vm_config.seed(vm_seed(&mut rng)).build());
+    let program_size: usize = vm.config().memory_size;
+    let fitness_context = FitnessContext::new(game_area.treasure_count(), vm.config());
+    // Genomes are generated up front so only the evaluation is timed
+    let genomes: Vec<Vec<u8>> = (0..args.evaluations.max(1))
+        .map(|_| environment::random_genome(&mut rng, instruction_set, program_size))
+        .collect();
+
+    let start = std::time::Instant::now();
+    let mut iterations: u64 = 0;
+    let mut total_fitness: f64 = 0.0;
+    for genes in &genomes {
+        let result = vm.run(genes, game_area);
+        iterations += u64::from(result.iterations);
+        total_fitness += fitness_function.evaluate(&result, &fitness_context);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("Evaluations: {}, Time: {:.3} s, Evaluations per second: {:.0}, Iterations per second: {:.0}",
+             genomes.len(), elapsed, genomes.len() as f64 / elapsed, iterations as f64 / elapsed);
+    println!("Mean fitness: {:.4}", total_fitness / genomes.len() as f64);
+}
+
+fn map_elites(cli: &Cli, args: &MapElitesArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction, game_area: &GameArea) {
+    let step_alphabet = cli.glyphs;
+    let comparison = cli.compare;
+    let evaluations = args.evaluations;
+    let mutation_probability = args.mutation;
+    let instruction_set = cli.instruction_set;
+    let seed: u64 = cli.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}, Fitness function: {}", seed, fitness_function.name());
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    // Coverage is measured on the recorded trajectory
+    let vm = Vm::new(vm_config.record_trajectory(true).seed(vm_seed(&mut rng)).build());
+    let program_size: usize = vm.config().memory_size;
+    let fitness_context = FitnessContext::new(game_area.treasure_count(), vm.config());
+    let max_steps = vm.config().iteration_limit.min(vm.config().max_moves) as usize;
+    let mut archive = elites::Archive::new(args.bins, max_steps);
+    // Random programs seed the archive before it is varied
+    let initial = (evaluations / 10).max(1);
+
+    for evaluation in 0..evaluations {
+        let genes = match archive.random_elite(&mut rng) {
+            Some(parent1) if evaluation >= initial => {
+                let parent2 = archive.random_elite(&mut rng).unwrap();
+                core::reproduce(parent1, parent2, &UniformCrossover, &BitFlipMutation, mutation_probability, &mut rng)
             }
+            _ => Genes::from(environment::random_genome(&mut rng, instruction_set, program_size)),
+        };
+        if verifier::verify_genome(&genes, instruction_set, program_size).is_err() {
+            continue;
         }
+        let result = vm.run(&genes, game_area);
+        let mut chromosome = Chromosome::with_genes(genes);
+        chromosome.fitness = fitness_function.evaluate(&result, &fitness_context);
+        chromosome.found_treasures = result.found_treasures;
+        chromosome.iterations = result.iterations;
+        chromosome.complexity = result.executed_instructions;
+        let coverage = elites::coverage(&result.trajectory, game_area);
+        chromosome.steps = result.steps;
+        archive.insert(chromosome, coverage, comparison);
+    }
+
+    elites::print_archive(&archive);
+    if let Some(best) = archive.elites().max_by(|a, b| comparison.compare(a, b)) {
+        println!("Best elite: Fitness: {}, Steps: {} ({}), Treasures: {}, Iterations: {}",
+                 best.fitness, step_alphabet.format(&best.steps), best.steps.len(), best.found_treasures, best.iterations);
+        encoding::print_genome(best, &cli.genome_format, cli.instruction_set, step_alphabet);
+    }
+}
+
+// The plain algorithm on a small population, every phase is explained and followed by a pause
+fn teach(cli: &Cli, args: &TeachArgs, vm_config: VmConfigBuilder, fitness_function: &dyn FitnessFunction, game_area: &GameArea) {
+    let step_alphabet = cli.glyphs;
+    let comparison = cli.compare;
+    let instruction_set = cli.instruction_set;
+    let subjects_num = args.subjects as usize;
+    let seed: u64 = cli.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}, Fitness function: {}", seed, fitness_function.name());
 
-        let mut new_generation: Vec<Chromosome> = Vec::with_capacity(subjects_num);
-        while new_generation.len() < subjects_num {
-            let (parent1, parent2) = if selection_method == 0 {
-                core::selection_roulette(&current_generation, total_fitness, &mut rng)
-            } else {
-                core::selection_tournament(&current_generation, &mut rng)
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let vm = Vm::new(vm_config.seed(vm_seed(&mut rng)).build());
+    let program_size: usize = vm.config().memory_size;
+    let fitness_context = FitnessContext::new(game_area.treasure_count(), vm.config());
+    let shown_bytes = args.bytes.min(program_size);
+    let pause = !args.no_pause && std::io::IsTerminal::is_terminal(&std::io::stdin());
+    print!("{}", render::render_map(game_area, &[], cli.color.enabled()));
+    println!("{} random programs of {} bytes, the first {} bytes of every genome are shown bit by bit",
+             subjects_num, program_size, shown_bytes);
+
+    let mut population: Population = Population::from_genomes(
+        (0..subjects_num).map(|_| Genes::from(environment::random_genome(&mut rng, instruction_set, program_size))));
+    for generation in 1..=args.generations {
+        teach::print_heading(generation, "evaluation");
+        println!("Every program runs on the map, its fitness is computed from the treasures it found and the steps it took");
+        for chromosome in population.chromosomes_mut() {
+            if verifier::verify_genome(&chromosome.genes, instruction_set, program_size).is_err() {
+                // Invalid programs are not run, they keep zero fitness
+                continue;
+            }
+            let result = vm.run(&chromosome.genes, game_area);
+            chromosome.fitness = fitness_function.evaluate(&result, &fitness_context);
+            chromosome.found_treasures = result.found_treasures;
+            chromosome.iterations = result.iterations;
+            chromosome.steps = result.steps;
+        }
+        population.sort_by_fitness(comparison);
+        population.scale(FitnessScaling::None, comparison);
+        let total_fitness: f64 = population.total_fitness();
+        let chromosomes = population.chromosomes();
+        println!("Sorted from the best:");
+        teach::print_population(chromosomes, shown_bytes, step_alphabet);
+        teach::pause(pause);
+        if generation == args.generations {
+            break;
+        }
+
+        teach::print_heading(generation, "selection");
+        println!("{}", match args.selection {
+            Selection::Roulette => "Roulette: every program owns a slice of the wheel as large as its fitness, the wheel is spun for every parent",
+            Selection::Tournament => "Tournament: two random programs are compared for every parent, the better one is chosen",
+            Selection::Alias => "Alias: the odds of the roulette, every parent is drawn from a table built once for the generation",
+        });
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        while pairs.len() * (core::NUM_OF_CHILDREN as usize) < subjects_num {
+            let mut pick = || match args.selection {
+                Selection::Roulette | Selection::Alias => {
+                    let (index, point) = core::roulette_pick(chromosomes, total_fitness, &mut rng);
+                    (index, teach::explain_roulette(chromosomes, total_fitness, index, point))
+                }
+                Selection::Tournament => {
+                    let (index, contestants) = core::tournament_pick(chromosomes, comparison, &mut rng);
+                    (index, teach::explain_tournament(chromosomes, index, contestants))
+                }
             };
+            let (parent1, explanation1) = pick();
+            let (parent2, explanation2) = pick();
+            println!("  Pair {}:\n    {}\n    {}", pairs.len() + 1, explanation1, explanation2);
+            pairs.push((parent1, parent2));
+        }
+        teach::pause(pause);
 
-            let mut iterations = subjects_num - new_generation.len();
-            if iterations > core::NUM_OF_CHILDREN as usize {
-                iterations = core::NUM_OF_CHILDREN as usize;
+        let mut children: Vec<(usize, (usize, usize), core::Inheritance)> = Vec::with_capacity(subjects_num);
+        for &(parent1, parent2) in &pairs {
+            for _ in 0..(core::NUM_OF_CHILDREN as usize).min(subjects_num - children.len()) {
+                let inheritance = core::reproduce_traced(&chromosomes[parent1], &chromosomes[parent2], &UniformCrossover, &BitFlipMutation,
+                                                       args.mutation, &mut rng);
+                children.push((children.len(), (parent1, parent2), inheritance));
             }
-            for _ in 0..iterations {
-                new_generation.push(core::Chromosome::with_instructions(core::reproduce(parent1, parent2, mutation_probability, &mut rng)));
+        }
+        teach::print_heading(generation, "crossover");
+        println!("Every bit of a child is taken from one of its parents at random (1 first parent, 2 second parent)");
+        for (child, parents, inheritance) in &children {
+            teach::print_crossover(*child, *parents, chromosomes, inheritance, shown_bytes);
+        }
+        teach::pause(pause);
+
+        teach::print_heading(generation, "mutation");
+        println!("Every bit of a child flips with probability {}", args.mutation);
+        for (child, _, inheritance) in &children {
+            teach::print_mutation(*child, inheritance, shown_bytes);
+        }
+        teach::pause(pause);
+        population = Population::from_genomes(children.into_iter().map(|(_, _, inheritance)| inheritance.genes));
+    }
+
+    let best = population.best().unwrap();
+    println!("\nBest program: Fitness: {}, Steps: {} ({}), Treasures: {}, Iterations: {}",
+             best.fitness, step_alphabet.format(&best.steps), best.steps.len(), best.found_treasures, best.iterations);
+    encoding::print_genome(best, &cli.genome_format, instruction_set, step_alphabet);
+}
+
+fn mapgen(cli: &Cli, args: &MapgenArgs) -> ExitCode {
+    let seed: u64 = cli.seed.unwrap_or_else(rand::random);
+    let game_area = match map::generate(args.width, args.height, args.treasures, &mut Pcg64::seed_from_u64(seed)) {
+        Ok(game_area) => game_area,
+        Err(e) => {
+            eprintln!("Invalid map size: {}", e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    if cli.dry_run {
+        println!("{:#?}", args);
+        println!("Seed: {}", seed);
+        return dry_run_map(&game_area);
+    }
+    match &args.output {
+        Some(path) => match std::fs::write(path, game_area.to_string()) {
+            Ok(()) => println!("Seed: {}, Map written to {}", seed, path.display()),
+            Err(e) => {
+                eprintln!("Failed to write the map: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
             }
+        },
+        None => print!("{}", game_area),
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn import_trail(cli: &Cli, args: &ImportTrailArgs) -> ExitCode {
+    let game_area = match std::fs::read_to_string(&args.file).map_err(error::Error::from).and_then(|text| map::parse_trail(&text)) {
+        Ok(game_area) => game_area,
+        Err(e) => {
+            eprintln!("Invalid trail {}: {}", args.file.display(), e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
         }
+    };
+    if cli.dry_run {
+        println!("{:#?}", args);
+        return dry_run_map(&game_area);
+    }
+    match &args.output {
+        Some(path) => match std::fs::write(path, game_area.to_string()) {
+            Ok(()) => println!("{}x{} trail with {} food pellets written to {}", game_area.width(), game_area.height(),
+                               game_area.treasure_count(), path.display()),
+            Err(e) => {
+                eprintln!("Failed to write the map: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        },
+        None => print!("{}", game_area),
+    }
+    return ExitCode::SUCCESS;
+}
 
-        debug_assert_eq!(new_generation.len(), subjects_num);
-        let local_best: Chromosome = current_generation.swap_remove(0);
+// Set for the child process so --watch from a configuration file does not start another watcher
+const WATCHED_ENV: &str = "TREASURE_SEARCH_WATCHED";
+// Map files are checked for changes this often
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
 
-        match &best_so_far {
-            None => {
-                best_so_far = Some(local_best);
-            },
-            Some(value) => {
-                if local_best.fitness > value.fitness {
-                    best_so_far = Some(local_best);
+// Runs the search in a child process with the same arguments, restarted whenever a map file is modified
+fn watch(map_paths: &[std::path::PathBuf]) -> ExitCode {
+    let modified = |path: &std::path::Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let executable = match std::env::current_exe() {
+        Ok(executable) => executable,
+        Err(e) => {
+            eprintln!("Failed to find the executable: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    let args: Vec<String> = std::env::args().skip(1).filter(|arg| arg != "--watch").collect();
+    // Ctrl-C reaches the child as well, the watcher waits for its result
+    if let Err(e) = ctrlc::set_handler(|| INTERRUPTED.store(true, atomic::Ordering::SeqCst)) {
+        eprintln!("Failed to install the Ctrl-C handler: {}", e);
+    }
+    let modified_all = || map_paths.iter().map(|path| modified(path)).collect::<Vec<_>>();
+    let mut last_modified = modified_all();
+    loop {
+        let mut child = match std::process::Command::new(&executable).args(&args).env(WATCHED_ENV, "1").spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("Failed to start the search: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        };
+        let mut status: Option<std::process::ExitStatus> = None;
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            if status.is_none() {
+                status = child.try_wait().ok().flatten();
+                if status.is_some() {
+                    println!("\nWaiting for changes of {}", display_paths(map_paths));
+                }
+            }
+            if INTERRUPTED.load(atomic::Ordering::SeqCst) {
+                let status = status.or_else(|| child.wait().ok());
+                return ExitCode::from(status.and_then(|status| status.code()).map_or(EXIT_FAILURE, |code| code as u8));
+            }
+            let current = modified_all();
+            if current != last_modified {
+                last_modified = current;
+                if status.is_none() {
+                    child.kill().ok();
+                    child.wait().ok();
                 }
+                println!("\n{} changed, restarting", display_paths(map_paths));
+                break;
             }
         }
+    }
+}
+
+fn display_paths(paths: &[std::path::PathBuf]) -> String {
+    return paths.iter().map(|path| path.display().to_string()).collect::<Vec<String>>().join(", ");
+}
 
-        current_generation = new_generation;
+fn batch(cli: &Cli, args: &BatchArgs) -> ExitCode {
+    let jobs = match batch::load_jobs(&args.file) {
+        Ok(jobs) => batch::repeat_jobs(jobs, args.runs),
+        Err(e) => {
+            eprintln!("Invalid job file {}: {}", args.file.display(), e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    if cli.dry_run {
+        println!("{:#?}", args);
+        return dry_run_jobs(&jobs);
+    }
+    let results = match batch::results_output(args.results.as_deref()).and_then(|mut output| batch::run_jobs(&jobs, args.jobs as usize, &mut output)) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Failed to run the jobs: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    batch::print_comparison(&jobs, &results);
+    batch::print_significance(&jobs, &results);
+    let solved = results.iter().filter(|result| result.solved()).count();
+    let unsolved = results.iter().filter(|result| result.unsolved()).count();
+    eprintln!("{} jobs: {} solved, {} unsolved, {} failed", results.len(), solved, unsolved, results.len() - solved - unsolved);
+    if solved + unsolved < results.len() {
+        return ExitCode::from(EXIT_FAILURE);
+    }
+    if unsolved > 0 {
+        return ExitCode::from(EXIT_UNSOLVED);
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn tournament(cli: &Cli, args: &TournamentArgs) -> ExitCode {
+    let tournament = match tournament::load(&args.file) {
+        Ok(tournament) => tournament,
+        Err(e) => {
+            eprintln!("Invalid tournament file {}: {}", args.file.display(), e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    let jobs = tournament.jobs();
+    if cli.dry_run {
+        println!("{:#?}", args);
+        println!("{} configurations, {} maps, seeds {:?}, {} evaluations per run", tournament.configs.len(),
+                 tournament.maps.len(), tournament.seeds, tournament.evaluations);
+        return dry_run_jobs(&jobs);
+    }
+    let output: Result<Box<dyn Write>, String> = match &args.results {
+        Some(path) => batch::results_output(Some(path)),
+        None => Ok(Box::new(std::io::sink())),
+    };
+    let results = match output.and_then(|mut output| batch::run_jobs(&jobs, args.jobs as usize, &mut output)) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Failed to run the tournament: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    tournament.print_report(&results);
+    let failed = results.iter().filter(|result| !result.solved() && !result.unsolved()).count();
+    if failed > 0 {
+        eprintln!("{} of {} runs failed", failed, results.len());
+        return ExitCode::from(EXIT_FAILURE);
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn reproduce(cli: &Cli, args: &ReproduceArgs) -> ExitCode {
+    let manifest = match manifest::load(&args.manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Invalid manifest {}: {}", args.manifest.display(), e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    if cli.dry_run {
+        let map_texts: Vec<String> = manifest.maps().cloned().collect();
+        let map_hash = parse_maps(&map_texts)
+            .map(|maps| format!("{:016x}", ensemble::maps_fingerprint(maps.iter().map(|(_, game_area)| game_area))));
+        if map_hash.as_ref().is_ok_and(|map_hash| *map_hash != manifest.map_hash) {
+            eprintln!("Invalid manifest {}: the maps do not match their hash {}", args.manifest.display(), manifest.map_hash);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+        if let Err(e) = check_run_args(&manifest.args, Some(&map_texts)) {
+            eprintln!("Invalid manifest {}: {}", args.manifest.display(), e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+        println!("{:#?}", args);
+        println!("Program: {}", manifest.program);
+        println!("Run: {}", manifest.args.join(" "));
+        println!("Seed {}, map hash {}, instruction set {}, {} generations", manifest.seed, manifest.map_hash,
+                 manifest.instruction_set, manifest.generations);
+        println!("Configuration is valid");
+        return ExitCode::SUCCESS;
+    }
+    if manifest.program != manifest::program() {
+        eprintln!("Warning: the manifest was written by {}, this is {}", manifest.program, manifest::program());
+    }
+    println!("Running again: {} {}", manifest.program, manifest.args.join(" "));
+    println!("Seed {}, map hash {}, instruction set {}", manifest.seed, manifest.map_hash, manifest.instruction_set);
+    let differences = match manifest::reproduce(&manifest) {
+        Ok(differences) => differences,
+        Err(e) => {
+            eprintln!("Failed to run the search: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    if !differences.is_empty() {
+        println!("Not reproduced: {}", differences.join(", "));
+        return ExitCode::from(EXIT_FAILURE);
+    }
+    println!("Reproduced: best fitness {} after {} generations, {}",
+             manifest.best_fitness.map_or_else(|| String::from("none"), |fitness| fitness.to_string()), manifest.generations,
+             if manifest.solved { "solved" } else { "unsolved" });
+    return ExitCode::SUCCESS;
+}
+
+fn worker(cli: &Cli, args: &WorkerArgs) -> ExitCode {
+    if cli.dry_run {
+        if let Err(e) = std::net::ToSocketAddrs::to_socket_addrs(args.listen.as_str()) {
+            eprintln!("Invalid address {}: {}", args.listen, e);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+        println!("{:#?}", args);
+        println!("Configuration is valid");
+        return ExitCode::SUCCESS;
+    }
+    if let Err(e) = remote::serve(&args.listen, worker_environment) {
+        eprintln!("Failed to listen on {}: {}", args.listen, e);
+        return ExitCode::from(EXIT_FAILURE);
+    }
+    return ExitCode::SUCCESS;
+}
+
+// The environment of the coordinator's run, built the same way from its command line.
+// Fitness scripts given by a path are read from the file system of the worker.
+fn worker_environment(setup: &remote::Setup) -> Result<GridEnvironment<'static>, String> {
+    let cli = Cli::try_parse_from(&setup.args).map_err(|e| e.to_string())?;
+    let Command::Run(args) = &cli.command else {
+        return Err(String::from("only runs can be evaluated on workers"));
+    };
+    let maps = parse_maps(std::iter::once(&setup.map).chain(&setup.extra_maps)).map_err(|e| format!("invalid map {}", e))?;
+    let fitness_function = fitness_function(&cli, &maps).ok_or("invalid fitness function")?;
+    let starts: &[(usize, usize)] = cli.starts.as_ref().map_or(&[], |starts| &starts.0);
+    let cases = ensemble::map_cases(&maps, starts).map_err(|e| format!("invalid maps or start positions: {}", e))?;
+    let vm_config = vm_config(&cli).record_trajectory(fitness_function.needs_trajectory()).seed(setup.vm_seed).build();
+    return Ok(GridEnvironment::owned(Vm::new(vm_config), fitness_function, cases, args.aggregate.clone()));
+}
+
+// Shortest tour of the map and the fitness of a machine walking it, printed for reference
+fn optimum(game_area: &GameArea, fitness_function: &dyn FitnessFunction, fitness_context: &FitnessContext) -> Option<(solver::Tour, f64)> {
+    let tour = solver::shortest_tour(game_area)?;
+    let fitness = fitness_function.evaluate(&tour.ideal_result(), fitness_context);
+    return Some((tour, fitness));
+}
+
+fn print_optimum(out: &mut dyn Write, optimum: Option<&(solver::Tour, f64)>, step_alphabet: StepAlphabet) {
+    if let Some((tour, fitness)) = optimum {
+        writeln!(out, "Optimal tour ({}): Fitness: {}, Steps: {} ({})",
+                 if tour.exact { "exact" } else { "approximate" }, fitness, step_alphabet.format(&tour.steps), tour.steps.len()).ok();
+    }
+}
+
+fn relative_to_optimum(optimum: Option<&(solver::Tour, f64)>, fitness: f64, steps: usize) -> String {
+    return match optimum {
+        Some((tour, optimal_fitness)) if *optimal_fitness > 0.0 && !tour.steps.is_empty() => {
+            format!(", Of optimum: fitness {:.3}, steps {:.3}", fitness / optimal_fitness, steps as f64 / tour.steps.len() as f64)
+        }
+        _ => String::new(),
+    };
+}
+
+// Printed below a solution
+fn solution_details(case_names: &[String], resampling: Resampling, genome_format: &[GenomeFormat], instruction_set: InstructionSet,
+                    step_alphabet: StepAlphabet, chromosome: &Chromosome) -> String {
+    let mut details = String::new();
+    if case_names.len() >= 2 {
+        let breakdown: Vec<String> = case_names.iter().zip(&chromosome.case_fitness)
+            .map(|(name, fitness)| format!("{}: {:.4}", name, fitness))
+            .collect();
+        details += &format!("Cases: {}\n", breakdown.join(", "));
     }
+    if resampling.enabled() {
+        details += &format!("Samples: {}, Standard error: {:.4}\n", chromosome.fitness_stats.count, chromosome.fitness_stats.standard_error());
+    }
+    details.push_str(&encoding::format_genome(chromosome, genome_format, instruction_set, step_alphabet));
+    return details;
+}
+
+// Genome files of another instruction set are refused, those of another map only warned about
+fn load_genome(argument: &str, vm_config: &VmConfig, game_area: &GameArea) -> Option<(Program, Option<GenomeHeader>)> {
+    let (program, header) = match read_genome(argument) {
+        Ok(read) => read,
+        Err(e) => {
+            eprintln!("Invalid genome: {}", e);
+            return None;
+        }
+    };
+    if let Some(header) = &header {
+        if header.instruction_set != vm_config.instruction_set {
+            eprintln!("Invalid genome: it was saved for the {} instruction set", format!("{:?}", header.instruction_set).to_ascii_lowercase());
+            return None;
+        }
+        if header.map_fingerprint.is_some_and(|fingerprint| fingerprint != map::fingerprint(game_area)) {
+            eprintln!("The genome was saved on a different map");
+        }
+    }
+    if let Err(e) = program.verify(vm_config.instruction_set, vm_config.memory_size) {
+        eprintln!("Invalid genome: {}", e);
+        return None;
+    }
+    return Some((program, header));
 }
 
+// The printed form of a genome or a file with it: a genome file, a JSON run summary or a checkpoint.
+// Only genome files have a header.
+fn read_genome(argument: &str) -> Result<(Program, Option<GenomeHeader>), String> {
+    let path = std::path::Path::new(argument);
+    if !path.is_file() {
+        return argument.parse().map(|program| (program, None)).map_err(|e: error::Error| e.to_string());
+    }
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if genome_file::is_genome_file(&bytes) {
+        return genome_file::decode(&bytes).map(|(header, program)| (program, Some(header))).map_err(|e| e.to_string());
+    }
+    let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+    if let Ok(serde_json::Value::Object(document)) = serde_json::from_str::<serde_json::Value>(&text) {
+        let best = document.get("best")
+            .or_else(|| document.get("snapshot").and_then(|snapshot| snapshot.get("best")))
+            .ok_or("the file has no best solution")?;
+        let genes = best.get("genome").or_else(|| best.get("genes")).ok_or("the best solution has no genome")?;
+        return serde_json::from_value(genes.clone()).map(|genes| (Program::new(genes), None)).map_err(|e| e.to_string());
+    }
+    return text.parse().map(|program| (program, None)).map_err(|e: error::Error| e.to_string());
+}
+
+// Seed of the random stream used by the RAND instruction, identical for every evaluation
+fn vm_seed(rng: &mut Pcg64) -> u64 {
+    return rng.gen();
+}
+
+fn vm_config(cli: &Cli) -> VmConfigBuilder {
+    let mut vm_config = VmConfig::builder()
+        .instruction_set(cli.instruction_set)
+        .memory_mapped_inputs(cli.inputs)
+        .iteration_limit(cli.iterations)
+        .memory_size(cli.memory_size)
+        .collision_policy(cli.collision);
+    // Moves are unlimited unless requested, the iteration limit still applies
+    if let Some(max_moves) = cli.max_moves {
+        vm_config = vm_config.max_moves(max_moves);
+    }
+    return vm_config;
+}
+
+#[cfg(feature = "script")]
+fn fitness_script(script: &str) -> Option<Box<dyn FitnessFunction>> {
+    let source = match script.strip_prefix('@') {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Failed to read fitness script: {}", e);
+                return None;
+            }
+        },
+        None => String::from(script),
+    };
+    return match fitness::ScriptFitness::new(&source) {
+        Ok(fitness_function) => Some(Box::new(fitness_function)),
+        Err(e) => {
+            eprintln!("Invalid fitness script: {}", e);
+            None
+        }
+    };
+}
+
+#[cfg(not(feature = "script"))]
+fn fitness_script(_script: &str) -> Option<Box<dyn FitnessFunction>> {
+    eprintln!("Fitness scripts require the \"script\" feature!");
+    return None;
+}
+
+// Events of the engine to the logs, and to the console all but restarts, statistics only when they are due
+fn forward_events(events: &Receiver<Event>, mut console: Option<&mut ConsoleReporter>, reporters: &mut [Box<dyn Reporter>],
+                  progress: &progress::Progress, stats_due: bool) {
+    for event in events.try_iter() {
+        let shown = match &event {
+            Event::GenerationCompleted(_) => stats_due,
+            Event::Restarted { .. } => false,
+            _ => true,
+        };
+        if let Some(console) = console.as_mut().filter(|_| shown) {
+            progress.suspend(|| console.report(&event)).ok();
+        }
+        report(reporters, &event);
+    }
+}
+
+// Write failures are reported without stopping the search
+fn report(reporters: &mut [Box<dyn Reporter>], event: &Event) {
+    for reporter in reporters {
+        if let Err(e) = reporter.report(event) {
+            eprintln!("Failed to write the log: {}", e);
+        }
+    }
+}
+
+fn human_output(summary_on_stdout: bool) -> Box<dyn Write + Send> {
+    if summary_on_stdout {
+        return Box::new(std::io::stderr());
+    }
+    return Box::new(std::io::stdout());
+}
+
+// On the standard error, which stays free of the results
 fn ask_user(text: &str) -> bool {
-    print!("{}", text);
-    std::io::stdout().flush().unwrap();
+    eprint!("{}", text);
+    std::io::stderr().flush().unwrap();
     let mut ans = String::new();
     std::io::stdin().read_line(&mut ans).ok();
     return ans.trim().eq_ignore_ascii_case("y");
 }
-
-fn parse_error_handler<T, E>(_e: E) -> T {
-    eprintln!("Failed to parse number!");
-    exit(-1);
-}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::batch::{self, Job};
+
+// Everything needed to run a search again and check that it ends the same way, written by `run --manifest`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    // Name and version of the program which ran the search
+    pub program: String,
+    // Options of the run with the configuration file and the environment resolved, without the seed, the map and
+    // the options which only change what is shown or written
+    pub args: Vec<String>,
+    pub seed: u64,
+    pub instruction_set: String,
+    // Text of the map and of the maps after it, see ensemble::maps_fingerprint for their hash
+    pub map: String,
+    #[serde(default)]
+    pub extra_maps: Vec<String>,
+    pub map_hash: String,
+    // See GaConfig::fingerprint
+    pub config_fingerprint: String,
+    pub generations: u32,
+    pub best_fitness: Option<f64>,
+    pub solved: bool,
+}
+
+// Long names of the options left out of the arguments of a manifest
+pub const LEFT_OUT: &[&str] = &[
+    "seed", "map", "config", "manifest", "dry-run", "non-interactive", "output", "output-file", "quiet", "verbose",
+    "log-format", "log-csv", "log-jsonl", "autosave", "color", "genome-format", "save-genome", "report-every",
+    "throughput", "plot", "svg", "gif", "tui", "watch", "control-port", "serve", "live", "checkpoint",
+    "checkpoint-every", "save-population", "workers", "leaderboard",
+    "tensorboard",
+];
+
+impl Manifest {
+    pub fn maps(&self) -> impl Iterator<Item = &String> {
+        return std::iter::once(&self.map).chain(&self.extra_maps);
+    }
+}
+
+pub fn program() -> String {
+    return String::from(concat!("treasure-search ", env!("CARGO_PKG_VERSION")));
+}
+
+pub fn save(path: &Path, manifest: &Manifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    return std::fs::write(path, json + "\n").map_err(|e| e.to_string());
+}
+
+pub fn load(path: &Path) -> Result<Manifest, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    return serde_json::from_str(&json).map_err(|e| e.to_string());
+}
+
+// Runs the search of the manifest in another process, returns what ended differently
+pub fn reproduce(manifest: &Manifest) -> Result<Vec<String>, String> {
+    let executable = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut args: Vec<String> = manifest.args.clone();
+    args.extend([String::from("--seed"), manifest.seed.to_string()]);
+    let mut map_paths: Vec<std::path::PathBuf> = Vec::new();
+    for (i, map) in manifest.maps().enumerate() {
+        let map_path = std::env::temp_dir().join(format!("treasure-search-manifest-{}-{}.txt", std::process::id(), i));
+        let written = std::fs::write(&map_path, map);
+        map_paths.push(map_path);
+        if let Err(e) = written {
+            map_paths.iter().for_each(|path| { std::fs::remove_file(path).ok(); });
+            return Err(e.to_string());
+        }
+        args.extend([String::from("--map"), map_paths[i].display().to_string()]);
+    }
+    let job = Job { name: String::from("reproduce"), config: String::from("reproduce"), args };
+    let result = batch::run_job(&executable, &job, 0);
+    for map_path in &map_paths {
+        std::fs::remove_file(map_path).ok();
+    }
+    let (solved, finished) = (result.solved(), result.solved() || result.unsolved());
+    let Some(summary) = result.summary.filter(|_| finished) else {
+        return Err(result.error.unwrap_or_else(|| String::from("the run failed without a summary")));
+    };
+
+    let mut differences: Vec<String> = Vec::new();
+    let map_hash = summary["map_hash"].as_str().unwrap_or("");
+    if map_hash != manifest.map_hash {
+        differences.push(format!("map hash {} instead of {}", map_hash, manifest.map_hash));
+    }
+    let generations = summary["generations"].as_u64();
+    if generations != Some(u64::from(manifest.generations)) {
+        differences.push(format!("{} generations instead of {}", generations.unwrap_or(0), manifest.generations));
+    }
+    let best_fitness = summary["best"]["fitness"].as_f64();
+    if best_fitness != manifest.best_fitness {
+        differences.push(format!("best fitness {} instead of {}", describe(best_fitness), describe(manifest.best_fitness)));
+    }
+    if solved != manifest.solved {
+        differences.push(String::from(if manifest.solved { "not solved" } else { "solved" }));
+    }
+    return Ok(differences);
+}
+
+fn describe(fitness: Option<f64>) -> String {
+    return fitness.map_or_else(|| String::from("none"), |fitness| fitness.to_string());
+}
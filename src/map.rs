@@ -0,0 +1,236 @@
+use ::core::fmt;
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+
+use crate::core::{self, Position, AREA_TILE_NOTHING, AREA_TILE_PLAYER, AREA_TILE_TREASURE};
+use crate::error::Error;
+
+// Text form of a map: one line per row, '.' is an empty tile, 'P' the player and 'T' a treasure
+pub const GLYPH_NOTHING: char = '.';
+pub const GLYPH_PLAYER: char = 'P';
+pub const GLYPH_TREASURE: char = 'T';
+
+// Tiles of a rectangular map in one slice row by row, (x, y) with y growing downwards
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameArea {
+    width: usize,
+    height: usize,
+    tiles: Box<[u8]>,
+    // Indices of the treasure tiles in ascending order, kept by set()
+    treasures: Vec<usize>,
+}
+
+impl GameArea {
+    // Every tile empty
+    pub fn new(width: usize, height: usize) -> GameArea {
+        return GameArea { width, height, tiles: vec![AREA_TILE_NOTHING; width * height].into_boxed_slice(), treasures: Vec::new() };
+    }
+
+    // Rows must have the same length
+    pub fn from_rows(rows: &[Vec<u8>]) -> Result<GameArea, Error> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(Error::Parse(format!("row {} has {} tiles, expected {}", y, row.len(), width)));
+        }
+        let tiles: Box<[u8]> = rows.concat().into_boxed_slice();
+        let treasures: Vec<usize> = (0..tiles.len()).filter(|&i| tiles[i] == AREA_TILE_TREASURE).collect();
+        return Ok(GameArea { width, height: rows.len(), tiles, treasures });
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        return position.x >= 0 && position.y >= 0 && (position.x as usize) < self.width && (position.y as usize) < self.height;
+    }
+
+    // Panics outside of the map like indexing
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        assert!(x < self.width, "x {} is outside of a map {} tiles wide", x, self.width);
+        return self.tiles[y * self.width + x];
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, tile: u8) {
+        assert!(x < self.width, "x {} is outside of a map {} tiles wide", x, self.width);
+        let index = y * self.width + x;
+        self.tiles[index] = tile;
+        match (self.treasures.binary_search(&index), tile == AREA_TILE_TREASURE) {
+            (Err(position), true) => self.treasures.insert(position, index),
+            (Ok(position), false) => {
+                self.treasures.remove(position);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        return self.tiles.chunks(self.width.max(1));
+    }
+
+    // Every tile with its position, row by row
+    pub fn tiles(&self) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        return self.tiles.iter().enumerate().map(|(i, &tile)| ((i % self.width, i / self.width), tile));
+    }
+
+    // Row by row, the n-th one has the treasure index n
+    pub fn treasures(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        return self.treasures.iter().map(|&i| (i % self.width, i / self.width));
+    }
+
+    pub fn treasure_count(&self) -> u32 {
+        return self.treasures.len() as u32;
+    }
+
+    // Position of the treasure in treasures(), None for other tiles
+    pub fn treasure_index(&self, x: usize, y: usize) -> Option<usize> {
+        assert!(x < self.width, "x {} is outside of a map {} tiles wide", x, self.width);
+        return self.treasures.binary_search(&(y * self.width + x)).ok();
+    }
+
+    // Start of the player, the top left corner on a map without one
+    pub fn player(&self) -> Position {
+        return match self.tiles().find(|&(_, tile)| tile == AREA_TILE_PLAYER) {
+            Some(((x, y), _)) => Position::new(x as isize, y as isize),
+            None => Position::default(),
+        };
+    }
+}
+
+// The text form
+impl fmt::Display for GameArea {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for &tile in row {
+                write!(f, "{}", glyph(tile))?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn load(path: &std::path::Path) -> Result<GameArea, Error> {
+    let text = std::fs::read_to_string(path)?;
+    return parse(&text);
+}
+
+// Rows must have the same length, there must be one player and at least one treasure
+pub fn parse(text: &str) -> Result<GameArea, Error> {
+    let mut rows: Vec<Vec<u8>> = Vec::new();
+    for (y, line) in text.lines().map(str::trim).filter(|line| !line.is_empty()).enumerate() {
+        let row: Vec<u8> = line.chars()
+            .filter(|glyph| !glyph.is_whitespace())
+            .enumerate()
+            .map(|(x, glyph)| match glyph.to_ascii_uppercase() {
+                GLYPH_NOTHING => Ok(AREA_TILE_NOTHING),
+                GLYPH_PLAYER => Ok(AREA_TILE_PLAYER),
+                GLYPH_TREASURE => Ok(AREA_TILE_TREASURE),
+                _ => Err(Error::Parse(format!("unknown tile '{}' at {},{}", glyph, x, y))),
+            })
+            .collect::<Result<_, _>>()?;
+        rows.push(row);
+    }
+    let game_area = GameArea::from_rows(&rows)?;
+    if game_area.width() == 0 || game_area.height() == 0 {
+        return Err(Error::InvalidMap(String::from("the map is empty")));
+    }
+    let players = game_area.tiles().filter(|&(_, tile)| tile == AREA_TILE_PLAYER).count();
+    if players != 1 {
+        return Err(Error::InvalidMap(format!("the map has {} players, expected 1", players)));
+    }
+    if game_area.treasure_count() == 0 {
+        return Err(Error::InvalidMap(String::from("the map has no treasures")));
+    }
+    return Ok(game_area);
+}
+
+// Trail of the artificial ant benchmarks (Santa Fe, Los Altos Hills) as published with GP systems: '#' or 'X' is food,
+// every other glyph ('.' for a gap in the trail, ' ') an empty tile. The ant starts at 'S', at the top left corner
+// without one, where a pellet is dropped. ECJ files start with a line of the width and the height, short rows and
+// missing rows at the end are empty; without that line the longest row sets the width.
+// The ant of the literature turns and moves ahead on a toroidal grid, here the player moves in any of four directions,
+// so results compare by food eaten (see the food fitness) with --collision wrap and a move budget.
+pub fn parse_trail(text: &str) -> Result<GameArea, Error> {
+    let mut lines: Vec<&str> = text.lines().map(|line| line.trim_end_matches('\r')).collect();
+    let size: Option<(usize, usize)> = lines.first().and_then(|line| {
+        let mut numbers = line.split_whitespace().map(|number| number.parse::<usize>().ok());
+        return match (numbers.next(), numbers.next(), numbers.next()) {
+            (Some(Some(width)), Some(Some(height)), None) => Some((width, height)),
+            _ => None,
+        };
+    });
+    if size.is_some() {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let (width, height) = size.unwrap_or_else(|| (lines.iter().map(|line| line.chars().count()).max().unwrap_or(0), lines.len()));
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidMap(String::from("the trail is empty")));
+    }
+    if lines.len() > height {
+        return Err(Error::Parse(format!("the trail has {} rows, expected {}", lines.len(), height)));
+    }
+    let mut game_area = GameArea::new(width, height);
+    let mut start: Option<(usize, usize)> = None;
+    for (y, line) in lines.iter().enumerate() {
+        if line.chars().count() > width {
+            return Err(Error::Parse(format!("row {} has {} tiles, expected {}", y, line.chars().count(), width)));
+        }
+        for (x, glyph) in line.chars().enumerate() {
+            match glyph {
+                '#' | 'X' | 'x' => game_area.set(x, y, AREA_TILE_TREASURE),
+                'S' | 's' if start.is_some() => return Err(Error::InvalidMap(String::from("the trail has more than one start"))),
+                'S' | 's' => start = Some((x, y)),
+                _ => {}
+            }
+        }
+    }
+    let (x, y) = start.unwrap_or((0, 0));
+    game_area.set(x, y, AREA_TILE_PLAYER);
+    if game_area.treasure_count() == 0 {
+        return Err(Error::InvalidMap(String::from("the trail has no food")));
+    }
+    return Ok(game_area);
+}
+
+// FNV-1a hash of the text form, identical for identical maps on every platform
+pub fn fingerprint(game_area: &GameArea) -> u64 {
+    let text = game_area.rows().flat_map(|row| row.iter().map(|&tile| glyph(tile) as u8).chain([b'\n']));
+    return core::fnv1a_bytes(text);
+}
+
+fn glyph(tile: u8) -> char {
+    return match tile {
+        AREA_TILE_PLAYER => GLYPH_PLAYER,
+        AREA_TILE_TREASURE => GLYPH_TREASURE,
+        _ => GLYPH_NOTHING,
+    };
+}
+
+// Player and treasures on distinct random tiles
+pub fn generate(width: usize, height: usize, treasures: usize, rng: &mut Pcg64) -> Result<GameArea, Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidConfig(String::from("the map must have at least one tile")));
+    }
+    if treasures == 0 || treasures >= width * height {
+        return Err(Error::InvalidConfig(format!("a {}x{} map fits between 1 and {} treasures", width, height, width * height - 1)));
+    }
+    let mut tiles: Vec<usize> = (0..width * height).collect();
+    tiles.shuffle(rng);
+    let mut game_area = GameArea::new(width, height);
+    game_area.set(tiles[0] % width, tiles[0] / width, AREA_TILE_PLAYER);
+    for &tile in &tiles[1..=treasures] {
+        game_area.set(tile % width, tile / width, AREA_TILE_TREASURE);
+    }
+    return Ok(game_area);
+}
@@ -0,0 +1,97 @@
+use ::core::fmt;
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+use crate::core::Genes;
+
+// Changes the genes of a child after crossover
+pub trait MutationOperator<G = Genes>: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Mask of the changed bits of every byte, empty for other genomes. The probability is the mutation rate of the search.
+    fn mutate(&self, genes: &mut G, probability: f64, rng: &mut Pcg64) -> Vec<u8>;
+}
+
+impl<G> fmt::Debug for dyn MutationOperator<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.name());
+    }
+}
+
+// Every bit is flipped with the given probability
+#[derive(Clone, Copy, Debug)]
+pub struct BitFlipMutation;
+
+impl MutationOperator for BitFlipMutation {
+    fn name(&self) -> &'static str {
+        return "bit-flip";
+    }
+
+    fn mutate(&self, genes: &mut Genes, probability: f64, rng: &mut Pcg64) -> Vec<u8> {
+        let mut mutated: Vec<u8> = vec![0; genes.len()];
+        let bits = genes.len() * 8;
+        if probability >= 1.0 {
+            mutated.fill(u8::MAX);
+        } else if probability > 0.0 {
+            // The gaps between flipped bits are geometric, drawn instead of a coin for every bit
+            let log_keep = libm::log1p(-probability);
+            let mut bit: usize = 0;
+            loop {
+                let gap = libm::floor(libm::log1p(-rng.gen::<f64>()) / log_keep);
+                if gap >= (bits - bit) as f64 {
+                    break;
+                }
+                bit += gap as usize;
+                mutated[bit / 8] |= 0x80 >> (bit % 8);
+                bit += 1;
+            }
+        }
+        for (gene, &flipped) in genes.iter_mut().zip(&mutated) {
+            *gene ^= flipped;
+        }
+        return mutated;
+    }
+}
+
+// Every byte is replaced by a random one with the given probability
+#[derive(Clone, Copy, Debug)]
+pub struct ByteResetMutation;
+
+impl MutationOperator for ByteResetMutation {
+    fn name(&self) -> &'static str {
+        return "byte-reset";
+    }
+
+    fn mutate(&self, genes: &mut Genes, probability: f64, rng: &mut Pcg64) -> Vec<u8> {
+        let mut mutated: Vec<u8> = Vec::with_capacity(genes.len());
+        for gene in genes.iter_mut() {
+            let previous = *gene;
+            if rng.gen_bool(probability) {
+                *gene = rng.gen();
+            }
+            mutated.push(previous ^ *gene);
+        }
+        return mutated;
+    }
+}
+
+// Operators applied one after another, a bit changed back by a later one counts as unchanged
+#[derive(Clone, Debug)]
+pub struct ChainedMutation(pub Vec<Arc<dyn MutationOperator>>);
+
+impl MutationOperator for ChainedMutation {
+    fn name(&self) -> &'static str {
+        return "chained";
+    }
+
+    fn mutate(&self, genes: &mut Genes, probability: f64, rng: &mut Pcg64) -> Vec<u8> {
+        let mut mutated: Vec<u8> = vec![0; genes.len()];
+        for operator in &self.0 {
+            for (total, changed) in mutated.iter_mut().zip(operator.mutate(genes, probability, rng)) {
+                *total ^= changed;
+            }
+        }
+        return mutated;
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+// Critical value of a two-sided 95% confidence interval
+pub const CONFIDENCE_Z: f64 = 1.96;
+
+// Running mean and variance of repeated fitness samples (Welford)
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct SampleStats {
+    pub count: u32,
+    pub mean: f64,
+    m2: f64,
+}
+
+impl SampleStats {
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn standard_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        let variance = self.m2 / (self.count - 1) as f64;
+        return libm::sqrt(variance / self.count as f64);
+    }
+
+    // Whether the confidence intervals of the two means overlap, never true for unsampled stats
+    pub fn overlaps(&self, other: &SampleStats) -> bool {
+        if self.count == 0 || other.count == 0 {
+            return false;
+        }
+        let margin = CONFIDENCE_Z * (self.standard_error() + other.standard_error());
+        return (self.mean - other.mean).abs() < margin;
+    }
+}
+
+// How many times every chromosome is evaluated
+#[derive(Clone, Copy, Debug)]
+pub struct Resampling {
+    pub samples: u32,
+    // Upper bound for near-ties which get extra samples
+    pub max_samples: u32,
+}
+
+impl Resampling {
+    pub fn new(samples: u32, max_samples: Option<u32>) -> Resampling {
+        let samples = samples.max(1);
+        return Resampling {
+            samples,
+            max_samples: max_samples.unwrap_or(samples * 4).max(samples),
+        };
+    }
+
+    pub fn enabled(&self) -> bool {
+        return self.samples > 1;
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+// Best and mean fitness of every generation
+#[cfg(feature = "plot")]
+pub fn write_fitness_curve(path: &Path, history: &[(f64, f64)]) -> Result<(), String> {
+    use plotters::prelude::*;
+
+    let root = BitMapBackend::new(path, (960, 540)).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| e.to_string())?;
+    let generations = history.len().max(2) as u32;
+    let low = history.iter().map(|&(best, mean)| best.min(mean)).fold(0.0, f64::min);
+    let high = history.iter().map(|&(best, _)| best).fold(f64::NEG_INFINITY, f64::max).max(low + 1e-3);
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Fitness", ("sans-serif", 24))
+        .margin(12)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(1..generations, low..high * 1.05)
+        .map_err(|e| e.to_string())?;
+    chart.configure_mesh().x_desc("Generation").y_desc("Fitness").draw().map_err(|e| e.to_string())?;
+    chart.draw_series(LineSeries::new(history.iter().enumerate().map(|(i, &(best, _))| (i as u32 + 1, best)), &BLUE))
+        .map_err(|e| e.to_string())?
+        .label("best")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart.draw_series(LineSeries::new(history.iter().enumerate().map(|(i, &(_, mean))| (i as u32 + 1, mean)), &RED))
+        .map_err(|e| e.to_string())?
+        .label("mean")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart.configure_series_labels().border_style(BLACK).background_style(WHITE).draw().map_err(|e| e.to_string())?;
+    return root.present().map_err(|e| e.to_string());
+}
+
+// Without the feature there is nothing to draw the chart with
+#[cfg(not(feature = "plot"))]
+pub fn write_fitness_curve(_path: &Path, _history: &[(f64, f64)]) -> Result<(), String> {
+    return Err(String::from("the chart requires the \"plot\" feature"));
+}
@@ -0,0 +1,134 @@
+use alloc::vec::Vec;
+
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+use crate::core::{Chromosome, Comparison, Genes, Genome};
+use crate::scaling::FitnessScaling;
+use crate::selection::{AliasTable, SelectionStrategy};
+use crate::stats::{self, GenerationStats};
+
+// Chromosomes of one generation, sorted from the best once evaluated
+#[derive(Clone, Debug)]
+pub struct Population<G = Genes> {
+    chromosomes: Vec<Chromosome<G>>,
+    // Sum of the selection fitness and its running sums, negative values counting as zero, set by scale()
+    total_fitness: f64,
+    cumulative_fitness: Vec<f64>,
+    // Built by build_alias_table() for the strategies sampling from it
+    alias_table: AliasTable,
+}
+
+impl<G> Population<G> {
+    pub fn new(chromosomes: Vec<Chromosome<G>>) -> Population<G> {
+        return Population { chromosomes, total_fitness: 0.0, cumulative_fitness: Vec::new(), alias_table: AliasTable::default() };
+    }
+
+    // Not evaluated yet
+    pub fn from_genomes(genomes: impl IntoIterator<Item = G>) -> Population<G> {
+        return Population::new(genomes.into_iter().map(Chromosome::with_genes).collect());
+    }
+
+    // Puts the next generation in place of the chromosomes, which are returned, keeping the other buffers
+    pub fn replace(&mut self, chromosomes: Vec<Chromosome<G>>) -> Vec<Chromosome<G>> {
+        self.total_fitness = 0.0;
+        self.cumulative_fitness.clear();
+        return ::core::mem::replace(&mut self.chromosomes, chromosomes);
+    }
+
+    // Keeps the first chromosomes, e.g. the ones an evaluation budget leaves room for
+    pub fn truncate(&mut self, len: usize) {
+        self.chromosomes.truncate(len);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.chromosomes.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.chromosomes.is_empty();
+    }
+
+    pub fn chromosomes(&self) -> &[Chromosome<G>] {
+        return &self.chromosomes;
+    }
+
+    pub fn chromosomes_mut(&mut self) -> &mut [Chromosome<G>] {
+        return &mut self.chromosomes;
+    }
+
+    pub fn into_chromosomes(self) -> Vec<Chromosome<G>> {
+        return self.chromosomes;
+    }
+
+    // The first chromosome, the best one once sorted
+    pub fn best(&self) -> Option<&Chromosome<G>> {
+        return self.chromosomes.first();
+    }
+
+    pub fn sort_by_fitness(&mut self, comparison: Comparison) {
+        self.chromosomes.sort_by(|a, b| comparison.compare(b, a));
+    }
+
+    // Only the best count chromosomes are sorted to the front, the order of the others is unspecified
+    pub fn sort_best(&mut self, count: usize, comparison: Comparison) {
+        let count = count.min(self.chromosomes.len());
+        if count == 0 {
+            return;
+        }
+        if count < self.chromosomes.len() {
+            self.chromosomes.select_nth_unstable_by(count - 1, |a, b| comparison.compare(b, a));
+        }
+        self.chromosomes[..count].sort_by(|a, b| comparison.compare(b, a));
+    }
+
+    // Sets the selection fitness of every chromosome
+    pub fn scale(&mut self, scaling: FitnessScaling, comparison: Comparison) {
+        self.total_fitness = scaling.apply(&mut self.chromosomes, comparison);
+        let mut sum: f64 = 0.0;
+        self.cumulative_fitness.clear();
+        self.cumulative_fitness.extend(self.chromosomes.iter().map(|c| {
+            sum += c.selection_fitness.max(0.0);
+            sum
+        }));
+    }
+
+    // Sum of the selection fitness of the last scale()
+    pub fn total_fitness(&self) -> f64 {
+        return self.total_fitness;
+    }
+
+    // Running sums of the selection fitness of the last scale() in the order of the chromosomes
+    pub fn cumulative_fitness(&self) -> &[f64] {
+        return &self.cumulative_fitness;
+    }
+
+    // From the selection fitness of the last scale()
+    pub fn build_alias_table(&mut self) {
+        self.alias_table.build(self.chromosomes.iter().map(|c| c.selection_fitness));
+    }
+
+    pub fn alias_table(&self) -> &AliasTable {
+        return &self.alias_table;
+    }
+
+    // Index of a parent chosen by the strategy, only valid after scale()
+    pub fn select(&self, strategy: &dyn SelectionStrategy<G>, comparison: Comparison, rng: &mut Pcg64) -> usize {
+        return strategy.select_from(self, comparison, rng);
+    }
+
+    // Every chromosome is equally likely
+    pub fn sample(&self, rng: &mut Pcg64) -> &Chromosome<G> {
+        return &self.chromosomes[rng.gen_range(0..self.chromosomes.len())];
+    }
+}
+
+impl<G: Genome> Population<G> {
+    pub fn diversity(&self) -> f64 {
+        return stats::diversity(&self.chromosomes);
+    }
+
+    pub fn stats(&self, generation: u32) -> GenerationStats {
+        return GenerationStats::of(generation, &self.chromosomes);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use crate::core::INSTR;
+use crate::map::GameArea;
+use crate::instruction::Instruction;
+use crate::vm::{InstructionSet, Vm};
+
+pub struct Profile {
+    pub runs: u32,
+    pub total_iterations: u64,
+    pub total_time: Duration,
+    pub instruction_iterations: Vec<u64>,
+    pub instruction_time: Vec<Duration>,
+    pub opcode_iterations: BTreeMap<&'static str, u64>,
+    pub opcode_time: BTreeMap<&'static str, Duration>,
+    // Backward control transfers (from, to) and how many times they were taken
+    pub loops: HashMap<(usize, usize), u64>,
+}
+
+pub fn profile_genome(vm: &Vm, genes: &[INSTR], game_area: &GameArea, runs: u32) -> Profile {
+    let instruction_set = vm.config().instruction_set;
+    let mut profile = Profile {
+        runs,
+        total_iterations: 0,
+        total_time: Duration::ZERO,
+        instruction_iterations: vec![0; genes.len()],
+        instruction_time: vec![Duration::ZERO; genes.len()],
+        opcode_iterations: BTreeMap::new(),
+        opcode_time: BTreeMap::new(),
+        loops: HashMap::new(),
+    };
+
+    for _ in 0..runs {
+        // Time between two tracer calls is attributed to the earlier instruction
+        let mut last: Option<(usize, &'static str, Instant)> = Option::None;
+        let start = Instant::now();
+        let result = vm.run_traced(genes, game_area, |state| {
+            let now = Instant::now();
+            let index = state.instruction_index;
+            let instruction = state.memory[index];
+            if let Some((last_index, last_opcode, last_time)) = last {
+                profile.instruction_time[last_index] += now - last_time;
+                *profile.opcode_time.entry(last_opcode).or_default() += now - last_time;
+                if index <= last_index {
+                    *profile.loops.entry((last_index, index)).or_default() += 1;
+                }
+            }
+            let opcode = Instruction::from_byte(instruction, instruction_set).name();
+            profile.instruction_iterations[index] += 1;
+            *profile.opcode_iterations.entry(opcode).or_default() += 1;
+            last = Some((index, opcode, Instant::now()));
+        });
+        let end = Instant::now();
+        if let Some((last_index, last_opcode, last_time)) = last {
+            profile.instruction_time[last_index] += end - last_time;
+            *profile.opcode_time.entry(last_opcode).or_default() += end - last_time;
+        }
+        profile.total_time += end - start;
+        profile.total_iterations += u64::from(result.iterations);
+    }
+    return profile;
+}
+
+pub fn print_report(profile: &Profile, genes: &[INSTR], instruction_set: InstructionSet) {
+    let total_iterations = profile.total_iterations.max(1) as f64;
+    let total_time = profile.total_time.as_secs_f64().max(f64::MIN_POSITIVE);
+    let instruction_time_sum: f64 = profile.instruction_time.iter().map(|time| time.as_secs_f64()).sum::<f64>()
+        .max(f64::MIN_POSITIVE);
+
+    println!("Runs: {}, Iterations per run: {}, Time per run: {:.3?}",
+             profile.runs, profile.total_iterations / u64::from(profile.runs.max(1)),
+             profile.total_time / profile.runs.max(1));
+    println!("Total time: {:.3?}, Iterations/s: {:.0}", profile.total_time, profile.total_iterations as f64 / total_time);
+
+    println!("\nPer instruction:");
+    println!("{:>5}  {:>4}  {:<6} {:>12} {:>8} {:>8}", "Index", "Byte", "Opcode", "Iterations", "Iter %", "Time %");
+    for (index, &iterations) in profile.instruction_iterations.iter().enumerate() {
+        if iterations == 0 {
+            continue;
+        }
+        println!("{:>5}  {:>4}  {:<6} {:>12} {:>7.2}% {:>7.2}%", index, genes[index],
+                 Instruction::from_byte(genes[index], instruction_set).name(), iterations,
+                 iterations as f64 / total_iterations * 100.0,
+                 profile.instruction_time[index].as_secs_f64() / instruction_time_sum * 100.0);
+    }
+
+    println!("\nPer opcode class:");
+    println!("{:<6} {:>12} {:>8} {:>8}", "Opcode", "Iterations", "Iter %", "Time %");
+    for (opcode, &iterations) in &profile.opcode_iterations {
+        let time = profile.opcode_time.get(opcode).copied().unwrap_or_default();
+        println!("{:<6} {:>12} {:>7.2}% {:>7.2}%", opcode, iterations, iterations as f64 / total_iterations * 100.0,
+                 time.as_secs_f64() / instruction_time_sum * 100.0);
+    }
+
+    println!("\nHot loops:");
+    let mut loops: Vec<(&(usize, usize), &u64)> = profile.loops.iter().collect();
+    loops.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    if loops.is_empty() {
+        println!("None");
+    }
+    for (&(from, to), &taken) in loops.iter().take(10) {
+        let body_iterations: u64 = profile.instruction_iterations[to..=from].iter().sum();
+        println!("{:>3} -> {:<3} taken {} times per run, body {} instructions, {:.2}% of iterations",
+                 from, to, taken / u64::from(profile.runs.max(1)), from - to + 1,
+                 body_iterations as f64 / total_iterations * 100.0);
+    }
+}
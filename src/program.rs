@@ -0,0 +1,152 @@
+use ::core::fmt;
+use ::core::ops::Deref;
+use ::core::str::FromStr;
+use alloc::{format, string::String, vec::Vec};
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::core::{self, Genome, INSTR};
+use crate::error::Error;
+use crate::verifier::{self, VerifyError};
+use crate::vm::InstructionSet;
+
+// Genome of a machine program as passed between the search, files and the command line,
+// named apart from the Genome trait every genome type implements
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Program(Vec<INSTR>);
+
+impl Program {
+    pub fn new(genes: Vec<INSTR>) -> Program {
+        return Program(genes);
+    }
+
+    pub fn into_genes(self) -> Vec<INSTR> {
+        return self.0;
+    }
+
+    // Same checks as before the machine runs it
+    pub fn verify(&self, instruction_set: InstructionSet, memory_size: usize) -> Result<(), VerifyError> {
+        return verifier::verify_genome(&self.0, instruction_set, memory_size);
+    }
+
+    // Identical on every platform, unlike the Hash implementation
+    pub fn fingerprint(&self) -> u64 {
+        return core::fnv1a(&self.0);
+    }
+
+    pub fn to_hex(&self) -> String {
+        return to_hex(&self.0);
+    }
+
+    pub fn to_base64(&self) -> String {
+        return STANDARD.encode(&self.0);
+    }
+}
+
+pub fn to_hex(genes: &[INSTR]) -> String {
+    return genes.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+// None unless the text is an even number of hex digits
+pub fn from_hex(text: &str) -> Option<Vec<INSTR>> {
+    if text.is_empty() || !text.len().is_multiple_of(2) || !text.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    return (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok()).collect();
+}
+
+impl Deref for Program {
+    type Target = [INSTR];
+
+    fn deref(&self) -> &[INSTR] {
+        return &self.0;
+    }
+}
+
+impl From<Vec<INSTR>> for Program {
+    fn from(genes: Vec<INSTR>) -> Self {
+        return Program(genes);
+    }
+}
+
+impl Genome for Program {
+    fn bytes(&self) -> &[u8] {
+        return &self.0;
+    }
+}
+
+// Hex, `{:#}` for base64
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return write!(f, "{}", self.to_base64());
+        }
+        return write!(f, "{}", self.to_hex());
+    }
+}
+
+// Hex, the printed list of bytes, e.g. "[200, 193, 147]", or base64
+impl FromStr for Program {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Program, Error> {
+        let text = text.trim();
+        if let Some(genes) = from_hex(text) {
+            return Ok(Program(genes));
+        }
+        let list: Result<Vec<INSTR>, _> = text.trim_start_matches('[').trim_end_matches(']')
+            .split(',')
+            .map(|value| value.trim().parse::<u8>())
+            .collect();
+        if let Ok(genes) = list {
+            return Ok(Program(genes));
+        }
+        return STANDARD.decode(text).map(Program)
+            .map_err(|_| Error::Parse(String::from("expected hex, base64 or a list of bytes")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+    use crate::instruction::Instruction;
+
+    #[test]
+    fn parses_hex_base64_and_lists() {
+        let program = Program::new(vec![200, 193, 147]);
+        assert_eq!("c8c193".parse::<Program>().unwrap(), program);
+        assert_eq!(" C8C193\n".parse::<Program>().unwrap(), program);
+        assert_eq!("yMGT".parse::<Program>().unwrap(), program);
+        assert_eq!("[200, 193, 147]".parse::<Program>().unwrap(), program);
+        for text in ["c8c", "[200, 256]", "yMG!"] {
+            assert!(text.parse::<Program>().is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let program = Program::new((0..=255).collect());
+        assert_eq!(program.to_string().parse::<Program>().unwrap(), program);
+        assert_eq!(format!("{:#}", program).parse::<Program>().unwrap(), program);
+        assert_eq!(Program::new(vec![0x0f, 0xa0]).to_string(), "0fa0");
+        assert_eq!(format!("{:#}", Program::new(vec![200, 193, 147])), "yMGT");
+    }
+
+    #[test]
+    fn verifies_the_length_and_the_instructions() {
+        let jump = Instruction::Jump(5).to_byte();
+        let add = Instruction::Add(1).to_byte();
+        for instruction_set in [InstructionSet::Classic, InstructionSet::Extended] {
+            assert_eq!(Program::new(vec![jump; 8]).verify(instruction_set, 8), Ok(()));
+            assert_eq!(Program::new(vec![jump; 7]).verify(instruction_set, 8),
+                       Err(VerifyError::InvalidLength { expected: 8, actual: 7 }));
+            assert!(matches!(Program::new(vec![jump; 4]).verify(instruction_set, 4), Err(VerifyError::JumpOutOfBounds { index: 0, .. })));
+        }
+        // The last byte needs an operand in the extended set only
+        let program = Program::new(vec![0, 0, 0, add]);
+        assert_eq!(program.verify(InstructionSet::Classic, 4), Ok(()));
+        assert_eq!(program.verify(InstructionSet::Extended, 4), Err(VerifyError::MissingOperand { index: 3, instruction: add }));
+    }
+}
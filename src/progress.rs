@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::core::Chromosome;
+
+// Progress bar on the standard error, hidden when it is not a terminal
+pub struct Progress {
+    bar: ProgressBar,
+    start: Instant,
+}
+
+impl Progress {
+    pub fn new(target_generations: u32, visible: bool) -> Progress {
+        let bar = if visible {
+            ProgressBar::new(u64::from(target_generations))
+        } else {
+            ProgressBar::hidden()
+        };
+        bar.set_style(ProgressStyle::with_template("{elapsed_precise} [{bar:30}] {pos}/{len} generations, ETA {eta} | {msg}")
+            .unwrap()
+            .progress_chars("=> "));
+        bar.enable_steady_tick(Duration::from_millis(250));
+        return Progress { bar, start: Instant::now() };
+    }
+
+    // After the target generation the search goes on without an end
+    pub fn unbounded(&self) {
+        self.bar.set_style(ProgressStyle::with_template("{elapsed_precise} {spinner} {pos} generations | {msg}").unwrap());
+        self.bar.unset_length();
+    }
+
+    pub fn update(&self, generation: u32, best: Option<&Chromosome>, optimal_fitness: Option<f64>, evaluations: u64) {
+        self.bar.set_position(u64::from(generation));
+        let rate = evaluations as f64 / self.start.elapsed().as_secs_f64().max(1e-9);
+        let Some(best) = best else {
+            self.bar.set_message(format!("{:.0} evaluations/s", rate));
+            return;
+        };
+        let relative = match optimal_fitness {
+            Some(optimal_fitness) if optimal_fitness > 0.0 => format!(", R: {:.3}", best.fitness / optimal_fitness),
+            _ => String::new(),
+        };
+        self.bar.set_message(format!("F: {:.4}, T: {}, S: {}, I: {}{}, {:.0} evaluations/s",
+                                     best.fitness, best.found_treasures, best.steps.len(), best.iterations, relative, rate));
+    }
+
+    // Hides the bar while regular output is printed or the user is asked
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        return self.bar.suspend(f);
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
@@ -0,0 +1,198 @@
+// Python module `treasure_search`, built as an extension with
+// `cargo rustc --lib --release --features python --crate-type cdylib` and the library renamed to treasure_search.so
+use std::sync::{Mutex, MutexGuard};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::core::{self, Chromosome};
+use crate::engine::Engine;
+use crate::error::Error;
+use crate::map::{self, GameArea};
+use crate::spec::EngineSpec;
+use crate::vm::Vm;
+
+fn to_py_error(e: Error) -> PyErr {
+    return PyValueError::new_err(e.to_string());
+}
+
+#[pyclass(name = "Map", frozen)]
+#[derive(Clone)]
+pub struct PyMap {
+    game_area: GameArea,
+}
+
+#[pymethods]
+impl PyMap {
+    #[getter]
+    fn width(&self) -> usize {
+        return self.game_area.width();
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        return self.game_area.height();
+    }
+
+    #[getter]
+    fn player(&self) -> (usize, usize) {
+        return self.game_area.player().tile();
+    }
+
+    #[getter]
+    fn treasures(&self) -> Vec<(usize, usize)> {
+        return self.game_area.treasures().collect();
+    }
+
+    fn __str__(&self) -> String {
+        return self.game_area.to_string();
+    }
+}
+
+#[pyfunction]
+fn load_map(path: std::path::PathBuf) -> PyResult<PyMap> {
+    return Ok(PyMap { game_area: map::load(&path).map_err(to_py_error)? });
+}
+
+#[pyfunction]
+fn parse_map(text: &str) -> PyResult<PyMap> {
+    return Ok(PyMap { game_area: map::parse(text).map_err(to_py_error)? });
+}
+
+#[pyfunction]
+fn default_map() -> PyMap {
+    return PyMap { game_area: core::build_game_area() };
+}
+
+fn chromosome_dict<'py>(py: Python<'py>, chromosome: &Chromosome) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("genome", PyBytes::new(py, &chromosome.genes))?;
+    dict.set_item("fitness", chromosome.fitness)?;
+    dict.set_item("treasures", chromosome.found_treasures)?;
+    dict.set_item("steps", &chromosome.steps)?;
+    dict.set_item("iterations", chromosome.iterations)?;
+    dict.set_item("complexity", chromosome.complexity)?;
+    return Ok(dict);
+}
+
+#[pyclass(name = "Engine")]
+pub struct PyEngine {
+    // Python may share the object between threads, the engine is only Send
+    engine: Mutex<Engine<'static>>,
+}
+
+impl PyEngine {
+    fn engine(&self) -> MutexGuard<'_, Engine<'static>> {
+        return self.engine.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+}
+
+#[pymethods]
+impl PyEngine {
+    #[new]
+    #[pyo3(signature = (map=None, fitness=String::from("steps"), subjects=EngineSpec::default().subjects,
+                        mutation_probability=EngineSpec::default().mutation_probability, selection=String::from("roulette"),
+                        elitism=EngineSpec::default().elitism, instruction_set=String::from("classic"),
+                        iteration_limit=EngineSpec::default().iteration_limit, memory_size=EngineSpec::default().memory_size, seed=0))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(map: Option<PyRef<PyMap>>, fitness: String, subjects: usize, mutation_probability: f64, selection: String, elitism: usize,
+           instruction_set: String, iteration_limit: u32, memory_size: usize, seed: u64) -> PyResult<PyEngine> {
+        let spec = EngineSpec {
+            map: map.map(|map| map.game_area.to_string()),
+            fitness,
+            subjects,
+            mutation_probability,
+            selection,
+            elitism,
+            instruction_set,
+            iteration_limit,
+            memory_size,
+            seed,
+        };
+        return Ok(PyEngine { engine: Mutex::new(spec.build().map_err(to_py_error)?) });
+    }
+
+    // Statistics of the stepped generation
+    fn step<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self.engine().step().stats;
+        let dict = PyDict::new(py);
+        dict.set_item("generation", stats.generation)?;
+        dict.set_item("best_fitness", stats.best_fitness)?;
+        dict.set_item("mean_fitness", stats.mean_fitness)?;
+        dict.set_item("worst_fitness", stats.worst_fitness)?;
+        dict.set_item("diversity", stats.diversity)?;
+        return Ok(dict);
+    }
+
+    // Steps at most the given number of generations, fewer once every treasure is collected, returns the best chromosome so far
+    fn run<'py>(&self, py: Python<'py>, generations: u32) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let mut engine = self.engine();
+        for _ in 0..generations {
+            if engine.solved() {
+                break;
+            }
+            engine.step();
+        }
+        return engine.best().map(|best| chromosome_dict(py, best)).transpose();
+    }
+
+    #[getter]
+    fn best<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        return self.engine().best().map(|best| chromosome_dict(py, best)).transpose();
+    }
+
+    #[getter]
+    fn generation(&self) -> u32 {
+        return self.engine().generation();
+    }
+
+    #[getter]
+    fn evaluations(&self) -> u64 {
+        return self.engine().evaluations();
+    }
+
+    #[getter]
+    fn solved(&self) -> bool {
+        return self.engine().solved();
+    }
+}
+
+// Runs a genome once on the map and returns what the machine did, with the whole trajectory
+#[pyfunction]
+#[pyo3(signature = (genome, map=None, instruction_set=String::from("classic"), iteration_limit=EngineSpec::default().iteration_limit,
+                    memory_size=EngineSpec::default().memory_size, seed=0))]
+fn replay<'py>(py: Python<'py>, genome: &[u8], map: Option<PyRef<PyMap>>, instruction_set: String, iteration_limit: u32,
+               memory_size: usize, seed: u64) -> PyResult<Bound<'py, PyDict>> {
+    let spec = EngineSpec {
+        map: map.map(|map| map.game_area.to_string()),
+        instruction_set,
+        iteration_limit,
+        memory_size,
+        ..EngineSpec::default()
+    };
+    let game_area = spec.game_area().map_err(to_py_error)?;
+    let mut vm_config = spec.vm_config().map_err(to_py_error)?;
+    vm_config.seed = seed;
+    vm_config.record_trajectory = true;
+    let result = Vm::new(vm_config).run(genome, &game_area);
+    let dict = PyDict::new(py);
+    dict.set_item("steps", result.steps)?;
+    dict.set_item("treasures", result.found_treasures)?;
+    dict.set_item("collected_treasures", result.collected_treasures)?;
+    dict.set_item("iterations", result.iterations)?;
+    dict.set_item("termination", format!("{:?}", result.termination))?;
+    dict.set_item("trajectory", result.trajectory)?;
+    return Ok(dict);
+}
+
+#[pymodule]
+fn treasure_search(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMap>()?;
+    module.add_class::<PyEngine>()?;
+    module.add_function(wrap_pyfunction!(load_map, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_map, module)?)?;
+    module.add_function(wrap_pyfunction!(default_map, module)?)?;
+    module.add_function(wrap_pyfunction!(replay, module)?)?;
+    return Ok(());
+}
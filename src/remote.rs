@@ -0,0 +1,216 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+
+use rand_pcg::Pcg64;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use treasure_search::core::{Chromosome, Comparison, Genes};
+use treasure_search::environment::{Environment, EvalResult, GridEnvironment};
+
+// First message of a coordinator: everything a worker needs to build the same environment.
+// Every message is one line of JSON, answered by one line.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Setup {
+    // Command line of the run with the configuration file and the environment resolved, see cli::resolved_args
+    pub args: Vec<String>,
+    // Text form of the map, workers do not need its file
+    pub map: String,
+    // Text form of the maps after the first one, see ensemble::map_cases
+    #[serde(default)]
+    pub extra_maps: Vec<String>,
+    pub vm_seed: u64,
+}
+
+// Genomes evaluated with the same seed, answered by their results in the same order
+#[derive(Serialize, Deserialize, Debug)]
+struct Request {
+    seed: Option<u64>,
+    // See Environment::bound_by
+    bound: Option<(Chromosome, Comparison)>,
+    genomes: Vec<Genes>,
+}
+
+// A connected worker, the answer to its setup was the fingerprint of its maps
+pub struct Connection {
+    address: String,
+    map_fingerprint: Option<u64>,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn open(address: &str, setup: &Setup) -> Result<Connection, String> {
+        let stream = TcpStream::connect(address).map_err(|e| e.to_string())?;
+        stream.set_nodelay(true).ok();
+        let reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut connection = Connection { address: String::from(address), map_fingerprint: None, reader, writer: stream };
+        let answer: Result<Option<u64>, String> = connection.request(setup)?;
+        connection.map_fingerprint = answer?;
+        return Ok(connection);
+    }
+
+    fn request<M: Serialize, A: DeserializeOwned>(&mut self, message: &M) -> Result<A, String> {
+        send(&mut self.writer, message)?;
+        return receive(&mut self.reader)?.ok_or_else(|| String::from("the worker closed the connection"));
+    }
+
+    fn evaluate(&mut self, genomes: &[&Genes], seed: Option<u64>, bound: Option<(Chromosome, Comparison)>) -> Result<Vec<EvalResult>, String> {
+        let request = Request { seed, bound, genomes: genomes.iter().map(|&&genes| genes).collect() };
+        let results: Vec<EvalResult> = self.request(&request)?;
+        if results.len() != genomes.len() {
+            return Err(format!("{} results for {} genomes", results.len(), genomes.len()));
+        }
+        return Ok(results);
+    }
+}
+
+fn send<M: Serialize>(writer: &mut TcpStream, message: &M) -> Result<(), String> {
+    let mut line = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    return writer.write_all(&line).map_err(|e| e.to_string());
+}
+
+// None once the other side closed the connection
+fn receive<M: DeserializeOwned>(reader: &mut BufReader<TcpStream>) -> Result<Option<M>, String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+        return Ok(None);
+    }
+    return serde_json::from_str(&line).map(Some).map_err(|e| e.to_string());
+}
+
+// Shares the batches of a generation among the workers, the local environment evaluates single genomes,
+// e.g. the extra samples of resampling, and the genomes of workers which failed
+pub struct RemoteEnvironment<'a> {
+    local: Box<dyn Environment + 'a>,
+    // None once the worker failed, it is not asked again
+    workers: Vec<Mutex<Option<Connection>>>,
+    // Passed on to the workers with every request
+    bound: Mutex<Option<(Chromosome, Comparison)>>,
+}
+
+impl<'a> RemoteEnvironment<'a> {
+    // Workers which built other maps than the local ones are dropped
+    pub fn new(local: Box<dyn Environment + 'a>, connections: Vec<Connection>) -> RemoteEnvironment<'a> {
+        let mut workers: Vec<Mutex<Option<Connection>>> = Vec::with_capacity(connections.len());
+        for connection in connections {
+            if connection.map_fingerprint != local.map_fingerprint() {
+                eprintln!("Worker {} built other maps, it is not used", connection.address);
+                continue;
+            }
+            workers.push(Mutex::new(Some(connection)));
+        }
+        return RemoteEnvironment { local, workers, bound: Mutex::new(None) };
+    }
+}
+
+impl Environment for RemoteEnvironment<'_> {
+    fn evaluate(&self, genome: &Genes, seed: Option<u64>) -> EvalResult {
+        return self.local.evaluate(genome, seed);
+    }
+
+    // One consecutive share of the genomes per worker, all of them asked at the same time
+    fn evaluate_many(&self, genomes: &[&Genes], seed: Option<u64>) -> Vec<EvalResult> {
+        if self.workers.is_empty() {
+            return self.local.evaluate_many(genomes, seed);
+        }
+        let share = genomes.len().div_ceil(self.workers.len());
+        let local = &*self.local;
+        let bound = self.bound.lock().unwrap().clone();
+        return std::thread::scope(|scope| {
+            let shares: Vec<_> = genomes.chunks(share).zip(&self.workers).map(|(genomes, worker)| {
+                let bound = bound.clone();
+                return scope.spawn(move || {
+                    let mut worker = worker.lock().unwrap();
+                    if let Some(connection) = worker.as_mut() {
+                        match connection.evaluate(genomes, seed, bound) {
+                            Ok(results) => return results,
+                            Err(e) => {
+                                eprintln!("Worker {} failed: {}, its genomes are evaluated here", connection.address, e);
+                                *worker = None;
+                            }
+                        }
+                    }
+                    return local.evaluate_many(genomes, seed);
+                });
+            }).collect();
+            shares.into_iter().flat_map(|share| share.join().unwrap()).collect()
+        });
+    }
+
+    fn random_genome(&self, rng: &mut Pcg64) -> Genes {
+        return self.local.random_genome(rng);
+    }
+
+    fn is_solution(&self, chromosome: &Chromosome) -> bool {
+        return self.local.is_solution(chromosome);
+    }
+
+    fn map_fingerprint(&self) -> Option<u64> {
+        return self.local.map_fingerprint();
+    }
+
+    fn bound_by(&self, solution: Option<&Chromosome>, comparison: Comparison) {
+        self.local.bound_by(solution, comparison);
+        *self.bound.lock().unwrap() = solution.map(|solution| (solution.clone(), comparison));
+    }
+}
+
+// Serves every coordinator on its own thread until the process is stopped
+pub fn serve<B>(address: &str, build: B) -> Result<(), String>
+where
+    B: Fn(&Setup) -> Result<GridEnvironment<'static>, String> + Sync,
+{
+    let listener = TcpListener::bind(address).map_err(|e| e.to_string())?;
+    eprintln!("Listening on {}", listener.local_addr().map_err(|e| e.to_string())?);
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Failed to accept a connection: {}", e);
+                    continue;
+                }
+            };
+            let build = &build;
+            scope.spawn(move || {
+                let peer = stream.peer_addr().map(|address| address.to_string()).unwrap_or_default();
+                eprintln!("Coordinator {} connected", peer);
+                match handle(stream, build) {
+                    Ok(()) => eprintln!("Coordinator {} disconnected", peer),
+                    Err(e) => eprintln!("Coordinator {} failed: {}", peer, e),
+                }
+            });
+        }
+    });
+    return Ok(());
+}
+
+fn handle(stream: TcpStream, build: &dyn Fn(&Setup) -> Result<GridEnvironment<'static>, String>) -> Result<(), String> {
+    stream.set_nodelay(true).ok();
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut writer = stream;
+    let Some(setup) = receive::<Setup>(&mut reader)? else {
+        return Ok(());
+    };
+    let environment = match build(&setup) {
+        Ok(environment) => environment,
+        Err(e) => {
+            send(&mut writer, &Err::<Option<u64>, String>(e.clone()))?;
+            return Err(e);
+        }
+    };
+    send(&mut writer, &Ok::<Option<u64>, String>(environment.map_fingerprint()))?;
+    while let Some(request) = receive::<Request>(&mut reader)? {
+        let genomes: Vec<&Genes> = request.genomes.iter().collect();
+        let (solution, comparison) = match request.bound {
+            Some((solution, comparison)) => (Some(solution), comparison),
+            None => (None, Comparison::Fitness),
+        };
+        environment.bound_by(solution.as_ref(), comparison);
+        send(&mut writer, &environment.evaluate_many(&genomes, request.seed))?;
+    }
+    return Ok(());
+}
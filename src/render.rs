@@ -0,0 +1,117 @@
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use crate::core::{AREA_TILE_PLAYER, AREA_TILE_TREASURE};
+use crate::map::GameArea;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_BLUE: &str = "\x1b[34m";
+const ANSI_GREY: &str = "\x1b[90m";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn parse(text: &str) -> Option<ColorMode> {
+        return match text.to_ascii_lowercase().as_str() {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        };
+    }
+
+    // Auto colors only a terminal which is not dumb and when NO_COLOR is not set
+    pub fn enabled(&self) -> bool {
+        return match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => std::io::stdout().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none()
+                && std::env::var("TERM").map_or(true, |term| term != "dumb"),
+        };
+    }
+}
+
+// Player green, treasures yellow, visited tiles blue with an arrow of the last move leaving them, the rest grey;
+// plain ASCII without colors
+pub fn render_map(game_area: &GameArea, path: &[(usize, usize)], color: bool) -> String {
+    return render(game_area, path, None, color);
+}
+
+// The map after walking the path, the player shown at the last position and treasures on the path collected
+pub fn render_frame(game_area: &GameArea, walked: &[(usize, usize)], color: bool) -> String {
+    return render(game_area, walked, walked.last().copied(), color);
+}
+
+fn render(game_area: &GameArea, path: &[(usize, usize)], position: Option<(usize, usize)>, color: bool) -> String {
+    let mut text: String = String::new();
+    for (y, row) in game_area.rows().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let visited = path.contains(&(x, y));
+            let (glyph, ansi) = if position == Some((x, y)) {
+                ('@', ANSI_GREEN)
+            } else if position.is_some() && visited {
+                ('*', ANSI_BLUE)
+            } else if tile == AREA_TILE_PLAYER {
+                ('P', ANSI_GREEN)
+            } else if tile == AREA_TILE_TREASURE {
+                ('T', ANSI_YELLOW)
+            } else if visited {
+                (direction(path, (x, y)), ANSI_BLUE)
+            } else {
+                ('.', ANSI_GREY)
+            };
+            if color {
+                text.push_str(ansi);
+                text.push(glyph);
+                text.push_str(ANSI_RESET);
+            } else {
+                text.push(glyph);
+            }
+            text.push(' ');
+        }
+        text.push('\n');
+    }
+    return text;
+}
+
+// Arrow of the last move leaving the tile, the tile the path ends on is marked with '*'
+fn direction(path: &[(usize, usize)], tile: (usize, usize)) -> char {
+    let Some(i) = path.windows(2).rposition(|step| step[0] == tile && step[1] != tile) else {
+        return '*';
+    };
+    let (from, to) = (path[i], path[i + 1]);
+    // Wrapping around the edge moves more than one tile the other way
+    let (dx, dy) = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+    return match (dx.signum() * if dx.abs() > 1 { -1 } else { 1 }, dy.signum() * if dy.abs() > 1 { -1 } else { 1 }) {
+        (1, _) => '>',
+        (-1, _) => '<',
+        (_, 1) => 'v',
+        _ => '^',
+    };
+}
+
+// Shows the path one move per frame, redrawing in place on a terminal
+pub fn play(game_area: &GameArea, trajectory: &[(usize, usize)], frame_delay: Duration, color: bool) {
+    let in_place = std::io::stdout().is_terminal();
+    let mut stdout = std::io::stdout();
+    for moves in 1..=trajectory.len() {
+        if in_place && moves > 1 {
+            // Back to the first line of the previous frame
+            print!("\x1b[{}F", game_area.height() + 1);
+        }
+        print!("{}", render_frame(game_area, &trajectory[..moves], color));
+        println!("Move {}/{}", moves - 1, trajectory.len() - 1);
+        stdout.flush().ok();
+        if moves < trajectory.len() {
+            std::thread::sleep(frame_delay);
+        }
+    }
+}
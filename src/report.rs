@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::core::Chromosome;
+
+// Machine-readable summary of a finished run
+#[derive(Serialize, Debug)]
+pub struct RunSummary {
+    pub config: RunConfigSummary,
+    pub seed: u64,
+    pub map_hash: String,
+    pub generations: u32,
+    pub evaluations: u64,
+    pub wall_time_seconds: f64,
+    pub best: Option<BestSummary>,
+    // Shortest tour collecting every treasure, absent when some treasure cannot be reached
+    pub optimum: Option<OptimumSummary>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RunConfigSummary {
+    pub subjects: usize,
+    pub target_generations: u32,
+    pub mutation_probability: f64,
+    pub selection: String,
+    pub scaling: String,
+    pub comparison: String,
+    pub fitness_function: String,
+    pub instruction_set: String,
+    pub iteration_limit: u32,
+    pub memory_size: usize,
+    pub collision_policy: String,
+    // Absent when moves are unlimited
+    pub max_moves: Option<u32>,
+    pub memory_mapped_inputs: bool,
+    pub samples: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BestSummary {
+    pub genome: Vec<u8>,
+    pub fitness: f64,
+    pub steps: String,
+    pub step_count: usize,
+    pub found_treasures: u32,
+    pub treasures: u32,
+    pub solved: bool,
+    pub iterations: u32,
+    pub complexity: u32,
+}
+
+impl BestSummary {
+    // Steps are given in the glyphs the run was printed with
+    pub fn new(chromosome: &Chromosome, steps: String, treasures: u32) -> BestSummary {
+        return BestSummary {
+            genome: chromosome.genes.to_vec(),
+            fitness: chromosome.fitness,
+            steps,
+            step_count: chromosome.steps.chars().count(),
+            found_treasures: chromosome.found_treasures,
+            treasures,
+            solved: chromosome.found_treasures == treasures,
+            iterations: chromosome.iterations,
+            complexity: chromosome.complexity,
+        };
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct OptimumSummary {
+    // Of the ideal program walking the tour, the reference for the fitness of the best solution
+    pub fitness: f64,
+    pub steps: String,
+    pub step_count: usize,
+    // False for a nearest neighbour tour of more than solver::EXACT_TREASURE_LIMIT treasures
+    pub exact: bool,
+}
+
+// Printed unless a file is given
+// Best solution of a running search, rewritten whenever it improves
+#[derive(Serialize, Debug)]
+pub struct Autosave {
+    pub seed: u64,
+    pub map_hash: String,
+    pub generation: u32,
+    pub best: BestSummary,
+}
+
+// Written next to the target first so readers never see a half-written file
+pub fn write_autosave(autosave: &Autosave, path: &Path) -> std::io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, serde_json::to_string_pretty(autosave)? + "\n")?;
+    return std::fs::rename(&temporary, path);
+}
+
+pub fn write_json(summary: &RunSummary, path: Option<&Path>) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(summary)?;
+    return match path {
+        Some(path) => std::fs::write(path, json + "\n"),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    };
+}
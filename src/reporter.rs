@@ -0,0 +1,273 @@
+#[cfg(feature = "std")]
+use std::io::Write;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::core::{Genes, Genome};
+#[cfg(feature = "std")]
+use crate::core::{Chromosome, StepAlphabet};
+use crate::engine::Event;
+#[cfg(feature = "std")]
+use crate::engine::StopReason;
+use crate::error::Error;
+#[cfg(feature = "std")]
+use crate::stats::CsvLog;
+
+// Receives the events of a search, e.g. to print or log them, added to the engine with add_reporter()
+pub trait Reporter<G = Genes>: Send {
+    fn report(&mut self, event: &Event<G>) -> Result<(), Error>;
+}
+
+// Drops every event
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentReporter;
+
+impl<G: Genome> Reporter<G> for SilentReporter {
+    fn report(&mut self, _event: &Event<G>) -> Result<(), Error> {
+        return Ok(());
+    }
+}
+
+#[cfg(feature = "std")]
+type Details<G> = Box<dyn Fn(&Chromosome<G>) -> String + Send>;
+
+// The messages of the command line, on the standard output unless another writer is given
+#[cfg(feature = "std")]
+pub struct ConsoleReporter<G = Genes> {
+    step_alphabet: StepAlphabet,
+    // Treasures of the map, shown next to the found ones
+    treasures: u32,
+    // Statistics are printed every this many generations
+    stats_every: u32,
+    new_best: bool,
+    // Every solution as it is found, otherwise only the one the search stops at
+    solutions: bool,
+    // Fitness and steps of the best possible solution the others are compared with
+    optimum: Option<(f64, usize)>,
+    // Lines printed below a solution, e.g. its genome
+    details: Option<Details<G>>,
+    writer: Box<dyn Write + Send>,
+    first_solution: Option<u32>,
+    // Generation of the last printed solution
+    announced: Option<u32>,
+}
+
+#[cfg(feature = "std")]
+impl<G> ConsoleReporter<G> {
+    pub fn new(step_alphabet: StepAlphabet, treasures: u32) -> ConsoleReporter<G> {
+        return ConsoleReporter {
+            step_alphabet,
+            treasures,
+            stats_every: 1,
+            new_best: true,
+            solutions: true,
+            optimum: None,
+            details: None,
+            writer: Box::new(std::io::stdout()),
+            first_solution: None,
+            announced: None,
+        };
+    }
+
+    pub fn stats_every(mut self, stats_every: u32) -> Self {
+        self.stats_every = stats_every.max(1);
+        return self;
+    }
+
+    pub fn new_best(mut self, new_best: bool) -> Self {
+        self.new_best = new_best;
+        return self;
+    }
+
+    pub fn solutions(mut self, solutions: bool) -> Self {
+        self.solutions = solutions;
+        return self;
+    }
+
+    pub fn optimum(mut self, fitness: f64, steps: usize) -> Self {
+        self.optimum = Some((fitness, steps));
+        return self;
+    }
+
+    pub fn details(mut self, details: impl Fn(&Chromosome<G>) -> String + Send + 'static) -> Self {
+        self.details = Some(Box::new(details));
+        return self;
+    }
+
+    pub fn writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.writer = Box::new(writer);
+        return self;
+    }
+
+    // A line about a generation no event tells, e.g. its throughput
+    pub fn note(&mut self, generation: u32, note: impl std::fmt::Display) -> Result<(), Error> {
+        writeln!(self.writer, "Generation {}: {}", generation, note)?;
+        return Ok(());
+    }
+
+    fn steps(&self, chromosome: &Chromosome<G>) -> String {
+        return format!("{} ({})", self.step_alphabet.format(&chromosome.steps), chromosome.steps.len());
+    }
+
+    fn relative_to_optimum(&self, chromosome: &Chromosome<G>) -> String {
+        return match self.optimum {
+            Some((fitness, steps)) if fitness > 0.0 && steps > 0 => format!(", Of optimum: fitness {:.3}, steps {:.3}",
+                                                                            chromosome.fitness / fitness, chromosome.steps.len() as f64 / steps as f64),
+            _ => String::new(),
+        };
+    }
+
+    fn write_details(&mut self, chromosome: &Chromosome<G>) -> Result<(), Error> {
+        writeln!(self.writer, "Complexity: {} distinct instructions executed", chromosome.complexity)?;
+        if let Some(details) = &self.details {
+            write!(self.writer, "{}", details(chromosome))?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G: Genome> Reporter<G> for ConsoleReporter<G> {
+    fn report(&mut self, event: &Event<G>) -> Result<(), Error> {
+        match event {
+            Event::GenerationCompleted(stats) => {
+                if stats.generation.is_multiple_of(self.stats_every) {
+                    writeln!(self.writer, "{}", stats)?;
+                }
+            }
+            Event::NewBest { generation, best } => {
+                if self.new_best {
+                    writeln!(self.writer, "Generation {}: new best fitness {}, Steps: {}, Treasures: {}/{}, Iterations: {}",
+                             generation, best.fitness, self.steps(best), best.found_treasures, self.treasures, best.iterations)?;
+                }
+            }
+            Event::SolutionFound { generation, solution } => {
+                self.first_solution.get_or_insert(*generation);
+                if self.solutions {
+                    self.announced = Some(*generation);
+                    writeln!(self.writer, "\nSuccessful solution! Generation: {}, Fitness: {}, Steps: {}, Iterations: {}{}",
+                             generation, solution.fitness, self.steps(solution), solution.iterations, self.relative_to_optimum(solution))?;
+                    self.write_details(solution)?;
+                }
+            }
+            Event::Restarted { generation } => writeln!(self.writer, "Generation {}: restarted", generation)?,
+            Event::Interrupted { generation } => writeln!(self.writer, "\nInterrupted at generation {}", generation)?,
+            Event::Finished { generation, reason, best } => {
+                let Some(best) = best else {
+                    return Ok(());
+                };
+                match reason {
+                    // Already printed when it was found
+                    StopReason::Solved if self.announced == Some(*generation) => return Ok(()),
+                    StopReason::Solved => writeln!(self.writer, "\nSearched {} more generations after the first solution!",
+                                                   generation - self.first_solution.unwrap_or(*generation))?,
+                    StopReason::GenerationLimit => writeln!(self.writer, "\nTarget generation reached!")?,
+                    StopReason::TimeLimit => writeln!(self.writer, "\nTime budget spent!")?,
+                    StopReason::EvaluationLimit => writeln!(self.writer, "\nEvaluation budget spent!")?,
+                    StopReason::Stopped => writeln!(self.writer, "\nStopped on request at generation {}", generation)?,
+                    // After Interrupted
+                    StopReason::Cancelled => {}
+                }
+                writeln!(self.writer, "\nBest solution so far: Generation: {}, Fitness: {}, Steps: {}, Treasures: {}, Iterations: {}{}",
+                         generation, best.fitness, self.steps(best), best.found_treasures, best.iterations, self.relative_to_optimum(best))?;
+                self.write_details(best)?;
+            }
+        }
+        self.writer.flush()?;
+        return Ok(());
+    }
+}
+
+// A row of statistics per generation, the other events are left out
+#[cfg(feature = "std")]
+pub struct CsvReporter(CsvLog);
+
+#[cfg(feature = "std")]
+impl CsvReporter {
+    // Appends to an existing log
+    pub fn open(path: &Path) -> Result<CsvReporter, Error> {
+        return Ok(CsvReporter(CsvLog::open(path)?));
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G: Genome> Reporter<G> for CsvReporter {
+    fn report(&mut self, event: &Event<G>) -> Result<(), Error> {
+        if let Event::GenerationCompleted(stats) = event {
+            self.0.write(stats)?;
+        }
+        return Ok(());
+    }
+}
+
+// One JSON object per event and line, e.g. for a log collector
+#[cfg(feature = "std")]
+pub struct JsonLinesReporter<W> {
+    writer: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Send> JsonLinesReporter<W> {
+    pub fn new(writer: W) -> JsonLinesReporter<W> {
+        return JsonLinesReporter { writer };
+    }
+}
+
+#[cfg(feature = "std")]
+impl JsonLinesReporter<std::fs::File> {
+    // Appends to an existing log
+    pub fn open(path: &Path) -> Result<JsonLinesReporter<std::fs::File>, Error> {
+        return Ok(JsonLinesReporter::new(std::fs::OpenOptions::new().create(true).append(true).open(path)?));
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn chromosome_json<G>(chromosome: &Chromosome<G>) -> serde_json::Value {
+    return serde_json::json!({
+        "fitness": chromosome.fitness,
+        "treasures": chromosome.found_treasures,
+        "steps": chromosome.steps,
+        "iterations": chromosome.iterations,
+    });
+}
+
+// One JSON object per event, the lines of JsonLinesReporter
+#[cfg(feature = "std")]
+pub fn event_json<G>(event: &Event<G>) -> serde_json::Value {
+    return match event {
+        Event::GenerationCompleted(stats) => serde_json::json!({
+            "event": "generation",
+            "generation": stats.generation,
+            "best_fitness": stats.best_fitness,
+            "mean_fitness": stats.mean_fitness,
+            "median_fitness": stats.median_fitness,
+            "worst_fitness": stats.worst_fitness,
+            "fitness_std_dev": stats.fitness_std_dev,
+            "diversity": stats.diversity,
+            "best_treasures": stats.best_treasures,
+            "best_steps": stats.best_steps,
+        }),
+        Event::NewBest { generation, best } => {
+            serde_json::json!({ "event": "new_best", "generation": generation, "best": chromosome_json(best) })
+        }
+        Event::SolutionFound { generation, solution } => {
+            serde_json::json!({ "event": "solution", "generation": generation, "solution": chromosome_json(solution) })
+        }
+        Event::Restarted { generation } => serde_json::json!({ "event": "restarted", "generation": generation }),
+        Event::Interrupted { generation } => serde_json::json!({ "event": "interrupted", "generation": generation }),
+        Event::Finished { generation, reason, best } => serde_json::json!({
+            "event": "finished",
+            "generation": generation,
+            "reason": reason.name(),
+            "best": best.as_ref().map(chromosome_json),
+        }),
+    };
+}
+
+#[cfg(feature = "std")]
+impl<G: Genome, W: Write + Send> Reporter<G> for JsonLinesReporter<W> {
+    fn report(&mut self, event: &Event<G>) -> Result<(), Error> {
+        writeln!(self.writer, "{}", event_json(event))?;
+        return Ok(());
+    }
+}
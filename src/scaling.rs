@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::core::{Chromosome, Comparison};
+
+// Post-processing of raw fitness into the values used by selection
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FitnessScaling {
+    None,
+    // Keeps the mean, the best chromosome gets `multiple` times the mean
+    Linear { multiple: f64 },
+    // Subtracts mean - c * standard deviation and drops anything below
+    SigmaTruncation { c: f64 },
+    // Linear rank by the comparison, the worst chromosome gets 1 and the best gets the population size
+    Rank,
+}
+
+impl FitnessScaling {
+    // Accepts "none", "linear[:multiple]", "sigma[:c]" or "rank"
+    pub fn parse(text: &str) -> Option<FitnessScaling> {
+        let (name, parameter) = match text.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter.parse::<f64>().ok()?)),
+            None => (text, None),
+        };
+        return match name.to_ascii_lowercase().as_str() {
+            "none" => Some(FitnessScaling::None),
+            "linear" => Some(FitnessScaling::Linear { multiple: parameter.unwrap_or(2.0) }),
+            "sigma" => Some(FitnessScaling::SigmaTruncation { c: parameter.unwrap_or(2.0) }),
+            "rank" => Some(FitnessScaling::Rank),
+            _ => None,
+        };
+    }
+
+    // Sets the selection fitness of every chromosome, returns their sum
+    pub fn apply<G>(&self, chromosomes: &mut [Chromosome<G>], comparison: Comparison) -> f64 {
+        let n = chromosomes.len() as f64;
+        let mean: f64 = chromosomes.iter().map(|c| c.fitness).sum::<f64>() / n;
+        match *self {
+            FitnessScaling::None => {
+                for c in chromosomes.iter_mut() {
+                    c.selection_fitness = c.fitness;
+                }
+            }
+            FitnessScaling::Linear { multiple } => {
+                let max = chromosomes.iter().map(|c| c.fitness).fold(f64::MIN, f64::max);
+                let min = chromosomes.iter().map(|c| c.fitness).fold(f64::MAX, f64::min);
+                let (a, b) = if max - mean <= f64::EPSILON {
+                    (1.0, 0.0)
+                } else if min > (multiple * mean - max) / (multiple - 1.0) {
+                    let delta = max - mean;
+                    ((multiple - 1.0) * mean / delta, mean * (max - multiple * mean) / delta)
+                } else {
+                    // Scale as much as possible without making the worst chromosome negative
+                    let delta = mean - min;
+                    (mean / delta, -min * mean / delta)
+                };
+                for c in chromosomes.iter_mut() {
+                    c.selection_fitness = (a * c.fitness + b).max(0.0);
+                }
+            }
+            FitnessScaling::SigmaTruncation { c: multiple } => {
+                let variance: f64 = chromosomes.iter().map(|c| (c.fitness - mean) * (c.fitness - mean)).sum::<f64>() / n;
+                let threshold = mean - multiple * libm::sqrt(variance);
+                for c in chromosomes.iter_mut() {
+                    c.selection_fitness = (c.fitness - threshold).max(0.0);
+                }
+            }
+            FitnessScaling::Rank => {
+                let mut order: Vec<usize> = (0..chromosomes.len()).collect();
+                order.sort_by(|&a, &b| comparison.compare(&chromosomes[a], &chromosomes[b]));
+                for (rank, index) in order.into_iter().enumerate() {
+                    chromosomes[index].selection_fitness = (rank + 1) as f64;
+                }
+            }
+        }
+        return chromosomes.iter().map(|c| c.selection_fitness).sum();
+    }
+}
+
+// The form parse() accepts
+impl fmt::Display for FitnessScaling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            FitnessScaling::None => write!(f, "none"),
+            FitnessScaling::Linear { multiple } => write!(f, "linear:{}", multiple),
+            FitnessScaling::SigmaTruncation { c } => write!(f, "sigma:{}", c),
+            FitnessScaling::Rank => write!(f, "rank"),
+        };
+    }
+}
@@ -0,0 +1,251 @@
+use alloc::vec::Vec;
+use ::core::fmt;
+
+use rand::Rng;
+use rand_pcg::Pcg64;
+
+use crate::core::{self, Chromosome, Comparison, Genes};
+use crate::population::Population;
+
+// Chooses the parents of the next generation
+pub trait SelectionStrategy<G = Genes>: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Index of one parent in the evaluated population, sorted from the best.
+    // The total fitness is the sum of the selection fitness of the population.
+    fn select(&self, chromosomes: &[Chromosome<G>], total_fitness: f64, comparison: Comparison, rng: &mut Pcg64) -> usize;
+
+    // Same as select on the scaled population, which strategies can use the sums of
+    fn select_from(&self, population: &Population<G>, comparison: Comparison, rng: &mut Pcg64) -> usize {
+        return self.select(population.chromosomes(), population.total_fitness(), comparison, rng);
+    }
+
+    // False when select() works on any order, then only the elites are sorted to the front
+    fn needs_sorted(&self) -> bool {
+        return true;
+    }
+
+    // Called once per generation after scaling, before any parent is selected
+    fn prepare(&self, _population: &mut Population<G>) {}
+}
+
+impl<G> fmt::Debug for dyn SelectionStrategy<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.name());
+    }
+}
+
+// Every chromosome owns a slice of the wheel as large as its selection fitness
+#[derive(Clone, Copy, Debug)]
+pub struct RouletteSelection;
+
+impl<G> SelectionStrategy<G> for RouletteSelection {
+    fn name(&self) -> &'static str {
+        return "roulette";
+    }
+
+    fn needs_sorted(&self) -> bool {
+        return false;
+    }
+
+    fn select(&self, chromosomes: &[Chromosome<G>], total_fitness: f64, _comparison: Comparison, rng: &mut Pcg64) -> usize {
+        let (index, _) = core::roulette_pick(chromosomes, total_fitness, rng);
+        return index;
+    }
+
+    // Binary search in the running sums instead of walking the wheel, the order of the population does not matter
+    fn select_from(&self, population: &Population<G>, _comparison: Comparison, rng: &mut Pcg64) -> usize {
+        let cumulative = population.cumulative_fitness();
+        let total = cumulative.last().copied().unwrap_or(0.0);
+        if !(total > 0.0 && total.is_finite()) {
+            return rng.gen_range(0..population.len());
+        }
+        let r: f64 = rng.gen_range(0f64..=total);
+        // The first chromosome whose running sum exceeds the point, the last one at the end of the wheel
+        return cumulative.partition_point(|&sum| sum <= r).min(population.len() - 1);
+    }
+}
+
+// The better of two random chromosomes
+#[derive(Clone, Copy, Debug)]
+pub struct TournamentSelection;
+
+impl<G> SelectionStrategy<G> for TournamentSelection {
+    fn name(&self) -> &'static str {
+        return "tournament";
+    }
+
+    fn needs_sorted(&self) -> bool {
+        return false;
+    }
+
+    fn select(&self, chromosomes: &[Chromosome<G>], _total_fitness: f64, comparison: Comparison, rng: &mut Pcg64) -> usize {
+        let (index, _) = core::tournament_pick(chromosomes, comparison, rng);
+        return index;
+    }
+}
+
+// Same odds as the roulette, every parent drawn in constant time from an alias table built once per generation
+#[derive(Clone, Copy, Debug)]
+pub struct AliasSelection;
+
+impl<G> SelectionStrategy<G> for AliasSelection {
+    fn name(&self) -> &'static str {
+        return "alias";
+    }
+
+    fn needs_sorted(&self) -> bool {
+        return false;
+    }
+
+    fn select(&self, chromosomes: &[Chromosome<G>], total_fitness: f64, _comparison: Comparison, rng: &mut Pcg64) -> usize {
+        let (index, _) = core::roulette_pick(chromosomes, total_fitness, rng);
+        return index;
+    }
+
+    fn select_from(&self, population: &Population<G>, _comparison: Comparison, rng: &mut Pcg64) -> usize {
+        return population.alias_table().sample(population.len(), rng);
+    }
+
+    fn prepare(&self, population: &mut Population<G>) {
+        population.build_alias_table();
+    }
+}
+
+// Walker's alias method: column i is kept with its probability, otherwise its alias is taken
+#[derive(Clone, Debug, Default)]
+pub struct AliasTable {
+    probability: Vec<f64>,
+    alias: Vec<u32>,
+    // Reused between builds
+    small: Vec<u32>,
+    large: Vec<u32>,
+}
+
+impl AliasTable {
+    // Negative weights count as zero, an empty table is left when the weights do not sum to a positive number
+    pub fn build(&mut self, weights: impl ExactSizeIterator<Item = f64> + Clone) {
+        self.probability.clear();
+        self.alias.clear();
+        let n = weights.len();
+        let total: f64 = weights.clone().map(|w| w.max(0.0)).sum();
+        if n == 0 || !(total > 0.0 && total.is_finite()) {
+            return;
+        }
+        self.probability.extend(weights.map(|w| w.max(0.0) * n as f64 / total));
+        self.alias.extend(0..n as u32);
+        self.small.clear();
+        self.large.clear();
+        for (i, &p) in self.probability.iter().enumerate() {
+            if p < 1.0 { self.small.push(i as u32) } else { self.large.push(i as u32) }
+        }
+        while let (Some(&less), Some(&more)) = (self.small.last(), self.large.last()) {
+            self.small.pop();
+            self.alias[less as usize] = more;
+            let rest = (self.probability[more as usize] + self.probability[less as usize]) - 1.0;
+            self.probability[more as usize] = rest;
+            if rest < 1.0 {
+                self.large.pop();
+                self.small.push(more);
+            }
+        }
+        // Only rounding errors are left over
+        for &i in self.small.iter().chain(&self.large) {
+            self.probability[i as usize] = 1.0;
+        }
+    }
+
+    // Index below len, uniform when the table is empty
+    pub fn sample(&self, len: usize, rng: &mut Pcg64) -> usize {
+        if self.probability.is_empty() {
+            return rng.gen_range(0..len);
+        }
+        let column = rng.gen_range(0..self.probability.len());
+        if rng.gen::<f64>() < self.probability[column] {
+            return column;
+        }
+        return self.alias[column] as usize;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::scaling::FitnessScaling;
+
+    const DRAWS: usize = 100_000;
+
+    fn population(weights: &[f64]) -> Population<()> {
+        let mut population: Population<()> = Population::new(weights.iter().map(|&fitness| {
+            let mut chromosome = Chromosome::with_genes(());
+            chromosome.fitness = fitness;
+            chromosome
+        }).collect());
+        population.scale(FitnessScaling::None, Comparison::Fitness);
+        return population;
+    }
+
+    // Share of the draws which picked each chromosome
+    fn frequencies(strategy: &dyn SelectionStrategy<()>, weights: &[f64], seed: u64) -> Vec<f64> {
+        let mut population = population(weights);
+        strategy.prepare(&mut population);
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut counts: Vec<usize> = vec![0; weights.len()];
+        for _ in 0..DRAWS {
+            counts[strategy.select_from(&population, Comparison::Fitness, &mut rng)] += 1;
+        }
+        return counts.into_iter().map(|count| count as f64 / DRAWS as f64).collect();
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 0.01, "{:?} is not close to {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn alias_matches_roulette() {
+        let weights = [1.0, 2.0, 3.0, 4.0, 0.5, 9.5];
+        let expected: Vec<f64> = weights.iter().map(|w| w / 20.0).collect();
+        let roulette = frequencies(&RouletteSelection, &weights, 7);
+        let alias = frequencies(&AliasSelection, &weights, 7);
+        assert_close(&roulette, &expected);
+        assert_close(&alias, &expected);
+        assert_close(&alias, &roulette);
+    }
+
+    #[test]
+    fn zero_weights_are_uniform() {
+        let weights = [0.0, 0.0, 0.0, 0.0];
+        assert_close(&frequencies(&RouletteSelection, &weights, 1), &[0.25; 4]);
+        assert_close(&frequencies(&AliasSelection, &weights, 1), &[0.25; 4]);
+    }
+
+    #[test]
+    fn negative_and_nan_weights_count_as_zero() {
+        for weights in [[-5.0, 1.0, 3.0], [f64::NAN, 1.0, 3.0]] {
+            for strategy in [&RouletteSelection as &dyn SelectionStrategy<()>, &AliasSelection] {
+                let frequencies = frequencies(strategy, &weights, 3);
+                assert_eq!(frequencies[0], 0.0, "{} picked a chromosome of weight {}", strategy.name(), weights[0]);
+                assert_close(&frequencies, &[0.0, 0.25, 0.75]);
+            }
+        }
+    }
+
+    #[test]
+    fn infinite_weights_are_uniform() {
+        let weights = [f64::INFINITY, 1.0];
+        assert_close(&frequencies(&RouletteSelection, &weights, 5), &[0.5, 0.5]);
+        assert_close(&frequencies(&AliasSelection, &weights, 5), &[0.5, 0.5]);
+    }
+
+    #[test]
+    fn empty_alias_table_samples_uniformly() {
+        let mut table = AliasTable::default();
+        table.build([0.0, -1.0].into_iter());
+        let mut rng = Pcg64::seed_from_u64(11);
+        assert!((0..100).all(|_| table.sample(3, &mut rng) < 3));
+    }
+}
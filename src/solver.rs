@@ -0,0 +1,252 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::core::{Direction, Position};
+use crate::distance::{self, TreasureDistances, UNREACHABLE};
+use crate::map::GameArea;
+use crate::vm::{Termination, VmResult};
+
+// Larger maps fall back to a nearest neighbour tour improved by 2-opt
+pub const EXACT_TREASURE_LIMIT: usize = 16;
+
+// Shortest known walk from the start collecting every treasure
+pub struct Tour {
+    // Treasures in the order they are collected
+    pub order: Vec<(usize, usize)>,
+    pub steps: String,
+    pub trajectory: Vec<(usize, usize)>,
+    // Whether the tour is proven to be the shortest one
+    pub exact: bool,
+}
+
+impl Tour {
+    // Result of a machine walking the tour without any wasted instruction
+    pub fn ideal_result(&self) -> VmResult {
+        let (player_x, player_y) = *self.trajectory.last().unwrap();
+        return VmResult {
+            iterations: self.steps.len() as u32,
+            found_treasures: self.order.len() as u32,
+            steps: self.steps.clone(),
+            termination: Termination::AllTreasures,
+            player: Position::new(player_x as isize, player_y as isize),
+            collected_treasures: self.order.clone(),
+            executed_instructions: 0,
+            trajectory: self.trajectory.clone(),
+        };
+    }
+}
+
+// None when some treasure cannot be reached
+pub fn shortest_tour(game_area: &GameArea) -> Option<Tour> {
+    let start = game_area.player().tile();
+    let distances = TreasureDistances::new(game_area);
+    let n = distances.treasures.len();
+    let from_start: Vec<u32> = (0..n).map(|treasure| distances.distance(treasure, start.0, start.1)).collect();
+    let between: Vec<Vec<u32>> = distances.treasures.iter()
+        .map(|&(x, y)| (0..n).map(|treasure| distances.distance(treasure, x, y)).collect())
+        .collect();
+    let (order, exact) = treasure_order(&from_start, &between)?;
+
+    let mut steps: String = String::new();
+    let mut trajectory: Vec<(usize, usize)> = vec![start];
+    let mut position = start;
+    for &treasure in &order {
+        let target = distances.treasures[treasure];
+        walk(game_area, position, target, &mut steps, &mut trajectory);
+        position = target;
+    }
+    return Some(Tour {
+        order: order.iter().map(|&treasure| distances.treasures[treasure]).collect(),
+        steps,
+        trajectory,
+        exact,
+    });
+}
+
+// Order of the treasures and whether it is the shortest one, None when some treasure cannot be reached
+fn treasure_order(from_start: &[u32], between: &[Vec<u32>]) -> Option<(Vec<usize>, bool)> {
+    if from_start.contains(&UNREACHABLE) || between.iter().any(|row| row.contains(&UNREACHABLE)) {
+        return None;
+    }
+    let exact = from_start.len() <= EXACT_TREASURE_LIMIT;
+    let order: Vec<usize> = if exact {
+        held_karp(from_start, between)
+    } else {
+        two_opt(nearest_neighbour(from_start, between), from_start, between)
+    };
+    return Some((order, exact));
+}
+
+// Dynamic programming over subsets of treasures, the tour does not return to the start
+fn held_karp(from_start: &[u32], between: &[Vec<u32>]) -> Vec<usize> {
+    let n = from_start.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let subsets = 1usize << n;
+    let mut cost: Vec<Vec<u32>> = vec![vec![UNREACHABLE; n]; subsets];
+    let mut parent: Vec<Vec<usize>> = vec![vec![usize::MAX; n]; subsets];
+    for last in 0..n {
+        cost[1 << last][last] = from_start[last];
+    }
+    for mask in 1..subsets {
+        for last in 0..n {
+            if mask & (1 << last) == 0 || cost[mask][last] == UNREACHABLE {
+                continue;
+            }
+            for next in 0..n {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                let next_mask = mask | (1 << next);
+                let next_cost = cost[mask][last] + between[last][next];
+                if next_cost < cost[next_mask][next] {
+                    cost[next_mask][next] = next_cost;
+                    parent[next_mask][next] = last;
+                }
+            }
+        }
+    }
+
+    let full = subsets - 1;
+    let mut last = (0..n).min_by_key(|&last| cost[full][last]).unwrap();
+    let mut mask = full;
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+    loop {
+        order.push(last);
+        let previous = parent[mask][last];
+        mask &= !(1 << last);
+        if previous == usize::MAX {
+            break;
+        }
+        last = previous;
+    }
+    order.reverse();
+    return order;
+}
+
+fn nearest_neighbour(from_start: &[u32], between: &[Vec<u32>]) -> Vec<usize> {
+    let n = from_start.len();
+    let mut visited: Vec<bool> = vec![false; n];
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+    let mut current: Option<usize> = None;
+    for _ in 0..n {
+        let next = (0..n).filter(|&next| !visited[next])
+            .min_by_key(|&next| current.map_or(from_start[next], |current| between[current][next]))
+            .unwrap();
+        visited[next] = true;
+        order.push(next);
+        current = Some(next);
+    }
+    return order;
+}
+
+// Reverses segments of the tour as long as it gets shorter
+fn two_opt(mut order: Vec<usize>, from_start: &[u32], between: &[Vec<u32>]) -> Vec<usize> {
+    let length = |order: &[usize]| -> u32 {
+        let mut total: u32 = order.first().map_or(0, |&first| from_start[first]);
+        for pair in order.windows(2) {
+            total += between[pair[0]][pair[1]];
+        }
+        return total;
+    };
+    let mut best = length(&order);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..order.len() {
+            for j in i + 1..order.len() {
+                order[i..=j].reverse();
+                let candidate = length(&order);
+                if candidate < best {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+    }
+    return order;
+}
+
+// Follows the distance field of the target down to zero
+fn walk(game_area: &GameArea, from: (usize, usize), to: (usize, usize),
+        steps: &mut String, trajectory: &mut Vec<(usize, usize)>) {
+    let field = distance::distance_field(game_area, to);
+    let columns = game_area.width();
+    let (mut x, mut y) = from;
+    while (x, y) != to {
+        for direction in Direction::ALL {
+            let next = Position::new(x as isize, y as isize).step(direction);
+            let (nx, ny) = next.tile();
+            if game_area.contains(next) && field[ny * columns + nx] < field[y * columns + x] {
+                x = nx;
+                y = ny;
+                steps.push(direction.glyph());
+                trajectory.push((x, y));
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map;
+
+    fn length(order: &[usize], from_start: &[u32], between: &[Vec<u32>]) -> u32 {
+        return from_start[order[0]] + order.windows(2).map(|pair| between[pair[0]][pair[1]]).sum::<u32>();
+    }
+
+    #[test]
+    fn shortest_tour_of_a_small_map() {
+        // Down to the lower treasure first, then back up and along the row: 2 + 3 + 3 steps.
+        // The row first, which is closer, costs 1 + 3 + 6.
+        let game_area = map::parse(". . . . .\nP T . . T\n. . . . .\nT . . . .").unwrap();
+        let tour = shortest_tour(&game_area).unwrap();
+        assert!(tour.exact);
+        assert_eq!(tour.steps.len(), 8);
+        assert_eq!(tour.order, [(0, 3), (1, 1), (4, 1)]);
+        assert_eq!(tour.trajectory.len(), 9);
+        assert_eq!(tour.trajectory.last(), Some(&(4, 1)));
+        for pair in tour.trajectory.windows(2) {
+            assert_eq!(pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1), 1);
+        }
+        let result = tour.ideal_result();
+        assert_eq!((result.found_treasures, result.iterations), (3, 8));
+    }
+
+    #[test]
+    fn held_karp_finds_the_shortest_order() {
+        // Treasures on a line at 1, 3, 6 and 10 with the start at 4: the left ones first take 1 + 2 + 9 steps,
+        // the right ones first 2 + 4 + 9
+        let positions: [u32; 4] = [1, 3, 6, 10];
+        let from_start: Vec<u32> = positions.iter().map(|&x| x.abs_diff(4)).collect();
+        let between: Vec<Vec<u32>> = positions.iter().map(|&a| positions.iter().map(|&b| a.abs_diff(b)).collect()).collect();
+        let (order, exact) = treasure_order(&from_start, &between).unwrap();
+        assert!(exact);
+        assert_eq!(order, [1, 0, 2, 3]);
+        assert_eq!(length(&order, &from_start, &between), 12);
+    }
+
+    #[test]
+    fn unreachable_treasures_have_no_tour() {
+        let from_start: Vec<u32> = vec![1, UNREACHABLE];
+        let between: Vec<Vec<u32>> = vec![vec![0, UNREACHABLE], vec![UNREACHABLE, 0]];
+        assert!(treasure_order(&from_start, &between).is_none());
+        // Reachable from the start but not from the other treasure
+        let from_start: Vec<u32> = vec![1, 2];
+        assert!(treasure_order(&from_start, &between).is_none());
+    }
+
+    #[test]
+    fn many_treasures_get_an_approximate_tour() {
+        let row: String = (0..=EXACT_TREASURE_LIMIT).map(|_| "T ").collect();
+        let game_area = map::parse(&format!("P {}", row.trim_end())).unwrap();
+        let tour = shortest_tour(&game_area).unwrap();
+        assert!(!tour.exact);
+        // Walking along the row is found anyway
+        assert_eq!(tour.steps.len(), EXACT_TREASURE_LIMIT + 1);
+    }
+}
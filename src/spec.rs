@@ -0,0 +1,91 @@
+use alloc::{format, string::String, vec};
+
+use serde::Deserialize;
+
+use crate::core::{self, Selection};
+use crate::engine::{Engine, GaConfig};
+use crate::ensemble::Case;
+use crate::error::Error;
+use crate::fitness::{self, StepPenaltyFitness};
+use crate::map::{self, GameArea};
+use crate::vm::{InstructionSet, VmConfig};
+
+// Settings of a search on one map by name, for callers in other languages (C API, Python, JSON).
+// Every field is optional, unknown fields are rejected.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineSpec {
+    // Text form of the map, the built-in map unless given
+    pub map: Option<String>,
+    pub fitness: String,
+    pub subjects: usize,
+    pub mutation_probability: f64,
+    pub selection: String,
+    pub elitism: usize,
+    pub instruction_set: String,
+    pub iteration_limit: u32,
+    pub memory_size: usize,
+    pub seed: u64,
+}
+
+impl Default for EngineSpec {
+    fn default() -> Self {
+        let ga_config = GaConfig::default();
+        let vm_config = VmConfig::default();
+        return EngineSpec {
+            map: None,
+            fitness: String::from("steps"),
+            subjects: ga_config.subjects,
+            mutation_probability: ga_config.mutation_probability,
+            selection: String::from("roulette"),
+            elitism: ga_config.elitism,
+            instruction_set: String::from("classic"),
+            iteration_limit: vm_config.iteration_limit,
+            memory_size: vm_config.memory_size,
+            seed: 0,
+        };
+    }
+}
+
+impl EngineSpec {
+    pub fn game_area(&self) -> Result<GameArea, Error> {
+        return match &self.map {
+            Some(text) => map::parse(text),
+            None => Ok(core::build_game_area()),
+        };
+    }
+
+    // The machine seed is left at zero, the engine draws its own
+    pub fn vm_config(&self) -> Result<VmConfig, Error> {
+        let Some(instruction_set) = InstructionSet::parse(&self.instruction_set) else {
+            return Err(Error::InvalidConfig(format!("unknown instruction set '{}'", self.instruction_set)));
+        };
+        return Ok(VmConfig::builder()
+            .iteration_limit(self.iteration_limit)
+            .memory_size(self.memory_size)
+            .instruction_set(instruction_set)
+            .build());
+    }
+
+    // Without a generation budget, the caller steps it
+    pub fn build(&self) -> Result<Engine<'static>, Error> {
+        let game_area = self.game_area()?;
+        let Some(selection) = Selection::parse(&self.selection) else {
+            return Err(Error::InvalidConfig(format!("unknown selection '{}'", self.selection)));
+        };
+        let Some(fitness_function) = fitness::by_name(&self.fitness, StepPenaltyFitness::for_map(&game_area)) else {
+            return Err(Error::InvalidConfig(format!("unknown fitness function '{}', expected one of {}",
+                                                    self.fitness, fitness::FITNESS_FUNCTIONS.join(", "))));
+        };
+        let config = GaConfig::builder()
+            .subjects(self.subjects)
+            .mutation_probability(self.mutation_probability)
+            .selection(selection.strategy())
+            .elitism(self.elitism)
+            .vm(self.vm_config()?)
+            .generations(u32::MAX)
+            .build();
+        let cases = vec![Case { name: String::from("map"), game_area }];
+        return Engine::owned(config, fitness_function, cases, self.seed);
+    }
+}
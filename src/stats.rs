@@ -0,0 +1,286 @@
+use ::core::fmt;
+use ::core::time::Duration;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "std")]
+use std::io::Write;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Chromosome, Genome};
+
+// Summary of one evaluated generation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub generation: u32,
+    pub best_fitness: f64,
+    pub mean_fitness: f64,
+    pub worst_fitness: f64,
+    pub median_fitness: f64,
+    pub fitness_std_dev: f64,
+    // Mean Hamming distance between two chromosomes in bits
+    pub diversity: f64,
+    pub best_treasures: u32,
+    // Steps of the fittest chromosome
+    pub best_steps: usize,
+}
+
+impl GenerationStats {
+    pub fn of<G: Genome>(generation: u32, chromosomes: &[Chromosome<G>]) -> GenerationStats {
+        let count = chromosomes.len().max(1) as f64;
+        let mean_fitness = chromosomes.iter().map(|c| c.fitness).sum::<f64>() / count;
+        let variance = chromosomes.iter().map(|c| (c.fitness - mean_fitness) * (c.fitness - mean_fitness)).sum::<f64>() / count;
+        let mut sorted: Vec<f64> = chromosomes.iter().map(|c| c.fitness).collect();
+        sorted.sort_by(f64::total_cmp);
+        let median_fitness = match sorted.len() {
+            0 => 0.0,
+            n if n % 2 == 0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+            n => sorted[n / 2],
+        };
+        let fittest = chromosomes.iter().max_by(|a, b| a.fitness.total_cmp(&b.fitness));
+        return GenerationStats {
+            generation,
+            best_fitness: chromosomes.iter().map(|c| c.fitness).fold(f64::NEG_INFINITY, f64::max),
+            mean_fitness,
+            worst_fitness: chromosomes.iter().map(|c| c.fitness).fold(f64::INFINITY, f64::min),
+            median_fitness,
+            fitness_std_dev: libm::sqrt(variance),
+            diversity: diversity(chromosomes),
+            best_treasures: chromosomes.iter().map(|c| c.found_treasures).max().unwrap_or(0),
+            best_steps: fittest.map_or(0, |c| c.steps.len()),
+        };
+    }
+}
+
+impl fmt::Display for GenerationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Generation {}: fitness min {:.4}, mean {:.4}, median {:.4}, max {:.4}, std dev {:.4}, \
+                          best treasures {}, best steps {}, diversity {:.2} bits",
+                      self.generation, self.worst_fitness, self.mean_fitness, self.median_fitness, self.best_fitness,
+                      self.fitness_std_dev, self.best_treasures, self.best_steps, self.diversity);
+    }
+}
+
+// Offspring of one generation fitter than the better of their parents, by whether they were mutated.
+// Elites and genomes the engine did not breed (random, seeded or restored) are not counted. Mutated children are told
+// by the mask of changed bits, those of genomes without one (see MutationOperator) count as only crossed over.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OperatorStats {
+    // Only crossed over
+    pub crossover_offspring: u32,
+    pub crossover_improved: u32,
+    pub mutation_offspring: u32,
+    pub mutation_improved: u32,
+}
+
+impl OperatorStats {
+    pub fn crossover_success_rate(&self) -> Option<f64> {
+        return rate(self.crossover_improved, self.crossover_offspring);
+    }
+
+    pub fn mutation_success_rate(&self) -> Option<f64> {
+        return rate(self.mutation_improved, self.mutation_offspring);
+    }
+
+    // Of every child
+    pub fn success_rate(&self) -> Option<f64> {
+        return rate(self.crossover_improved + self.mutation_improved, self.crossover_offspring + self.mutation_offspring);
+    }
+}
+
+fn rate(improved: u32, offspring: u32) -> Option<f64> {
+    return Some(f64::from(improved) / f64::from(offspring)).filter(|_| offspring > 0);
+}
+
+// Work of one generation and where its time went, the times are zero without the std feature
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Throughput {
+    pub evaluations: u64,
+    // Machine iterations of the evaluated programs, of the reported case when there are several
+    pub iterations: u64,
+    pub evaluation_time: Duration,
+    // Sorting, scaling and choosing parents
+    pub selection_time: Duration,
+    // Crossover and mutation
+    pub reproduction_time: Duration,
+    // Evaluations given up as they could not beat the solution, and the iterations they did not run
+    pub bounded: u64,
+    pub skipped_iterations: u64,
+}
+
+impl Throughput {
+    pub fn evaluations_per_second(&self) -> f64 {
+        return self.evaluations as f64 / self.evaluation_time.as_secs_f64().max(1e-9);
+    }
+
+    pub fn iterations_per_second(&self) -> f64 {
+        return self.iterations as f64 / self.evaluation_time.as_secs_f64().max(1e-9);
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} evaluations/s, {:.0} iterations/s, evaluation {:.3} ms, selection {:.3} ms, reproduction {:.3} ms",
+               self.evaluations_per_second(), self.iterations_per_second(), self.evaluation_time.as_secs_f64() * 1000.0,
+               self.selection_time.as_secs_f64() * 1000.0, self.reproduction_time.as_secs_f64() * 1000.0)?;
+        if self.bounded > 0 {
+            write!(f, ", {} evaluations bounded ({} iterations skipped)", self.bounded, self.skipped_iterations)?;
+        }
+        return Ok(());
+    }
+}
+
+// Measures the parts of a generation, always zero without the std feature
+#[derive(Clone, Copy)]
+pub struct Stopwatch {
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub fn start() -> Stopwatch {
+        return Stopwatch {
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        };
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        return self.start.elapsed();
+        #[cfg(not(feature = "std"))]
+        return Duration::ZERO;
+    }
+}
+
+// Every bit contributes ones * zeros differing pairs, linear in the population size.
+// Bytes beyond the shortest genome are not compared.
+pub fn diversity<G: Genome>(chromosomes: &[Chromosome<G>]) -> f64 {
+    let n = chromosomes.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let length = chromosomes.iter().map(|c| c.genes.bytes().len()).min().unwrap_or(0);
+    let mut differing: u64 = 0;
+    for index in 0..length {
+        for bit in 0..8 {
+            let ones = chromosomes.iter().filter(|c| c.genes.bytes()[index] & (1 << bit) != 0).count() as u64;
+            differing += ones * (n as u64 - ones);
+        }
+    }
+    return differing as f64 / (n * (n - 1) / 2) as f64;
+}
+
+// Mann-Whitney U test of two independent samples, e.g. the best fitness of repeated runs of two configurations
+#[derive(Clone, Copy, Debug)]
+pub struct MannWhitney {
+    // Pairs of one value of each sample in which the first is larger, ties count half
+    pub u: f64,
+    // Two-sided, normal approximation with tie and continuity correction
+    pub p_value: f64,
+    // Rank-biserial correlation 2U / (n1 n2) - 1, from -1 to 1 and positive when the first sample tends to be larger
+    pub effect_size: f64,
+}
+
+// None unless both samples have a value, infinities rank above every finite value and NaN is left out
+pub fn mann_whitney(first: &[f64], second: &[f64]) -> Option<MannWhitney> {
+    let first: Vec<f64> = first.iter().copied().filter(|value| !value.is_nan()).collect();
+    let second: Vec<f64> = second.iter().copied().filter(|value| !value.is_nan()).collect();
+    if first.is_empty() || second.is_empty() {
+        return None;
+    }
+    let mut values: Vec<(f64, bool)> = first.iter().map(|&value| (value, true)).chain(second.iter().map(|&value| (value, false))).collect();
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let n = values.len() as f64;
+    // Tied values share the mean of their ranks
+    let mut first_ranks: f64 = 0.0;
+    let mut ties: f64 = 0.0;
+    let mut start = 0;
+    while start < values.len() {
+        let end = start + values[start..].iter().take_while(|value| value.0 == values[start].0).count();
+        let tied = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0;
+        first_ranks += rank * values[start..end].iter().filter(|value| value.1).count() as f64;
+        ties += tied * tied * tied - tied;
+        start = end;
+    }
+    let (n1, n2) = (first.len() as f64, second.len() as f64);
+    let u = first_ranks - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)).max(1.0));
+    let p_value = if variance > 0.0 {
+        let z = ((u - mean).abs() - 0.5).max(0.0) / libm::sqrt(variance);
+        libm::erfc(z / ::core::f64::consts::SQRT_2).min(1.0)
+    } else {
+        1.0
+    };
+    return Some(MannWhitney { u, p_value, effect_size: 2.0 * u / (n1 * n2) - 1.0 });
+}
+
+// Appends one row per generation, the header is written to new files only
+#[cfg(feature = "std")]
+pub struct CsvLog {
+    file: File,
+}
+
+#[cfg(feature = "std")]
+impl CsvLog {
+    pub fn open(path: &Path) -> std::io::Result<CsvLog> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "generation,best_fitness,mean_fitness,worst_fitness,diversity,best_treasures,median_fitness,fitness_std_dev,best_steps")?;
+        }
+        return Ok(CsvLog { file });
+    }
+
+    pub fn write(&mut self, stats: &GenerationStats) -> std::io::Result<()> {
+        return writeln!(self.file, "{},{},{},{},{:.4},{},{},{},{}", stats.generation, stats.best_fitness, stats.mean_fitness,
+                        stats.worst_fitness, stats.diversity, stats.best_treasures, stats.median_fitness, stats.fitness_std_dev,
+                        stats.best_steps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mann_whitney_with_ties() {
+        // Ranks of the first sample 1, 3, 3, 6.5, 8 and 9
+        let test = mann_whitney(&[1.1, 2.3, 2.3, 4.0, 5.5, 6.1], &[2.3, 3.0, 4.0, 7.2, 8.1]).unwrap();
+        assert_eq!(test.u, 9.5);
+        // z = (|9.5 - 15| - 0.5) / sqrt(30 / 12 * (12 - 30 / 110))
+        assert!((test.p_value - 0.355_785_924).abs() < 1e-6, "p {}", test.p_value);
+        assert!((test.effect_size - (2.0 * 9.5 / 30.0 - 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn mann_whitney_separated_samples() {
+        let test = mann_whitney(&[6.0, 7.0, 8.0, 9.0, 10.0], &[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        assert_eq!(test.u, 25.0);
+        assert!((test.p_value - 0.012_185_780).abs() < 1e-6, "p {}", test.p_value);
+        assert_eq!(test.effect_size, 1.0);
+        // Infinities, e.g. runs without a solution, rank above every finite value
+        let test = mann_whitney(&[f64::INFINITY, f64::INFINITY], &[1.0, 2.0]).unwrap();
+        assert_eq!(test.u, 4.0);
+    }
+
+    #[test]
+    fn mann_whitney_leaves_out_nan() {
+        let test = mann_whitney(&[f64::NAN, 6.0, 7.0, f64::NAN], &[1.0, f64::NAN, 2.0]).unwrap();
+        assert_eq!(test.u, 4.0);
+        assert_eq!(test.effect_size, 1.0);
+        assert!(mann_whitney(&[f64::NAN], &[1.0]).is_none());
+    }
+
+    #[test]
+    fn mann_whitney_needs_both_samples() {
+        assert!(mann_whitney(&[], &[1.0]).is_none());
+        assert!(mann_whitney(&[1.0], &[]).is_none());
+        let test = mann_whitney(&[2.0, 2.0], &[2.0]).unwrap();
+        assert_eq!((test.u, test.p_value, test.effect_size), (1.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,165 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::report::BestSummary;
+use crate::stats::GenerationStats;
+
+// What the endpoints answer, updated by the search after every generation
+#[derive(Serialize, Debug, Default)]
+pub struct Status {
+    pub seed: u64,
+    pub map_hash: String,
+    pub generation: u32,
+    // Absent once the search runs without a generation budget
+    pub target_generations: Option<u32>,
+    pub evaluations: u64,
+    // Of the last evaluated generation
+    pub evaluations_per_second: f64,
+    pub elapsed_seconds: f64,
+    // Requested over the control port
+    pub restarts: u32,
+    pub best: Option<BestSummary>,
+    // Of the last evaluated generation
+    pub stats: Option<GenerationStats>,
+    pub finished: bool,
+    pub stop_requested: bool,
+}
+
+#[cfg(not(feature = "metrics"))]
+pub const HELP: &str = "GET /status, /generation, /best or /stats, POST /stop";
+#[cfg(feature = "metrics")]
+pub const HELP: &str = "GET /status, /generation, /best, /stats or /metrics, POST /stop";
+
+// Answers HTTP requests with JSON on its own threads, e.g. `curl 127.0.0.1:8080/status`:
+//   GET /status      everything below at once
+//   GET /generation  generation, target, evaluations and elapsed time
+//   GET /best        best solution so far
+//   GET /stats       statistics of the last evaluated population
+//   GET /metrics     counters and gauges in the Prometheus text format, with the metrics feature
+//   POST /stop       ends the search after the current generation
+pub struct StatusServer {
+    status: Arc<Mutex<Status>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl StatusServer {
+    pub fn listen(address: &str, status: Status) -> std::io::Result<StatusServer> {
+        let listener = TcpListener::bind(address)?;
+        let status = Arc::new(Mutex::new(status));
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = StatusServer { status: status.clone(), stop: stop.clone() };
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (status, stop) = (status.clone(), stop.clone());
+                std::thread::spawn(move || serve(stream, &status, &stop));
+            }
+        });
+        return Ok(server);
+    }
+
+    pub fn update(&self, update: impl FnOnce(&mut Status)) {
+        update(&mut self.status.lock().unwrap());
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        return self.stop.load(Ordering::SeqCst);
+    }
+}
+
+// One request per connection, its body is ignored
+fn serve(stream: TcpStream, status: &Mutex<Status>, stop: &AtomicBool) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {}
+        }
+    }
+    let mut words = request_line.split_whitespace();
+    let method = words.next().unwrap_or("");
+    let path = words.next().unwrap_or("").split('?').next().unwrap_or("");
+    let (code, content_type, body) = respond(method, path, status, stop);
+    let reason = match code {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    write!(writer, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           code, reason, content_type, body.len(), body).ok();
+}
+
+const JSON: &str = "application/json";
+
+// Status code, content type and body
+fn respond(method: &str, path: &str, status: &Mutex<Status>, stop: &AtomicBool) -> (u16, &'static str, String) {
+    let mut status = status.lock().unwrap();
+    let body = match (method, path) {
+        ("GET", "/" | "/status") => serde_json::to_value(&*status),
+        ("GET", "/generation") => Ok(serde_json::json!({
+            "generation": status.generation,
+            "target_generations": status.target_generations,
+            "evaluations": status.evaluations,
+            "evaluations_per_second": status.evaluations_per_second,
+            "elapsed_seconds": status.elapsed_seconds,
+            "finished": status.finished,
+        })),
+        ("GET", "/best") => serde_json::to_value(&status.best),
+        ("GET", "/stats") => serde_json::to_value(&status.stats),
+        #[cfg(feature = "metrics")]
+        ("GET", "/metrics") => return (200, "text/plain; version=0.0.4", metrics(&status)),
+        ("POST", "/stop") => {
+            stop.store(true, Ordering::SeqCst);
+            status.stop_requested = true;
+            Ok(serde_json::json!({ "stop_requested": true }))
+        }
+        #[cfg(feature = "metrics")]
+        (_, "/metrics") => return (405, JSON, serde_json::json!({ "error": HELP }).to_string()),
+        (_, "/" | "/status" | "/generation" | "/best" | "/stats" | "/stop") => {
+            return (405, JSON, serde_json::json!({ "error": HELP }).to_string());
+        }
+        _ => return (404, JSON, serde_json::json!({ "error": HELP }).to_string()),
+    };
+    return match body {
+        Ok(body) => (200, JSON, body.to_string()),
+        Err(e) => (500, JSON, serde_json::json!({ "error": e.to_string() }).to_string()),
+    };
+}
+
+// Prometheus text format, the gauges of the best solution are left out until there is one
+#[cfg(feature = "metrics")]
+fn metrics(status: &Status) -> String {
+    let mut metrics: Vec<(&str, &str, &str, String)> = vec![
+        ("treasure_search_generations_total", "counter", "Generations evaluated", status.generation.to_string()),
+        ("treasure_search_evaluations_total", "counter", "Chromosome evaluations", status.evaluations.to_string()),
+        ("treasure_search_evaluations_per_second", "gauge", "Evaluations per second of the last generation",
+         status.evaluations_per_second.to_string()),
+        ("treasure_search_restarts_total", "counter", "Restarts with a random population", status.restarts.to_string()),
+        ("treasure_search_elapsed_seconds", "gauge", "Time since the search started", status.elapsed_seconds.to_string()),
+        ("treasure_search_finished", "gauge", "1 once the search finished", u8::from(status.finished).to_string()),
+    ];
+    if let Some(best) = &status.best {
+        metrics.push(("treasure_search_best_fitness", "gauge", "Fitness of the best solution so far", best.fitness.to_string()));
+        metrics.push(("treasure_search_best_treasures", "gauge", "Treasures found by the best solution so far",
+                      best.found_treasures.to_string()));
+    }
+    let mut text = format!("# HELP treasure_search_info Seed and map of the search\n# TYPE treasure_search_info gauge\n\
+                            treasure_search_info{{seed=\"{}\",map_hash=\"{}\"}} 1\n", status.seed, status.map_hash);
+    for (name, kind, help, value) in metrics {
+        text += &format!("# HELP {} {}\n# TYPE {} {}\n{} {}\n", name, help, name, kind, name, value);
+    }
+    return text;
+}
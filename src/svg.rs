@@ -0,0 +1,56 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::core::{AREA_TILE_PLAYER, AREA_TILE_TREASURE};
+use crate::map::GameArea;
+
+const CELL_SIZE: usize = 40;
+
+fn center(coordinate: usize) -> usize {
+    return coordinate * CELL_SIZE + CELL_SIZE / 2;
+}
+
+// The map with the path drawn through it, collected treasures numbered in the order of pickup
+pub fn render_path(game_area: &GameArea, trajectory: &[(usize, usize)], collected: &[(usize, usize)]) -> String {
+    let width = game_area.width() * CELL_SIZE;
+    let height = game_area.height() * CELL_SIZE;
+    let mut svg: String = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif" text-anchor="middle">"#,
+             width, height).unwrap();
+    writeln!(svg, r##"<rect width="{}" height="{}" fill="#f4f4f4"/>"##, width, height).unwrap();
+    for (y, row) in game_area.rows().enumerate() {
+        for x in 0..row.len() {
+            writeln!(svg, r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#d0d0d0"/>"##,
+                     x * CELL_SIZE, y * CELL_SIZE, CELL_SIZE, CELL_SIZE).unwrap();
+        }
+    }
+
+    if trajectory.len() > 1 {
+        let points: Vec<String> = trajectory.iter().map(|&(x, y)| format!("{},{}", center(x), center(y))).collect();
+        writeln!(svg, r##"<polyline points="{}" fill="none" stroke="#2f6fdf" stroke-width="4" stroke-linecap="round" stroke-linejoin="round"/>"##,
+                 points.join(" ")).unwrap();
+    }
+
+    for (y, row) in game_area.rows().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            if tile == AREA_TILE_TREASURE {
+                writeln!(svg, r##"<circle cx="{}" cy="{}" r="{}" fill="#f2c12e" stroke="#a07800" stroke-width="2"/>"##,
+                         center(x), center(y), CELL_SIZE / 3).unwrap();
+                if let Some(order) = collected.iter().position(|&treasure| treasure == (x, y)) {
+                    writeln!(svg, r#"<text x="{}" y="{}" font-size="{}" dominant-baseline="central">{}</text>"#,
+                             center(x), center(y), CELL_SIZE / 3, order + 1).unwrap();
+                }
+            } else if tile == AREA_TILE_PLAYER {
+                writeln!(svg, r##"<circle cx="{}" cy="{}" r="{}" fill="#2fa84f"/>"##, center(x), center(y), CELL_SIZE / 3).unwrap();
+                writeln!(svg, r##"<text x="{}" y="{}" font-size="{}" fill="#ffffff" dominant-baseline="central">S</text>"##,
+                         center(x), center(y), CELL_SIZE / 3).unwrap();
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
+pub fn write_path(path: &Path, game_area: &GameArea, trajectory: &[(usize, usize)], collected: &[(usize, usize)]) -> std::io::Result<()> {
+    return std::fs::write(path, render_path(game_area, trajectory, collected));
+}
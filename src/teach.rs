@@ -0,0 +1,83 @@
+use std::io::Write;
+
+use crate::core::{Chromosome, Inheritance, StepAlphabet};
+
+// Waits for Enter so every phase can be discussed before the next one
+pub fn pause(enabled: bool) {
+    if !enabled {
+        return;
+    }
+    print!("Press Enter to continue...");
+    std::io::stdout().flush().ok();
+    std::io::stdin().read_line(&mut String::new()).ok();
+}
+
+pub fn print_heading(generation: u32, phase: &str) {
+    println!("\n=== Generation {}: {} ===", generation, phase);
+}
+
+// Bits of the leading genes, most significant bit first
+pub fn bits(genes: &[u8]) -> String {
+    return genes.iter().map(|gene| format!("{:08b}", gene)).collect::<Vec<String>>().join(" ");
+}
+
+// One line per chromosome with the leading genes bit by bit, best first
+pub fn print_population(chromosomes: &[Chromosome], shown_bytes: usize, step_alphabet: StepAlphabet) {
+    for (i, chromosome) in chromosomes.iter().enumerate() {
+        println!("  #{:<2} {}  fitness {:.4}, treasures {}, steps {} ({})", i, bits(&chromosome.genes[..shown_bytes]),
+                 chromosome.fitness, chromosome.found_treasures, step_alphabet.format(&chromosome.steps), chromosome.steps.len());
+    }
+}
+
+pub fn explain_roulette(chromosomes: &[Chromosome], total_fitness: f64, index: usize, point: Option<f64>) -> String {
+    let Some(point) = point else {
+        return format!("#{} chosen uniformly at random, the fitness sum {:.4} is not positive", index, total_fitness);
+    };
+    let start: f64 = chromosomes[..index].iter().map(|c| c.selection_fitness).sum();
+    let end = start + chromosomes[index].selection_fitness;
+    return format!("#{} chosen, the wheel stopped at {:.4} of {:.4} and #{} owns {:.4}..{:.4} ({:.1}% of the wheel)",
+                   index, point, total_fitness, index, start, end, 100.0 * (end - start) / total_fitness);
+}
+
+pub fn explain_tournament(chromosomes: &[Chromosome], winner: usize, contestants: (usize, usize)) -> String {
+    let (first, second) = contestants;
+    return format!("#{} chosen, it won the tournament of #{} ({:.4}) against #{} ({:.4})", winner,
+                   first, chromosomes[first].selection_fitness, second, chromosomes[second].selection_fitness);
+}
+
+// The leading genes of both parents, the parent every bit was taken from and the result
+pub fn print_crossover(child: usize, parents: (usize, usize), population: &[Chromosome], inheritance: &Inheritance, shown_bytes: usize) {
+    let (parent1, parent2) = parents;
+    let taken: Vec<String> = inheritance.from_parent2[..shown_bytes].iter()
+        .map(|mask| (0..8).rev().map(|bit| if mask >> bit & 1 == 1 { '2' } else { '1' }).collect())
+        .collect();
+    let unmutated: Vec<u8> = inheritance.genes.iter().zip(&inheritance.mutated).map(|(gene, flipped)| gene ^ flipped).collect();
+    let bits_from_parent2: u32 = inheritance.from_parent2.iter().map(|mask| mask.count_ones()).sum();
+    println!("  Child #{} of #{} and #{}: {} bits from #{}, {} from #{}", child, parent1, parent2,
+             inheritance.genes.len() as u32 * 8 - bits_from_parent2, parent1, bits_from_parent2, parent2);
+    println!("    parent #{:<2} {}", parent1, bits(&population[parent1].genes[..shown_bytes]));
+    println!("    parent #{:<2} {}", parent2, bits(&population[parent2].genes[..shown_bytes]));
+    println!("    taken from {}", taken.join(" "));
+    println!("    child      {}", bits(&unmutated[..shown_bytes]));
+}
+
+// Every byte of the child with flipped bits, before and after the mutation
+pub fn print_mutation(child: usize, inheritance: &Inheritance, shown_bytes: usize) {
+    let flips: Vec<String> = inheritance.mutated.iter().enumerate()
+        .filter(|(_, &mask)| mask != 0)
+        .map(|(byte, mask)| format!("byte {} {:08b} -> {:08b}", byte, inheritance.genes[byte] ^ mask, inheritance.genes[byte]))
+        .collect();
+    let flipped_bits: u32 = inheritance.mutated.iter().map(|mask| mask.count_ones()).sum();
+    if flips.is_empty() {
+        println!("  Child #{}: no bits flipped", child);
+        return;
+    }
+    println!("  Child #{}: {} bit{} flipped: {}", child, flipped_bits, if flipped_bits == 1 { "" } else { "s" }, flips.join(", "));
+    let marks: Vec<String> = inheritance.mutated[..shown_bytes].iter()
+        .map(|mask| (0..8).rev().map(|bit| if mask >> bit & 1 == 1 { '^' } else { ' ' }).collect())
+        .collect();
+    if inheritance.mutated[..shown_bytes].iter().any(|&mask| mask != 0) {
+        println!("    mutated    {}", bits(&inheritance.genes[..shown_bytes]));
+        println!("               {}", marks.join(" "));
+    }
+}
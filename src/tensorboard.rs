@@ -0,0 +1,215 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::stats::{GenerationStats, OperatorStats, Throughput};
+
+// Event file TensorBoard reads, e.g. `tensorboard --logdir runs` with a directory per run under runs: TFRecords
+// (length, masked CRC-32C of the length, data, masked CRC-32C of the data) of Event protocol buffers, the first
+// with the file version, then one with the scalars of every generation as its step.
+pub struct EventWriter {
+    writer: BufWriter<File>,
+    path: PathBuf,
+}
+
+impl EventWriter {
+    // A new file in the directory, which is created when missing
+    pub fn create(directory: &Path) -> std::io::Result<EventWriter> {
+        std::fs::create_dir_all(directory)?;
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        let path = directory.join(format!("events.out.tfevents.{}.treasure-search.{}", seconds, std::process::id()));
+        let mut writer = EventWriter { writer: BufWriter::new(File::create(&path)?), path };
+        let mut event: Vec<u8> = Vec::new();
+        field_double(&mut event, 1, wall_time());
+        field_bytes(&mut event, 3, b"brain.Event:2");
+        writer.write_record(&event)?;
+        writer.writer.flush()?;
+        return Ok(writer);
+    }
+
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+
+    // Flushed at once so a running TensorBoard shows the generation
+    pub fn write_generation(&mut self, stats: &GenerationStats, operators: &OperatorStats, throughput: &Throughput) -> std::io::Result<()> {
+        let mut scalars: Vec<(&str, f64)> = vec![
+            ("fitness/best", stats.best_fitness),
+            ("fitness/mean", stats.mean_fitness),
+            ("fitness/median", stats.median_fitness),
+            ("fitness/worst", stats.worst_fitness),
+            ("fitness/std_dev", stats.fitness_std_dev),
+            ("population/diversity", stats.diversity),
+            ("best/treasures", f64::from(stats.best_treasures)),
+            ("best/steps", stats.best_steps as f64),
+            ("throughput/evaluations_per_second", throughput.evaluations_per_second()),
+        ];
+        // Absent for generations without bred offspring, e.g. the first one
+        for (tag, rate) in [("operators/success_rate", operators.success_rate()),
+                            ("operators/crossover_success_rate", operators.crossover_success_rate()),
+                            ("operators/mutation_success_rate", operators.mutation_success_rate())] {
+            if let Some(rate) = rate {
+                scalars.push((tag, rate));
+            }
+        }
+        let mut summary: Vec<u8> = Vec::new();
+        for (tag, value) in scalars {
+            let mut scalar: Vec<u8> = Vec::new();
+            field_bytes(&mut scalar, 1, tag.as_bytes());
+            // simple_value, a float
+            scalar.push(2 << 3 | 5);
+            scalar.extend_from_slice(&(value as f32).to_le_bytes());
+            field_bytes(&mut summary, 1, &scalar);
+        }
+        let mut event: Vec<u8> = Vec::new();
+        field_double(&mut event, 1, wall_time());
+        // step, a varint
+        event.push(2 << 3);
+        varint(&mut event, u64::from(stats.generation));
+        field_bytes(&mut event, 5, &summary);
+        self.write_record(&event)?;
+        return self.writer.flush();
+    }
+
+    fn write_record(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let length = (data.len() as u64).to_le_bytes();
+        self.writer.write_all(&length)?;
+        self.writer.write_all(&masked_crc(&length).to_le_bytes())?;
+        self.writer.write_all(data)?;
+        return self.writer.write_all(&masked_crc(data).to_le_bytes());
+    }
+}
+
+fn wall_time() -> f64 {
+    return SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |elapsed| elapsed.as_secs_f64());
+}
+
+fn varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn field_double(bytes: &mut Vec<u8>, field: u8, value: f64) {
+    bytes.push(field << 3 | 1);
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn field_bytes(bytes: &mut Vec<u8>, field: u8, value: &[u8]) {
+    bytes.push(field << 3 | 2);
+    varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value);
+}
+
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc: u32 = !0;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82F63B78 } else { crc >> 1 };
+        }
+    }
+    return !crc;
+}
+
+// CRC-32C rotated and offset as TFRecord expects
+fn masked_crc(data: &[u8]) -> u32 {
+    return crc32c(data).rotate_right(15).wrapping_add(0xa282ead8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Field number, wire type and value of every field of a protocol buffer, the value of varints in little endian
+    fn fields(mut bytes: &[u8]) -> Vec<(u8, u8, Vec<u8>)> {
+        let read_varint = |bytes: &mut &[u8]| -> u64 {
+            let mut value: u64 = 0;
+            for shift in (0..64).step_by(7) {
+                let byte = bytes[0];
+                *bytes = &bytes[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            return value;
+        };
+        let mut fields: Vec<(u8, u8, Vec<u8>)> = Vec::new();
+        while !bytes.is_empty() {
+            let key = read_varint(&mut bytes) as u8;
+            let length = match key & 7 {
+                0 => {
+                    fields.push((key >> 3, 0, read_varint(&mut bytes).to_le_bytes().to_vec()));
+                    continue;
+                }
+                1 => 8,
+                2 => read_varint(&mut bytes) as usize,
+                5 => 4,
+                wire_type => panic!("unexpected wire type {}", wire_type),
+            };
+            fields.push((key >> 3, key & 7, bytes[..length].to_vec()));
+            bytes = &bytes[length..];
+        }
+        return fields;
+    }
+
+    // Data of every record, checking the lengths and the checksums
+    fn records(mut bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut records: Vec<Vec<u8>> = Vec::new();
+        while !bytes.is_empty() {
+            let length = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+            assert_eq!(u32::from_le_bytes(bytes[8..12].try_into().unwrap()), masked_crc(&bytes[..8]));
+            let data = &bytes[12..12 + length];
+            assert_eq!(u32::from_le_bytes(bytes[12 + length..16 + length].try_into().unwrap()), masked_crc(data));
+            records.push(data.to_vec());
+            bytes = &bytes[16 + length..];
+        }
+        return records;
+    }
+
+    #[test]
+    fn crc32c_check_value() {
+        assert_eq!(crc32c(b"123456789"), 0xE3069283);
+        assert_eq!(masked_crc(b"123456789"), 0xE3069283u32.rotate_right(15).wrapping_add(0xa282ead8));
+    }
+
+    #[test]
+    fn generation_event() {
+        let directory = std::env::temp_dir().join(format!("treasure-search-tensorboard-{}", std::process::id()));
+        let mut writer = EventWriter::create(&directory).unwrap();
+        let stats = GenerationStats {
+            generation: 300,
+            best_fitness: 2.5,
+            mean_fitness: 1.0,
+            worst_fitness: 0.0,
+            median_fitness: 1.0,
+            fitness_std_dev: 0.5,
+            diversity: 12.0,
+            best_treasures: 4,
+            best_steps: 17,
+        };
+        writer.write_generation(&stats, &OperatorStats::default(), &Throughput::default()).unwrap();
+        let bytes = std::fs::read(writer.path()).unwrap();
+        std::fs::remove_dir_all(&directory).ok();
+
+        let records = records(&bytes);
+        assert_eq!(records.len(), 2);
+        assert_eq!(fields(&records[0])[1], (3, 2, b"brain.Event:2".to_vec()));
+        let event = fields(&records[1]);
+        assert_eq!(event[1], (2, 0, 300u64.to_le_bytes().to_vec()));
+        let (field, wire_type, summary) = &event[2];
+        assert_eq!((*field, *wire_type), (5, 2));
+        let scalars: Vec<(String, f32)> = fields(summary).iter().map(|(_, _, value)| {
+            let scalar = fields(value);
+            (String::from_utf8(scalar[0].2.clone()).unwrap(), f32::from_le_bytes(scalar[1].2[..].try_into().unwrap()))
+        }).collect();
+        assert_eq!(scalars[0], (String::from("fitness/best"), 2.5));
+        assert!(scalars.contains(&(String::from("best/steps"), 17.0)));
+        // No offspring were bred, so there are no success rates
+        assert!(scalars.iter().all(|(tag, _)| !tag.starts_with("operators/")));
+    }
+}
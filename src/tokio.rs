@@ -0,0 +1,57 @@
+// Runs an engine on a blocking task of the tokio runtime, for async services
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::core::Genome;
+use crate::engine::{CancellationToken, Engine, GenerationReport, RunResult};
+
+// Reports waiting to be read, the engine waits once the stream falls this far behind
+const REPORT_BUFFER: usize = 64;
+
+// Stream of the reports of every generation, finish() awaits the outcome of run()
+pub struct AsyncRun<G: Genome> {
+    reports: mpsc::Receiver<GenerationReport<G>>,
+    task: JoinHandle<RunResult<G>>,
+    cancellation: CancellationToken,
+}
+
+// Must be called inside of a tokio runtime
+pub fn spawn<G: Genome + 'static>(mut engine: Engine<'static, G>) -> AsyncRun<G> {
+    let (sender, reports) = mpsc::channel(REPORT_BUFFER);
+    let cancellation = engine.cancellation_token();
+    let task = tokio::task::spawn_blocking(move || {
+        // A dropped stream is not read any more, the search goes on
+        return engine.run_with(|report| {
+            sender.blocking_send(report).ok();
+        });
+    });
+    return AsyncRun { reports, task, cancellation };
+}
+
+impl<G: Genome> AsyncRun<G> {
+    // Stops the search before the next generation
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    // Reports not read yet are dropped, a panic of the engine is resumed here
+    pub async fn finish(self) -> RunResult<G> {
+        return match self.task.await {
+            Ok(result) => result,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        };
+    }
+}
+
+impl<G: Genome> Stream for AsyncRun<G> {
+    type Item = GenerationReport<G>;
+
+    // Ends after the last generation
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<GenerationReport<G>>> {
+        return self.reports.poll_recv(cx);
+    }
+}
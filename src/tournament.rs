@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use crate::batch::{Job, JobResult};
+use crate::cli;
+use crate::error::Error;
+
+// Named configurations run on every map with every seed for the same evaluation budget
+pub struct Tournament {
+    pub configs: Vec<(String, Vec<String>)>,
+    // None for the built-in map
+    pub maps: Vec<Option<String>>,
+    pub seeds: Vec<u64>,
+    pub evaluations: u64,
+}
+
+// Options the tournament sets the same for every configuration
+const RESERVED: &[&str] = &["map", "seed", "generations", "max_seconds", "max_evaluations", "until"];
+
+pub fn load(path: &Path) -> Result<Tournament, Error> {
+    let text = std::fs::read_to_string(path)?;
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| Error::Parse(e.to_string()))?;
+    let evaluations = match table.get("evaluations") {
+        Some(toml::Value::Integer(evaluations)) if *evaluations > 0 => *evaluations as u64,
+        _ => return Err(Error::InvalidConfig(String::from("evaluations has to be a positive budget"))),
+    };
+    let maps: Vec<Option<String>> = match table.get("maps") {
+        Some(toml::Value::Array(maps)) if !maps.is_empty() => maps.iter()
+            .map(|map| map.as_str().map(|map| Some(String::from(map))))
+            .collect::<Option<_>>()
+            .ok_or_else(|| Error::InvalidConfig(String::from("maps has to be a list of map files")))?,
+        None => vec![None],
+        _ => return Err(Error::InvalidConfig(String::from("maps has to be a list of map files"))),
+    };
+    let seeds: Vec<u64> = match (table.get("seeds"), table.get("runs")) {
+        (Some(toml::Value::Array(seeds)), None) if !seeds.is_empty() => seeds.iter()
+            .map(|seed| seed.as_integer().and_then(|seed| u64::try_from(seed).ok()))
+            .collect::<Option<_>>()
+            .ok_or_else(|| Error::InvalidConfig(String::from("seeds has to be a list of seeds")))?,
+        (None, Some(toml::Value::Integer(runs))) if *runs > 0 => (0..*runs as u64).collect(),
+        (None, None) => vec![0],
+        _ => return Err(Error::InvalidConfig(String::from("give either seeds, a list of seeds, or runs, a number of seeds from 0"))),
+    };
+    let mut defaults: Vec<String> = Vec::new();
+    if let Some(shared) = table.get("defaults") {
+        let shared = shared.as_table().ok_or_else(|| Error::InvalidConfig(String::from("defaults has to be a table")))?;
+        for (key, value) in shared {
+            push_option(&mut defaults, key, value)?;
+        }
+    }
+    let entries = match table.get("config") {
+        Some(toml::Value::Array(entries)) if entries.len() >= 2 => entries,
+        _ => return Err(Error::InvalidConfig(String::from("the file needs at least two [[config]] tables"))),
+    };
+    let mut configs: Vec<(String, Vec<String>)> = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let entry = entry.as_table().ok_or_else(|| Error::InvalidConfig(String::from("every config has to be a table")))?;
+        let mut args = defaults.clone();
+        let mut name = format!("config {}", index + 1);
+        for (key, value) in entry {
+            match (key.as_str(), value) {
+                ("name", toml::Value::String(value)) => name = value.clone(),
+                _ => push_option(&mut args, key, value)?,
+            }
+        }
+        if configs.iter().any(|(other, _)| *other == name) {
+            return Err(Error::InvalidConfig(format!("two configs are named {}", name)));
+        }
+        configs.push((name, args));
+    }
+    return Ok(Tournament { configs, maps, seeds, evaluations });
+}
+
+fn push_option(args: &mut Vec<String>, key: &str, value: &toml::Value) -> Result<(), Error> {
+    if RESERVED.contains(&key.replace('-', "_").as_str()) {
+        return Err(Error::InvalidConfig(format!("{} is set by the tournament", key)));
+    }
+    return cli::push_option(args, key, value);
+}
+
+impl Tournament {
+    // Every configuration next to each other for every map and seed, so runs at the same time compare fairly
+    pub fn jobs(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = Vec::with_capacity(self.maps.len() * self.seeds.len() * self.configs.len());
+        for map in &self.maps {
+            for seed in &self.seeds {
+                for (name, args) in &self.configs {
+                    let mut args = args.clone();
+                    if let Some(map) = map {
+                        args.extend([String::from("--map"), map.clone()]);
+                    }
+                    // Until the budget is spent even after a solution, so every run gets the same one
+                    args.extend([String::from("--seed"), seed.to_string(), String::from("--max-evaluations"),
+                                 self.evaluations.to_string(), String::from("--generations"), u32::MAX.to_string(),
+                                 String::from("--until"), String::from("budget")]);
+                    jobs.push(Job {
+                        name: format!("{} on {} seed {}", name, map_name(map), seed),
+                        config: name.clone(),
+                        args,
+                    });
+                }
+            }
+        }
+        return jobs;
+    }
+
+    // A configuration wins a map with the best mean best fitness over the seeds, ties are wins for each.
+    // The fitness is normalized per map from the worst (0) to the best (1) run, so maps of any fitness scale weigh the same.
+    // Configurations are ranked by wins, then by the mean normalized fitness.
+    pub fn print_report(&self, results: &[JobResult]) {
+        let configs = self.configs.len();
+        let fitness = |result: &JobResult| result.summary.as_ref().and_then(|summary| summary.pointer("/best/fitness")?.as_f64());
+        let mut wins: Vec<u32> = vec![0; configs];
+        let mut normalized: Vec<Vec<f64>> = vec![Vec::new(); configs];
+        let mut solved: Vec<usize> = vec![0; configs];
+        let mut evaluations: Vec<Vec<u64>> = vec![Vec::new(); configs];
+        let names: Vec<&str> = self.configs.iter().map(|(name, _)| name.as_str()).collect();
+        let map_width = self.maps.iter().map(|map| map_name(map).chars().count()).max().unwrap_or(0).max(3);
+        let width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0).max(10);
+        let seeds: Vec<String> = self.seeds.iter().map(u64::to_string).collect();
+        println!("Mean best fitness after {} evaluations per run, seeds {}:", self.evaluations, seeds.join(", "));
+        print!("{:<map_width$}", "Map", map_width = map_width);
+        for name in &names {
+            print!("  {:>width$}", name, width = width);
+        }
+        println!("  Winner");
+        for (map_index, map) in self.maps.iter().enumerate() {
+            let runs = &results[map_index * self.seeds.len() * configs..(map_index + 1) * self.seeds.len() * configs];
+            let values: Vec<f64> = runs.iter().filter_map(fitness).collect();
+            let lowest = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let highest = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let mut means: Vec<Option<f64>> = Vec::with_capacity(configs);
+            for config in 0..configs {
+                let config_runs: Vec<&JobResult> = runs.iter().skip(config).step_by(configs).collect();
+                solved[config] += config_runs.iter().filter(|result| result.solved()).count();
+                evaluations[config].extend(config_runs.iter()
+                    .filter_map(|result| result.summary.as_ref()?.get("evaluations")?.as_u64()));
+                let values: Vec<f64> = config_runs.iter().filter_map(|result| fitness(result)).collect();
+                normalized[config].extend(values.iter().map(|value| if highest > lowest { (value - lowest) / (highest - lowest) } else { 1.0 }));
+                means.push(Some(values.iter().sum::<f64>() / values.len() as f64).filter(|_| !values.is_empty()));
+            }
+            let best = means.iter().flatten().copied().fold(f64::NEG_INFINITY, f64::max);
+            let winners: Vec<&str> = (0..configs).filter(|&config| means[config] == Some(best)).map(|config| names[config]).collect();
+            for config in 0..configs {
+                if means[config] == Some(best) {
+                    wins[config] += 1;
+                }
+            }
+            print!("{:<map_width$}", map_name(map), map_width = map_width);
+            for mean in &means {
+                print!("  {:>width$}", mean.map_or_else(|| String::from("-"), |mean| format!("{:.4}", mean)), width = width);
+            }
+            println!("  {}", if winners.is_empty() { String::from("-") } else { winners.join(", ") });
+        }
+
+        let mean_normalized: Vec<Option<f64>> = normalized.iter()
+            .map(|values| Some(values.iter().sum::<f64>() / values.len() as f64).filter(|_| !values.is_empty()))
+            .collect();
+        let mut ranking: Vec<usize> = (0..configs).collect();
+        ranking.sort_by(|&a, &b| wins[b].cmp(&wins[a])
+            .then(mean_normalized[b].unwrap_or(f64::NEG_INFINITY).total_cmp(&mean_normalized[a].unwrap_or(f64::NEG_INFINITY))));
+        let runs = self.maps.len() * self.seeds.len();
+        println!();
+        // Mean evaluations a run actually used, the budget unless a run was cut short
+        println!("{:>4}  {:<width$}  {:>4}  {:>23}  {:>7}  {:>11}", "Rank", "Config", "Wins", "Mean normalized fitness", "Solved",
+                 "Evaluations", width = width);
+        for (rank, config) in ranking.into_iter().enumerate() {
+            let mean_evaluations: Option<f64> = Some(evaluations[config].iter().sum::<u64>() as f64 / evaluations[config].len() as f64)
+                .filter(|_| !evaluations[config].is_empty());
+            println!("{:>4}  {:<width$}  {:>4}  {:>23}  {:>7}  {:>11}", rank + 1, names[config], wins[config],
+                     mean_normalized[config].map_or_else(|| String::from("-"), |mean| format!("{:.4}", mean)),
+                     format!("{}/{}", solved[config], runs),
+                     mean_evaluations.map_or_else(|| String::from("-"), |mean| format!("{:.0}", mean)), width = width);
+        }
+    }
+}
+
+fn map_name(map: &Option<String>) -> &str {
+    return map.as_deref().unwrap_or("built-in");
+}
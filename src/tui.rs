@@ -0,0 +1,197 @@
+use crate::core::Chromosome;
+use crate::map::GameArea;
+use crate::stats::GenerationStats;
+
+// What the search does after the dashboard was updated
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+// State of the search shown on the dashboard
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub struct Snapshot<'a> {
+    pub generation: u32,
+    // None once the search goes on past the target generation
+    pub target_generations: Option<u32>,
+    pub best: Option<&'a Chromosome>,
+    pub stats: &'a GenerationStats,
+    pub evaluations_per_second: f64,
+}
+
+#[cfg(feature = "tui")]
+pub use dashboard::Dashboard;
+
+#[cfg(feature = "tui")]
+mod dashboard {
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Color, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Paragraph, Sparkline};
+    use ratatui::DefaultTerminal;
+
+    use super::{Control, GameArea, Snapshot};
+    use crate::core::{AREA_TILE_PLAYER, AREA_TILE_TREASURE};
+
+    // Redrawing every generation would slow the search down
+    const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+    // Path animation speed in moves per second
+    const MOVES_PER_SECOND: f64 = 8.0;
+    const HISTORY_LENGTH: usize = 512;
+
+    pub struct Dashboard {
+        terminal: DefaultTerminal,
+        game_area: GameArea,
+        last_draw: Instant,
+        paused: bool,
+        // Best fitness of every generation in thousandths
+        history: Vec<u64>,
+        trajectory: Vec<(usize, usize)>,
+        animation_start: Instant,
+    }
+
+    impl Dashboard {
+        pub fn start(game_area: &GameArea) -> Result<Dashboard, String> {
+            return Ok(Dashboard {
+                terminal: ratatui::try_init().map_err(|e| e.to_string())?,
+                game_area: game_area.clone(),
+                last_draw: Instant::now() - FRAME_INTERVAL,
+                paused: false,
+                history: Vec::new(),
+                trajectory: Vec::new(),
+                animation_start: Instant::now(),
+            });
+        }
+
+        // Path of the new best chromosome, the animation starts over
+        pub fn set_best(&mut self, trajectory: Vec<(usize, usize)>) {
+            self.trajectory = trajectory;
+            self.animation_start = Instant::now();
+        }
+
+        pub fn update(&mut self, snapshot: &Snapshot) -> Control {
+            self.history.push((snapshot.stats.best_fitness.max(0.0) * 1000.0) as u64);
+            if self.history.len() > HISTORY_LENGTH {
+                self.history.remove(0);
+            }
+            if self.last_draw.elapsed() < FRAME_INTERVAL {
+                return Control::Continue;
+            }
+            loop {
+                self.draw(snapshot);
+                self.last_draw = Instant::now();
+                let timeout = if self.paused { FRAME_INTERVAL } else { Duration::ZERO };
+                while event::poll(timeout).unwrap_or(false) {
+                    match event::read() {
+                        Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                            KeyCode::Char('q') | KeyCode::Esc => return Control::Stop,
+                            KeyCode::Char('p') | KeyCode::Char(' ') => self.paused = !self.paused,
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                }
+                if !self.paused {
+                    return Control::Continue;
+                }
+            }
+        }
+
+        fn draw(&mut self, snapshot: &Snapshot) {
+            let moves = (self.animation_start.elapsed().as_secs_f64() * MOVES_PER_SECOND) as usize;
+            // The animation pauses on the final position for a while before it repeats
+            let shown = moves % (self.trajectory.len() + MOVES_PER_SECOND as usize).max(1);
+            let walked = &self.trajectory[..(shown + 1).min(self.trajectory.len())];
+            let map = map_lines(&self.game_area, walked);
+
+            let mut status: Vec<Line> = vec![
+                Line::from(match snapshot.target_generations {
+                    Some(target) => format!("Generation {}/{}", snapshot.generation, target),
+                    None => format!("Generation {}", snapshot.generation),
+                }),
+                Line::from(format!("{:.0} evaluations/s", snapshot.evaluations_per_second)),
+                Line::from(""),
+            ];
+            if let Some(best) = snapshot.best {
+                status.push(Line::from(format!("Best fitness: {:.4}", best.fitness)));
+                status.push(Line::from(format!("Treasures: {}, Steps: {}, Iterations: {}",
+                                               best.found_treasures, best.steps.len(), best.iterations)));
+            }
+            let stats = snapshot.stats;
+            status.push(Line::from(format!("Population: best {:.4}, mean {:.4}, median {:.4}, worst {:.4}, std dev {:.4}",
+                                           stats.best_fitness, stats.mean_fitness, stats.median_fitness, stats.worst_fitness,
+                                           stats.fitness_std_dev)));
+            status.push(Line::from(format!("Diversity: {:.2} bits, best treasures: {}", stats.diversity, stats.best_treasures)));
+            if self.paused {
+                status.push(Line::from(""));
+                status.push(Line::from(Span::styled("Paused", Style::default().fg(Color::Yellow))));
+            }
+
+            let history = &self.history;
+            let map_width = (self.game_area.width() * 2 + 2) as u16;
+            self.terminal.draw(|frame| {
+                let [main, footer] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+                let [left, right] = Layout::horizontal([Constraint::Length(map_width.max(12)), Constraint::Min(0)]).areas(main);
+                let [top, bottom] = Layout::vertical([Constraint::Length(status.len() as u16 + 2), Constraint::Min(3)]).areas(right);
+                frame.render_widget(Paragraph::new(map).block(Block::bordered().title("Best path")), left);
+                frame.render_widget(Paragraph::new(status).block(Block::bordered().title("Search")), top);
+                let visible = history.len().saturating_sub(bottom.width.saturating_sub(2) as usize);
+                frame.render_widget(Sparkline::default()
+                                        .block(Block::bordered().title("Best fitness"))
+                                        .data(&history[visible..])
+                                        .style(Style::default().fg(Color::Cyan)), bottom);
+                frame.render_widget(Paragraph::new("p/space: pause, q/esc: stop"), footer);
+            }).ok();
+        }
+    }
+
+    impl Drop for Dashboard {
+        fn drop(&mut self) {
+            ratatui::restore();
+        }
+    }
+
+    fn map_lines(game_area: &GameArea, walked: &[(usize, usize)]) -> Vec<Line<'static>> {
+        let position = walked.last().copied();
+        return game_area.rows().enumerate().map(|(y, row)| {
+            Line::from(row.iter().enumerate().map(|(x, &tile)| {
+                let visited = walked.contains(&(x, y));
+                let (glyph, color) = if position == Some((x, y)) {
+                    ("@ ", Color::Green)
+                } else if tile == AREA_TILE_TREASURE && visited {
+                    ("$ ", Color::DarkGray)
+                } else if tile == AREA_TILE_TREASURE {
+                    ("$ ", Color::Yellow)
+                } else if tile == AREA_TILE_PLAYER {
+                    ("P ", Color::Green)
+                } else if visited {
+                    ("· ", Color::Blue)
+                } else {
+                    (". ", Color::DarkGray)
+                };
+                return Span::styled(glyph, Style::default().fg(color));
+            }).collect::<Vec<Span>>())
+        }).collect();
+    }
+}
+
+// Without the feature the dashboard cannot be started
+#[cfg(not(feature = "tui"))]
+pub struct Dashboard;
+
+#[cfg(not(feature = "tui"))]
+impl Dashboard {
+    pub fn start(_game_area: &GameArea) -> Result<Dashboard, String> {
+        return Err(String::from("the dashboard requires the \"tui\" feature"));
+    }
+
+    pub fn set_best(&mut self, _trajectory: Vec<(usize, usize)>) {}
+
+    pub fn update(&mut self, _snapshot: &Snapshot) -> Control {
+        return Control::Continue;
+    }
+}
@@ -0,0 +1,115 @@
+use ::core::fmt;
+
+use crate::core::INSTR;
+use crate::instruction::Instruction;
+use crate::vm::InstructionSet;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    InvalidLength { expected: usize, actual: usize },
+    JumpOutOfBounds { index: usize, instruction: INSTR, target: usize },
+    MissingOperand { index: usize, instruction: INSTR },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidLength { expected, actual } => {
+                write!(f, "genome has {} instructions, expected {}", actual, expected)
+            }
+            VerifyError::JumpOutOfBounds { index, instruction, target } => {
+                write!(f, "instruction {} ({:#04x}): jump target {} is outside of the program", index, instruction, target)
+            }
+            VerifyError::MissingOperand { index, instruction } => {
+                write!(f, "instruction {} ({:#04x}): missing operand at the end of the program", index, instruction)
+            }
+        }
+    }
+}
+
+impl ::core::error::Error for VerifyError {}
+
+// Walks the program linearly, the byte following an extended instruction is treated as its operand.
+// Addresses of memory cells are not checked, the machine wraps them around the memory size; a jump past the end would
+// end the program instead of running it from there.
+pub fn verify_genome(genes: &[INSTR], instruction_set: InstructionSet, program_size: usize) -> Result<(), VerifyError> {
+    if genes.len() != program_size {
+        return Err(VerifyError::InvalidLength { expected: program_size, actual: genes.len() });
+    }
+
+    let mut index: usize = 0;
+    while index < genes.len() {
+        let instruction: INSTR = genes[index];
+        match Instruction::from_byte(instruction, instruction_set) {
+            Instruction::Inc(_) | Instruction::Dec(_) | Instruction::Move(_) => {}
+            Instruction::Jump(target) => {
+                if usize::from(target) >= program_size {
+                    return Err(VerifyError::JumpOutOfBounds { index, instruction, target: usize::from(target) });
+                }
+            }
+            Instruction::Rand | Instruction::Add(_) | Instruction::Sub(_) => {
+                if index + 1 >= genes.len() {
+                    return Err(VerifyError::MissingOperand { index, instruction });
+                }
+                index += 1;
+            }
+        }
+        index += 1;
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use crate::core::Direction;
+
+    const SETS: [InstructionSet; 2] = [InstructionSet::Classic, InstructionSet::Extended];
+
+    #[test]
+    fn addresses_wrap_like_the_machine() {
+        // INC 6 and DEC 7 on a memory of 4 cells change cells 2 and 3, see vm::tests::inc_and_dec_wrap_around
+        let genes = vec![Instruction::Inc(6).to_byte(), Instruction::Dec(7).to_byte(), 0, 0];
+        for instruction_set in SETS {
+            assert_eq!(verify_genome(&genes, instruction_set, 4), Ok(()));
+        }
+        // The operand of an extended instruction wraps as well
+        let genes = vec![Instruction::Add(1).to_byte(), 63, 0, 0];
+        assert_eq!(verify_genome(&genes, InstructionSet::Extended, 4), Ok(()));
+    }
+
+    #[test]
+    fn jumps_stay_within_the_program() {
+        for instruction_set in SETS {
+            let genes = vec![Instruction::Jump(3).to_byte(), 0, 0, 0];
+            assert_eq!(verify_genome(&genes, instruction_set, 4), Ok(()));
+            let genes = vec![0, Instruction::Jump(4).to_byte(), 0, 0];
+            assert_eq!(verify_genome(&genes, instruction_set, 4),
+                       Err(VerifyError::JumpOutOfBounds { index: 1, instruction: Instruction::Jump(4).to_byte(), target: 4 }));
+        }
+    }
+
+    #[test]
+    fn rejects_a_wrong_length() {
+        for instruction_set in SETS {
+            assert_eq!(verify_genome(&[0; 3], instruction_set, 4), Err(VerifyError::InvalidLength { expected: 4, actual: 3 }));
+            assert_eq!(verify_genome(&[0; 5], instruction_set, 4), Err(VerifyError::InvalidLength { expected: 4, actual: 5 }));
+        }
+    }
+
+    #[test]
+    fn operands_of_the_extended_set() {
+        let add = Instruction::Add(3).to_byte();
+        // Its operand byte is not decoded, a jump target of 63 is fine there
+        let genes = vec![add, Instruction::Jump(63).to_byte(), 0, 0];
+        assert_eq!(verify_genome(&genes, InstructionSet::Extended, 4), Ok(()));
+        // The classic set decodes the same bytes as a move and an out of bounds jump
+        assert_eq!(Instruction::from_byte(add, InstructionSet::Classic), Instruction::Move(Direction::Down));
+        assert!(matches!(verify_genome(&genes, InstructionSet::Classic, 4), Err(VerifyError::JumpOutOfBounds { index: 1, .. })));
+
+        let genes = vec![0, 0, 0, add];
+        assert_eq!(verify_genome(&genes, InstructionSet::Extended, 4), Err(VerifyError::MissingOperand { index: 3, instruction: add }));
+        assert_eq!(verify_genome(&genes, InstructionSet::Classic, 4), Ok(()));
+    }
+}
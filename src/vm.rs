@@ -0,0 +1,507 @@
+use ::core::mem;
+use alloc::{format, string::{String, ToString}, vec::Vec};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::core::{self, Position, AREA_TILE_NOTHING, INSTR};
+use crate::instruction::{self, Instruction};
+use crate::map::GameArea;
+
+// Read-only cells at the end of memory refreshed before every instruction, offsets from the first input cell
+pub const INPUT_PLAYER_X: usize = 0;
+pub const INPUT_PLAYER_Y: usize = 1;
+pub const INPUT_REMAINING_TREASURES: usize = 2;
+pub const INPUT_TILE: usize = 3;
+pub const INPUT_CELLS: usize = 4;
+
+pub const DEFAULT_ITERATION_LIMIT: u32 = 500;
+// Addresses are six bits wide
+pub const MAX_MEMORY_SIZE: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InstructionSet {
+    Classic,
+    Extended,
+}
+
+impl InstructionSet {
+    pub fn parse(text: &str) -> Option<InstructionSet> {
+        return match text.to_ascii_lowercase().as_str() {
+            "classic" | "0" => Some(InstructionSet::Classic),
+            "extended" | "1" => Some(InstructionSet::Extended),
+            _ => None,
+        };
+    }
+}
+
+// What happens when a move would leave the map
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionPolicy {
+    Terminate,
+    Block,
+    Wrap,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Termination {
+    AllTreasures,
+    IterationLimit,
+    MoveLimit,
+    EndOfProgram,
+    LeftMap,
+    MissingOperand,
+    // Stopped at move_bound, the run could not have beaten the best solution any more
+    Bounded,
+}
+
+#[derive(Clone, Debug)]
+pub struct VmConfig {
+    pub iteration_limit: u32,
+    pub memory_size: usize,
+    pub collision_policy: CollisionPolicy,
+    pub instruction_set: InstructionSet,
+    pub max_moves: u32,
+    // Moves after which the evaluation is given up, set for single evaluations by Vm::bounded
+    pub move_bound: u32,
+    pub memory_mapped_inputs: bool,
+    pub record_trajectory: bool,
+    // Seed of the random stream used by the RAND instruction, identical for every run
+    pub seed: u64,
+}
+
+impl VmConfig {
+    pub fn builder() -> VmConfigBuilder {
+        return VmConfigBuilder { config: VmConfig::default() };
+    }
+}
+
+impl Default for VmConfig {
+    fn default() -> Self {
+        return VmConfig {
+            iteration_limit: DEFAULT_ITERATION_LIMIT,
+            memory_size: MAX_MEMORY_SIZE,
+            collision_policy: CollisionPolicy::Terminate,
+            instruction_set: InstructionSet::Classic,
+            max_moves: u32::MAX,
+            move_bound: u32::MAX,
+            memory_mapped_inputs: false,
+            record_trajectory: false,
+            seed: 0,
+        };
+    }
+}
+
+pub struct VmConfigBuilder {
+    config: VmConfig,
+}
+
+impl VmConfigBuilder {
+    pub fn iteration_limit(mut self, iteration_limit: u32) -> Self {
+        self.config.iteration_limit = iteration_limit;
+        return self;
+    }
+
+    pub fn memory_size(mut self, memory_size: usize) -> Self {
+        self.config.memory_size = memory_size.clamp(1, MAX_MEMORY_SIZE);
+        return self;
+    }
+
+    pub fn collision_policy(mut self, collision_policy: CollisionPolicy) -> Self {
+        self.config.collision_policy = collision_policy;
+        return self;
+    }
+
+    pub fn instruction_set(mut self, instruction_set: InstructionSet) -> Self {
+        self.config.instruction_set = instruction_set;
+        return self;
+    }
+
+    pub fn max_moves(mut self, max_moves: u32) -> Self {
+        self.config.max_moves = max_moves;
+        return self;
+    }
+
+    pub fn memory_mapped_inputs(mut self, memory_mapped_inputs: bool) -> Self {
+        self.config.memory_mapped_inputs = memory_mapped_inputs;
+        return self;
+    }
+
+    pub fn record_trajectory(mut self, record_trajectory: bool) -> Self {
+        self.config.record_trajectory = record_trajectory;
+        return self;
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        return self;
+    }
+
+    pub fn build(self) -> VmConfig {
+        return self.config;
+    }
+}
+
+// State of the machine passed to the tracer before an instruction is executed
+pub struct VmState<'a> {
+    pub iteration: u32,
+    pub instruction_index: usize,
+    pub memory: &'a [u8],
+    pub player: Position,
+    pub found_treasures: u32,
+    pub steps: &'a str,
+}
+
+pub struct VmResult {
+    pub iterations: u32,
+    pub found_treasures: u32,
+    pub steps: String,
+    pub termination: Termination,
+    // Last position inside of the map
+    pub player: Position,
+    // Positions of collected treasures in the order they were found
+    pub collected_treasures: Vec<(usize, usize)>,
+    // Number of distinct memory cells executed as instructions
+    pub executed_instructions: u32,
+    // Every position of the player starting with the initial one, empty unless recorded
+    pub trajectory: Vec<(usize, usize)>,
+}
+
+pub struct Vm {
+    config: VmConfig,
+}
+
+// Buffers of a run kept for the next ones, so that evaluating many programs does not allocate them every time
+#[derive(Debug, Default)]
+pub struct Scratch {
+    memory: Vec<u8>,
+    collected: Vec<u64>,
+    steps: String,
+    collected_treasures: Vec<(usize, usize)>,
+    trajectory: Vec<(usize, usize)>,
+}
+
+impl Scratch {
+    pub fn new() -> Scratch {
+        return Scratch::default();
+    }
+
+    // Takes back the buffers of a result which is no longer needed
+    pub fn recycle(&mut self, result: VmResult) {
+        self.steps = result.steps;
+        self.collected_treasures = result.collected_treasures;
+        self.trajectory = result.trajectory;
+    }
+}
+
+impl Vm {
+    pub fn new(config: VmConfig) -> Vm {
+        return Vm { config };
+    }
+
+    pub fn config(&self) -> &VmConfig {
+        return &self.config;
+    }
+
+    // Same machine with a different random stream
+    pub fn reseeded(&self, seed: u64) -> Vm {
+        let mut config = self.config.clone();
+        config.seed = seed;
+        return Vm { config };
+    }
+
+    // Same machine giving up a run at its move bound, u32::MAX for none
+    pub fn bounded(&self, move_bound: u32) -> Vm {
+        let mut config = self.config.clone();
+        config.move_bound = move_bound;
+        return Vm { config };
+    }
+
+    pub fn run(&self, genome: &[INSTR], game_area: &GameArea) -> VmResult {
+        return self.execute(genome, game_area, &mut Scratch::new(), |_| {});
+    }
+
+    // Same as run with the buffers of the scratch
+    pub fn run_in(&self, genome: &[INSTR], game_area: &GameArea, scratch: &mut Scratch) -> VmResult {
+        return self.execute(genome, game_area, scratch, |_| {});
+    }
+
+    // Same as run, the tracer is called with the machine state before every executed instruction
+    pub fn run_traced<F: FnMut(&VmState)>(&self, genome: &[INSTR], game_area: &GameArea, tracer: F) -> VmResult {
+        return self.execute(genome, game_area, &mut Scratch::new(), tracer);
+    }
+
+    fn execute<F: FnMut(&VmState)>(&self, genome: &[INSTR], game_area: &GameArea, scratch: &mut Scratch, mut tracer: F) -> VmResult {
+        let mut machine_memory: Vec<u8> = mem::take(&mut scratch.memory);
+        machine_memory.clear();
+        machine_memory.extend_from_slice(genome);
+        machine_memory.resize(self.config.memory_size, 0);
+        let mut run = RunState::new(&self.config, game_area, scratch);
+        let termination = loop {
+            if let Some(termination) = run.stopped(&self.config, &machine_memory) {
+                break termination;
+            }
+            run.write_inputs(&self.config, &mut machine_memory);
+            tracer(&VmState {
+                iteration: run.iterations,
+                instruction_index: run.instruction_index,
+                memory: &machine_memory,
+                player: run.player,
+                found_treasures: run.found_treasures,
+                steps: &run.steps,
+            });
+            let decoded = Instruction::from_byte(machine_memory[run.instruction_index], self.config.instruction_set);
+            if let Some(termination) = run.execute(decoded, &self.config, &mut machine_memory) {
+                break termination;
+            }
+        };
+        scratch.memory = machine_memory;
+        return run.finish(termination, scratch);
+    }
+}
+
+// Everything of a run of a program but its memory, which lockstep::run_batch keeps together for all of its programs.
+// Vm::execute and run_batch step a program only through it, so that both give the same results.
+pub(crate) struct RunState<'m> {
+    game_area: &'m GameArea,
+    treasures: u32,
+    pub(crate) player: Position,
+    pub(crate) instruction_index: usize,
+    pub(crate) iterations: u32,
+    pub(crate) found_treasures: u32,
+    moves: u32,
+    // One bit per treasure index, the map itself stays untouched
+    collected: Vec<u64>,
+    // One bit per memory cell
+    executed: u64,
+    random_stream: Pcg64,
+    steps: String,
+    collected_treasures: Vec<(usize, usize)>,
+    trajectory: Vec<(usize, usize)>,
+}
+
+impl<'m> RunState<'m> {
+    // At the start of the program, with the buffers of the scratch
+    pub(crate) fn new(config: &VmConfig, game_area: &'m GameArea, scratch: &mut Scratch) -> RunState<'m> {
+        let player = game_area.player();
+        let treasures = game_area.treasure_count();
+        let mut collected: Vec<u64> = mem::take(&mut scratch.collected);
+        collected.clear();
+        collected.resize((treasures as usize).div_ceil(64), 0);
+        let mut steps: String = mem::take(&mut scratch.steps);
+        steps.clear();
+        let mut collected_treasures: Vec<(usize, usize)> = mem::take(&mut scratch.collected_treasures);
+        collected_treasures.clear();
+        let mut trajectory: Vec<(usize, usize)> = mem::take(&mut scratch.trajectory);
+        trajectory.clear();
+        if config.record_trajectory {
+            trajectory.push(player.tile());
+        }
+        return RunState {
+            game_area,
+            treasures,
+            player,
+            instruction_index: 0,
+            iterations: 0,
+            found_treasures: 0,
+            moves: 0,
+            collected,
+            executed: 0,
+            random_stream: Pcg64::seed_from_u64(config.seed),
+            steps,
+            collected_treasures,
+            trajectory,
+        };
+    }
+
+    // Why the program ends before its next instruction, None while it goes on
+    pub(crate) fn stopped(&self, config: &VmConfig, memory: &[u8]) -> Option<Termination> {
+        if self.found_treasures >= self.treasures {
+            return Some(Termination::AllTreasures);
+        }
+        if self.iterations >= config.iteration_limit {
+            return Some(Termination::IterationLimit);
+        }
+        if self.instruction_index >= memory.len() {
+            return Some(Termination::EndOfProgram);
+        }
+        return None;
+    }
+
+    // Refreshes the read-only input cells at the end of the memory, if the machine has them
+    pub(crate) fn write_inputs(&self, config: &VmConfig, memory: &mut [u8]) {
+        if !config.memory_mapped_inputs {
+            return;
+        }
+        let input_start = input_start(config, memory.len());
+        let mut inputs: [u8; INPUT_CELLS] = [0; INPUT_CELLS];
+        inputs[INPUT_PLAYER_X] = self.player.x.clamp(0, 255) as u8;
+        inputs[INPUT_PLAYER_Y] = self.player.y.clamp(0, 255) as u8;
+        inputs[INPUT_REMAINING_TREASURES] = (self.treasures - self.found_treasures).min(255) as u8;
+        let (x, y) = self.player.tile();
+        inputs[INPUT_TILE] = match self.game_area.treasure_index(x, y) {
+            Some(index) if self.collected[index / 64] & (1 << (index % 64)) != 0 => AREA_TILE_NOTHING,
+            _ => self.game_area.get(x, y),
+        };
+        let cells = memory.len() - input_start;
+        memory[input_start..].copy_from_slice(&inputs[..cells]);
+    }
+
+    // Executes the instruction decoded from the byte at the instruction index, Some if it ends the program
+    pub(crate) fn execute(&mut self, decoded: Instruction, config: &VmConfig, memory: &mut [u8]) -> Option<Termination> {
+        let memory_size = memory.len();
+        // Writes at or above this address are ignored
+        let input_start = input_start(config, memory_size);
+        self.executed |= 1 << self.instruction_index;
+        let mut jump: bool = false;
+        let mut length: usize = 1;
+        match decoded {
+            Instruction::Inc(address) => {
+                let address: usize = usize::from(address) % memory_size;
+                if address < input_start {
+                    memory[address] = memory[address].wrapping_add(1);
+                }
+            }
+            Instruction::Dec(address) => {
+                let address: usize = usize::from(address) % memory_size;
+                if address < input_start {
+                    memory[address] = memory[address].wrapping_sub(1);
+                }
+            }
+            Instruction::Jump(target) => {
+                self.instruction_index = usize::from(target);
+                jump = true;
+            }
+            Instruction::Rand | Instruction::Add(_) | Instruction::Sub(_) => {
+                // Extended operations take the target address from the following byte
+                if self.instruction_index + 1 >= memory_size {
+                    return Some(Termination::MissingOperand);
+                }
+                let target: usize = instruction::operand_address(memory[self.instruction_index + 1]) % memory_size;
+                length = 2;
+                match decoded {
+                    _ if target >= input_start => {}
+                    Instruction::Rand => {
+                        memory[target] = self.random_stream.gen();
+                    }
+                    Instruction::Add(immediate) => {
+                        memory[target] = memory[target].wrapping_add(immediate);
+                    }
+                    Instruction::Sub(immediate) => {
+                        memory[target] = memory[target].wrapping_sub(immediate);
+                    }
+                    _ => {}
+                }
+            }
+            Instruction::Move(direction) => {
+                // Move (print)
+                if self.moves >= config.max_moves {
+                    return Some(Termination::MoveLimit);
+                }
+                if self.moves >= config.move_bound {
+                    return Some(Termination::Bounded);
+                }
+                self.moves += 1;
+                let mut new_position = self.player.step(direction);
+                self.steps.push(direction.glyph());
+                if !self.game_area.contains(new_position) {
+                    match config.collision_policy {
+                        CollisionPolicy::Terminate => return Some(Termination::LeftMap),
+                        CollisionPolicy::Block => new_position = self.player,
+                        CollisionPolicy::Wrap => new_position = new_position.wrapped(self.game_area.width(), self.game_area.height()),
+                    }
+                }
+                self.player = new_position;
+                let (x, y) = self.player.tile();
+                if config.record_trajectory {
+                    self.trajectory.push((x, y));
+                }
+                if let Some(index) = self.game_area.treasure_index(x, y) {
+                    if self.collected[index / 64] & (1 << (index % 64)) == 0 {
+                        self.collected[index / 64] |= 1 << (index % 64);
+                        self.found_treasures += 1;
+                        self.collected_treasures.push((x, y));
+                    }
+                }
+            }
+        }
+        self.iterations += 1;
+        if !jump {
+            self.instruction_index += length;
+        }
+        return None;
+    }
+
+    // The result, the buffers it does not keep go back to the scratch
+    pub(crate) fn finish(self, termination: Termination, scratch: &mut Scratch) -> VmResult {
+        scratch.collected = self.collected;
+        return VmResult {
+            iterations: self.iterations,
+            found_treasures: self.found_treasures,
+            steps: self.steps,
+            termination,
+            player: self.player,
+            collected_treasures: self.collected_treasures,
+            executed_instructions: self.executed.count_ones(),
+            trajectory: self.trajectory,
+        };
+    }
+}
+
+// First of the read-only input cells, the memory size without them
+fn input_start(config: &VmConfig, memory_size: usize) -> usize {
+    if config.memory_mapped_inputs {
+        return memory_size.saturating_sub(INPUT_CELLS);
+    }
+    return memory_size;
+}
+
+// One line per byte: index, byte, opcode and operand, moves in the given glyphs
+pub fn disassemble(genome: &[INSTR], instruction_set: InstructionSet, step_alphabet: core::StepAlphabet) -> Vec<String> {
+    return genome.iter().enumerate().map(|(index, &byte)| {
+        let instruction = Instruction::from_byte(byte, instruction_set);
+        // Extended operations take the target address from the following byte
+        let target = genome.get(index + 1).map_or(String::from("?"), |&next| instruction::operand_address(next).to_string());
+        let operand = match instruction {
+            Instruction::Inc(address) | Instruction::Dec(address) | Instruction::Jump(address) => address.to_string(),
+            Instruction::Move(direction) => step_alphabet.0[direction.index()].to_string(),
+            Instruction::Rand => format!("[{}]", target),
+            Instruction::Add(immediate) | Instruction::Sub(immediate) => format!("[{}], {}", target, immediate),
+        };
+        return format!("{:02}: {:02x}  {:<4} {}", index, byte, instruction.name(), operand);
+    }).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cell 5 after every instruction of the genome run in order from its start
+    fn cell_5(genome: &[INSTR], instruction_set: InstructionSet) -> Vec<u8> {
+        let game_area = crate::map::parse("P.T").unwrap();
+        let vm = Vm::new(VmConfig::builder().instruction_set(instruction_set).iteration_limit(genome.len() as u32 + 1).build());
+        let mut cells: Vec<u8> = Vec::new();
+        vm.run_traced(genome, &game_area, |state| cells.push(state.memory[5]));
+        return cells[1..].to_vec();
+    }
+
+    #[test]
+    fn inc_and_dec_wrap_around() {
+        let (inc, dec) = (Instruction::Inc(5).to_byte(), Instruction::Dec(5).to_byte());
+        for instruction_set in [InstructionSet::Classic, InstructionSet::Extended] {
+            assert_eq!(cell_5(&[dec, dec, inc, inc, inc], instruction_set), [255, 254, 255, 0, 1]);
+        }
+        // Addresses wrap around the memory
+        let game_area = crate::map::parse("P.T").unwrap();
+        let vm = Vm::new(VmConfig::builder().memory_size(4).iteration_limit(1).build());
+        let mut scratch = Scratch::new();
+        vm.run_in(&[Instruction::Dec(6).to_byte(), 0, 0, 0], &game_area, &mut scratch);
+        assert_eq!(scratch.memory[2], 255);
+    }
+
+    #[test]
+    fn disassembles_dec() {
+        let lines = disassemble(&[Instruction::Dec(5).to_byte()], InstructionSet::Classic, core::StepAlphabet::default());
+        assert_eq!(lines, ["00: 45  DEC  5"]);
+    }
+}
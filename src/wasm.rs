@@ -0,0 +1,88 @@
+// Browser bindings, built with
+// `cargo rustc --lib --release --target wasm32-unknown-unknown --no-default-features --features wasm --crate-type cdylib`
+// and wrapped for JavaScript by wasm-bindgen
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::engine::Engine;
+use crate::error::Error;
+use crate::map::GameArea;
+use crate::spec::EngineSpec;
+use crate::vm::Vm;
+
+fn to_js_error(e: Error) -> JsError {
+    return JsError::new(&e.to_string());
+}
+
+#[wasm_bindgen(js_name = Engine)]
+pub struct WasmEngine {
+    engine: Engine<'static>,
+    // Map of the search, for drawing the best path
+    game_area: GameArea,
+}
+
+#[wasm_bindgen(js_class = Engine)]
+impl WasmEngine {
+    // Takes the same JSON configuration as the C API, e.g. `{"subjects": 50, "seed": 42, "map": "..."}`
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str) -> Result<WasmEngine, JsError> {
+        let spec: EngineSpec = serde_json::from_str(config_json).map_err(|e| JsError::new(&e.to_string()))?;
+        let game_area = spec.game_area().map_err(to_js_error)?;
+        return Ok(WasmEngine { engine: spec.build().map_err(to_js_error)?, game_area });
+    }
+
+    // Steps at most the given number of generations, fewer once every treasure is collected.
+    // Returns the number of stepped generations.
+    pub fn step(&mut self, generations: u32) -> u32 {
+        let mut stepped: u32 = 0;
+        while stepped < generations && !self.engine.solved() {
+            self.engine.step();
+            stepped += 1;
+        }
+        return stepped;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u32 {
+        return self.engine.generation();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solved(&self) -> bool {
+        return self.engine.solved();
+    }
+
+    // Width, height, player and treasures of the map
+    #[wasm_bindgen(js_name = mapJson)]
+    pub fn map_json(&self) -> String {
+        let treasures: Vec<(usize, usize)> = self.game_area.treasures().collect();
+        return json!({
+            "width": self.game_area.width(),
+            "height": self.game_area.height(),
+            "player": self.game_area.player().tile(),
+            "treasures": treasures,
+        }).to_string();
+    }
+
+    // Best chromosome so far with every visited position, null before the first generation
+    #[wasm_bindgen(js_name = bestPathJson)]
+    pub fn best_path_json(&self) -> String {
+        let Some(best) = self.engine.best() else {
+            return String::from("null");
+        };
+        let mut vm_config = self.engine.config().vm.clone();
+        vm_config.record_trajectory = true;
+        let result = Vm::new(vm_config).run(&best.genes, &self.game_area);
+        return json!({
+            "generation": self.engine.generation(),
+            "fitness": best.fitness,
+            "treasures": best.found_treasures,
+            "steps": best.steps,
+            "trajectory": result.trajectory,
+            "collected": result.collected_treasures,
+        }).to_string();
+    }
+}